@@ -25,35 +25,29 @@ mod test_clock {
     use std::{ops::Add, sync::{Arc, Mutex, MutexGuard}, time::{Duration, UNIX_EPOCH}};
     use once_cell::sync::Lazy;
 
-    static mut MOCK_TIME: Lazy<Arc<Mutex<Option<SystemTime>>>> = Lazy::new(||
+    static MOCK_TIME: Lazy<Arc<Mutex<Option<SystemTime>>>> = Lazy::new(||
         Arc::new(Mutex::new(None))
     );
 
     impl Clock {
         pub fn now() -> SystemTime {
-            unsafe {
-                MOCK_TIME
-                    .lock().unwrap()
-                    .to_owned().unwrap_or_else(SystemTime::now)
-            }
+            MOCK_TIME
+                .lock().unwrap()
+                .to_owned().unwrap_or_else(SystemTime::now)
         }
 
         /**
          * Revert clock back to current system time
          */
         pub fn mock_disable() {
-            unsafe {
-                *MOCK_TIME.lock().unwrap() = None;
-            }
+            *MOCK_TIME.lock().unwrap() = None;
         }
 
         /**
          * Keeps mocked time frozen to current system time at time of method call
          */
         pub fn mock_freeze() {
-            unsafe {
-                *MOCK_TIME.lock().unwrap() = Some(SystemTime::now());
-            }
+            *MOCK_TIME.lock().unwrap() = Some(SystemTime::now());
         }
 
         /**
@@ -61,14 +55,12 @@ mod test_clock {
          * If mock time was disabled, advances current system time as new mock time
          */
         pub fn mock_advance(duration: Duration) {
-            unsafe {
-                let mut time = MOCK_TIME.lock().unwrap();
-                *time = Some(
-                    (*time)
-                        .unwrap_or_else(SystemTime::now)
-                        .add(duration)
-                );
-            }
+            let mut time = MOCK_TIME.lock().unwrap();
+            *time = Some(
+                (*time)
+                    .unwrap_or_else(SystemTime::now)
+                    .add(duration)
+            );
         }
 
         /**
@@ -76,9 +68,7 @@ mod test_clock {
          */
         #[allow(dead_code)]
         pub fn mock_set_time(epoch_millis: u64) {
-            unsafe {
-                *MOCK_TIME.lock().unwrap() = Some(UNIX_EPOCH.add(Duration::from_millis(epoch_millis)));
-            }
+            *MOCK_TIME.lock().unwrap() = Some(UNIX_EPOCH.add(Duration::from_millis(epoch_millis)));
         }
     }
 
@@ -87,7 +77,7 @@ mod test_clock {
     /**
      * Only allows one test to use mocked clock at a time to avoid sync issues
      */
-    static mut SESSION_LOCK: Lazy<Arc<Mutex<MockSessionLock>>> = Lazy::new(||
+    static SESSION_LOCK: Lazy<Arc<Mutex<MockSessionLock>>> = Lazy::new(||
         Arc::new(Mutex::new(MockSessionLock))
     );
 
@@ -95,14 +85,14 @@ mod test_clock {
      * Captures lifetime of clock session to avoid tests writing over each's mocked time
      * Use this for any test that relies on SystemTime
      */
-    pub struct MockClockSession<'a>(MutexGuard<'a, MockSessionLock>);
+    pub struct MockClockSession<'a>(#[allow(dead_code)] MutexGuard<'a, MockSessionLock>);
 
     impl <'a> MockClockSession<'a> {
         /**
          * Blocks session if another session is already active 
          */
         pub fn new() -> Self {
-            Self(unsafe { SESSION_LOCK.lock().unwrap() })
+            Self(SESSION_LOCK.lock().unwrap())
         }
     }
 
@@ -0,0 +1,46 @@
+use bytes::Bytes;
+use thiserror::Error;
+
+/**
+ * Errors returned by command handlers
+ * Display output is the Redis compatible error reply, prefixed by its error code
+ */
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RedisError {
+    #[error("ERR unknown command '{0}', with args beginning with: {1}")]
+    UnknownCommand(String, String),
+    #[error("ERR wrong number of arguments for '{0}' command")]
+    WrongArity(String),
+    #[error("WRONGTYPE Operation against a key holding the wrong kind of value")]
+    WrongType,
+    #[error("ERR no such key")]
+    NoSuchKey,
+    #[error("ERR syntax error")]
+    Syntax,
+    #[error("ERR value is not an integer or out of range")]
+    NotInteger,
+    #[error("ERR value is not a valid float")]
+    NotFloat,
+    #[error("ERR increment or decrement would overflow")]
+    Overflow,
+    #[error("ERR invalid expire time in '{0}' command")]
+    InvalidExpireTime(String),
+    #[error("ERR Protocol error: {0}")]
+    Protocol(String),
+    #[error("NOPROTO unsupported protocol version")]
+    NoProto,
+    #[error("WRONGPASS invalid username-password pair or user is disabled.")]
+    WrongPass,
+    #[error("ERR {0}")]
+    Custom(String),
+}
+
+impl RedisError {
+    pub fn unknown_command(command: &Bytes, args: &[Bytes]) -> RedisError {
+        let args_preview = args.iter()
+            .map(|arg| format!("'{}' ", String::from_utf8_lossy(arg)))
+            .collect::<String>();
+
+        RedisError::UnknownCommand(String::from_utf8_lossy(command).into_owned(), args_preview)
+    }
+}
@@ -1,4 +1,8 @@
+// Redis command and option names are upper case acronyms by convention
+#![allow(clippy::upper_case_acronyms)]
+
 mod clock;
+mod error;
 mod resp;
 mod store;
 mod server;
@@ -0,0 +1,724 @@
+use std::ops::RangeInclusive;
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{error::RedisError, resp::{frame::RESPFrame, parser::MAX_BULK_SIZE}};
+
+use super::{canonical_integer, clamp_range, parse_integer, CommandContext, CommandResult, SetCommandFlags};
+
+/**
+ * SETBIT key offset value
+ * Bits are numbered from the most significant bit of the first byte
+ */
+pub fn setbit(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, offset, bit] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let offset = parse_bit_offset(offset)?;
+    let bit = match bit.as_ref() {
+        b"0" => false,
+        b"1" => true,
+        _ => return Err(RedisError::Custom("bit is not an integer or out of range".to_owned()))
+    };
+
+    let value = ctx.store.get_string_buffer_mut(key)?;
+    let byte = offset / 8;
+    if value.len() <= byte {
+        value.resize(byte + 1, 0);
+    }
+
+    let mask = 0x80 >> (offset % 8);
+    let previous = value[byte] & mask != 0;
+    if bit {
+        value[byte] |= mask;
+    } else {
+        value[byte] &= !mask;
+    }
+
+    Ok(RESPFrame::Integer(previous as i64))
+}
+
+/**
+ * GETBIT key offset
+ */
+pub fn getbit(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, offset] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let offset = parse_bit_offset(offset)?;
+
+    let value = ctx.store.get_raw(key)?.unwrap_or_default();
+    let bit = value.get(offset / 8).is_some_and(|byte| byte & (0x80 >> (offset % 8)) != 0);
+
+    Ok(RESPFrame::Integer(bit as i64))
+}
+
+/**
+ * BITCOUNT key [start end [BYTE | BIT]]
+ */
+pub fn bitcount(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let (key, range) = match args {
+        [key] => (key, None),
+        [key, start, end, unit @ ..] if unit.len() <= 1 => (key, Some(parse_bit_range(start, end, unit.first())?)),
+        [_, ..] => return Err(RedisError::Syntax),
+        [] => return Err(ctx.wrong_arity())
+    };
+
+    let value = ctx.store.get_raw(key)?.unwrap_or_default();
+    let bits = match bit_range(&value, range) {
+        Some((start, end)) => count_bits(&value, start, end),
+        None => 0,
+    };
+
+    Ok(RESPFrame::Integer(bits as i64))
+}
+
+/**
+ * BITPOS key bit [start [end [BYTE | BIT]]]
+ * Without an end, a search for 0 treats the string as padded with zeros
+ */
+pub fn bitpos(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, bit, range_args @ ..] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let bit = match parse_integer(bit)? {
+        0 => false,
+        1 => true,
+        _ => return Err(RedisError::Custom("The bit argument must be 1 or 0.".to_owned()))
+    };
+
+    let range = match range_args {
+        [] => None,
+        [start] => Some(parse_bit_range(start, &Bytes::from("-1"), None)?),
+        [start, end, unit @ ..] if unit.len() <= 1 => Some(parse_bit_range(start, end, unit.first())?),
+        _ => return Err(RedisError::Syntax)
+    };
+    let end_given = range_args.len() > 1;
+
+    let value = ctx.store.get_raw(key)?.unwrap_or_default();
+    if value.is_empty() {
+        return Ok(RESPFrame::Integer(if bit { -1 } else { 0 }))
+    }
+
+    let Some((start, end)) = bit_range(&value, range) else {
+        return Ok(RESPFrame::Integer(-1))
+    };
+
+    let position = match find_bit(&value, bit, start, end) {
+        Some(position) => position as i64,
+        None if !bit && !end_given => (end + 1) as i64,
+        None => -1,
+    };
+
+    Ok(RESPFrame::Integer(position))
+}
+
+/**
+ * BITOP <AND | OR | XOR | NOT> destkey key [key ...]
+ * Shorter strings are treated as padded with zeros
+ */
+pub fn bitop(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [operation, destination, keys @ ..] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let operation = operation.to_ascii_uppercase();
+    let combine: fn(u8, u8) -> u8 = match operation.as_slice() {
+        b"AND" => |a, b| a & b,
+        b"OR" => |a, b| a | b,
+        b"XOR" => |a, b| a ^ b,
+        b"NOT" if keys.len() != 1 => {
+            return Err(RedisError::Custom("BITOP NOT must be called with a single source key.".to_owned()))
+        },
+        // NOT has a single source, so it's only ever applied to the first byte
+        b"NOT" => |a, _| !a,
+        _ => return Err(RedisError::Syntax)
+    };
+    let is_unary = operation == b"NOT";
+
+    let values = keys.iter()
+        .map(|key| Ok(ctx.store.get(key)?.unwrap_or_default()))
+        .collect::<Result<Vec<Bytes>, RedisError>>()?;
+    let length = values.iter().map(Bytes::len).max().unwrap_or(0);
+
+    let result: BytesMut = (0..length)
+        .map(|i| {
+            let mut bytes = values.iter().map(|value| value.get(i).copied().unwrap_or(0));
+            let first = bytes.next().unwrap_or(0);
+            if is_unary {
+                combine(first, 0)
+            } else {
+                bytes.fold(first, combine)
+            }
+        })
+        .collect();
+
+    if result.is_empty() {
+        ctx.store.del(destination);
+    } else {
+        ctx.store.set(destination, &result.freeze(), &SetCommandFlags::default());
+    }
+
+    Ok(RESPFrame::Integer(length as i64))
+}
+
+/**
+ * BITFIELD key [GET encoding offset | [OVERFLOW <WRAP | SAT | FAIL>]
+ *   <SET encoding offset value | INCRBY encoding offset increment> ...]
+ */
+pub fn bitfield(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    bitfield_generic(ctx, args, false)
+}
+
+/**
+ * BITFIELD_RO key [GET encoding offset ...]
+ */
+pub fn bitfield_ro(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    bitfield_generic(ctx, args, true)
+}
+
+fn bitfield_generic(ctx: &mut CommandContext, args: &[Bytes], read_only: bool) -> CommandResult {
+    let [key, subcommands @ ..] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    // Every subcommand is validated before any of them run
+    let subcommands = parse_bitfield_subcommands(subcommands, read_only)?;
+
+    let written_length = subcommands.iter()
+        .filter(|subcommand| subcommand.operation != BitfieldOperation::GET)
+        .map(|subcommand| (subcommand.offset + subcommand.encoding.bits as usize - 1) / 8 + 1)
+        .max();
+
+    // Only grows and writes the string in place if some subcommand writes to it
+    let replies = match written_length {
+        Some(length) => {
+            let value = ctx.store.get_string_buffer_mut(key)?;
+            if value.len() < length {
+                value.resize(length, 0);
+            }
+            subcommands.iter().map(|subcommand| run_bitfield_subcommand(subcommand, value)).collect()
+        },
+        None => {
+            let value = ctx.store.get_raw(key)?.unwrap_or_default();
+            subcommands.iter()
+                .map(|subcommand| RESPFrame::Integer(subcommand.encoding.decode(read_field(&value, subcommand.offset, subcommand.encoding.bits))))
+                .collect()
+        },
+    };
+
+    Ok(RESPFrame::Array(replies))
+}
+
+fn run_bitfield_subcommand(subcommand: &BitfieldSubcommand, value: &mut [u8]) -> RESPFrame {
+    let encoding = subcommand.encoding;
+    let current = encoding.decode(read_field(value, subcommand.offset, encoding.bits));
+
+    let reply = match subcommand.operation {
+        BitfieldOperation::GET => return RESPFrame::Integer(current),
+        BitfieldOperation::SET(new) => encoding.overflow(new as i128, subcommand.overflow)
+            .map(|new| (new, current)),
+        BitfieldOperation::INCRBY(increment) => encoding.overflow(current as i128 + increment as i128, subcommand.overflow)
+            .map(|new| (new, new)),
+    };
+
+    match reply {
+        Some((new, reply)) => {
+            write_field(value, subcommand.offset, encoding.bits, new as u64);
+            RESPFrame::Integer(reply)
+        },
+        None => RESPFrame::Null,
+    }
+}
+
+/**
+ * Offsets address at most the 512MB string limit
+ */
+fn parse_bit_offset(offset: &[u8]) -> Result<usize, RedisError> {
+    check_bit_offset(canonical_integer(offset))
+}
+
+fn check_bit_offset(offset: Option<i64>) -> Result<usize, RedisError> {
+    offset
+        .filter(|offset| (0..MAX_BULK_SIZE as i64 * 8).contains(offset))
+        .map(|offset| offset as usize)
+        .ok_or_else(|| RedisError::Custom("bit offset is not an integer or out of range".to_owned()))
+}
+
+/**
+ * Range arguments, and whether they count bits rather than bytes
+ */
+fn parse_bit_range(start: &[u8], end: &[u8], unit: Option<&Bytes>) -> Result<(i64, i64, bool), RedisError> {
+    let is_bit = match unit {
+        None => false,
+        Some(unit) if unit.eq_ignore_ascii_case(b"BYTE") => false,
+        Some(unit) if unit.eq_ignore_ascii_case(b"BIT") => true,
+        Some(_) => return Err(RedisError::Syntax)
+    };
+
+    Ok((parse_integer(start)?, parse_integer(end)?, is_bit))
+}
+
+/**
+ * Inclusive range of bits to scan, or None if it's empty
+ */
+fn bit_range(value: &[u8], range: Option<(i64, i64, bool)>) -> Option<(usize, usize)> {
+    match range {
+        None if value.is_empty() => None,
+        None => Some((0, value.len() * 8 - 1)),
+        Some((start, end, true)) => clamp_range(start, end, value.len() * 8)
+            .map(|range| (*range.start(), *range.end())),
+        Some((start, end, false)) => clamp_range(start, end, value.len())
+            .map(|range| (range.start() * 8, range.end() * 8 + 7)),
+    }
+}
+
+fn count_bits(value: &[u8], start: usize, end: usize) -> usize {
+    let (first, last) = (start / 8, end / 8);
+    let count: u32 = value[first..=last].iter().map(|byte| byte.count_ones()).sum();
+
+    // Exclude bits before the start of the first byte, and after the end of the last byte
+    let before_start = (value[first] as u32 >> (8 - start % 8)).count_ones();
+    let after_end = (value[last] as u32 & (0xFF >> (end % 8 + 1))).count_ones();
+
+    (count - before_start - after_end) as usize
+}
+
+fn find_bit(value: &[u8], bit: bool, start: usize, end: usize) -> Option<usize> {
+    (start / 8..=end / 8).find_map(|index| {
+        let from = if index == start / 8 { start % 8 } else { 0 };
+        let to = if index == end / 8 { end % 8 } else { 7 };
+        let range_mask = ((0xFF >> from) & (0xFF << (7 - to))) as u8;
+
+        let byte = if bit { value[index] } else { !value[index] };
+        let matches = byte & range_mask;
+
+        (matches != 0).then(|| index * 8 + matches.leading_zeros() as usize)
+    })
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BitfieldOverflow {
+    WRAP,
+    SAT,
+    FAIL,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BitfieldOperation {
+    GET,
+    SET(i64),
+    INCRBY(i64),
+}
+
+/**
+ * Signed fields are up to 64 bits wide, unsigned ones up to 63 so they fit in a reply
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BitfieldEncoding {
+    signed: bool,
+    bits: u32,
+}
+
+impl BitfieldEncoding {
+    fn parse(encoding: &[u8]) -> Result<BitfieldEncoding, RedisError> {
+        let (signed, max_bits) = match encoding.first() {
+            Some(b'i') => (true, 64),
+            Some(b'u') => (false, 63),
+            _ => return Err(invalid_bitfield_type())
+        };
+
+        canonical_integer(&encoding[1..])
+            .filter(|bits| (1..=max_bits).contains(bits))
+            .map(|bits| BitfieldEncoding { signed, bits: bits as u32 })
+            .ok_or_else(invalid_bitfield_type)
+    }
+
+    fn range(&self) -> RangeInclusive<i128> {
+        if self.signed {
+            -(1 << (self.bits - 1))..=(1 << (self.bits - 1)) - 1
+        } else {
+            0..=(1 << self.bits) - 1
+        }
+    }
+
+    /**
+     * Interprets the low bits of a field, sign extending signed ones
+     */
+    fn decode(&self, raw: u64) -> i64 {
+        let shift = 64 - self.bits;
+        if self.signed {
+            ((raw << shift) as i64) >> shift
+        } else {
+            ((raw << shift) >> shift) as i64
+        }
+    }
+
+    /**
+     * Fits a value into the field, or None if it overflows with FAIL
+     */
+    fn overflow(&self, value: i128, overflow: BitfieldOverflow) -> Option<i64> {
+        let range = self.range();
+        if range.contains(&value) {
+            return Some(value as i64)
+        }
+
+        match overflow {
+            BitfieldOverflow::WRAP => Some(self.decode(value as u64)),
+            BitfieldOverflow::SAT => Some(value.clamp(*range.start(), *range.end()) as i64),
+            BitfieldOverflow::FAIL => None,
+        }
+    }
+}
+
+struct BitfieldSubcommand {
+    operation: BitfieldOperation,
+    encoding: BitfieldEncoding,
+    offset: usize,
+    overflow: BitfieldOverflow,
+}
+
+/**
+ * OVERFLOW applies to the SET and INCRBY subcommands after it
+ */
+fn parse_bitfield_subcommands(args: &[Bytes], read_only: bool) -> Result<Vec<BitfieldSubcommand>, RedisError> {
+    let mut args = args;
+    let mut overflow = BitfieldOverflow::WRAP;
+    let mut subcommands = vec![];
+
+    loop {
+        let (operation, encoding, offset, rest) = match args {
+            [] => break,
+            [name, encoding, offset, rest @ ..] if name.eq_ignore_ascii_case(b"GET") => {
+                (BitfieldOperation::GET, encoding, offset, rest)
+            },
+            [_, ..] if read_only => {
+                return Err(RedisError::Custom("BITFIELD_RO only supports the GET subcommand".to_owned()))
+            },
+            [name, encoding, offset, value, rest @ ..] if name.eq_ignore_ascii_case(b"SET") => {
+                (BitfieldOperation::SET(parse_integer(value)?), encoding, offset, rest)
+            },
+            [name, encoding, offset, increment, rest @ ..] if name.eq_ignore_ascii_case(b"INCRBY") => {
+                (BitfieldOperation::INCRBY(parse_integer(increment)?), encoding, offset, rest)
+            },
+            [name, mode, rest @ ..] if name.eq_ignore_ascii_case(b"OVERFLOW") => {
+                overflow = match mode.to_ascii_uppercase().as_slice() {
+                    b"WRAP" => BitfieldOverflow::WRAP,
+                    b"SAT" => BitfieldOverflow::SAT,
+                    b"FAIL" => BitfieldOverflow::FAIL,
+                    _ => return Err(RedisError::Custom("Invalid OVERFLOW type specified".to_owned()))
+                };
+                args = rest;
+                continue
+            },
+            _ => return Err(RedisError::Syntax)
+        };
+
+        let encoding = BitfieldEncoding::parse(encoding)?;
+        let offset = parse_bitfield_offset(offset, encoding.bits)?;
+        subcommands.push(BitfieldSubcommand { operation, encoding, offset, overflow });
+        args = rest;
+    }
+
+    Ok(subcommands)
+}
+
+/**
+ * Offsets prefixed with '#' count in multiples of the field width
+ */
+fn parse_bitfield_offset(offset: &[u8], bits: u32) -> Result<usize, RedisError> {
+    let offset = match offset.strip_prefix(b"#") {
+        Some(index) => canonical_integer(index).and_then(|index| index.checked_mul(bits as i64)),
+        None => canonical_integer(offset),
+    };
+
+    check_bit_offset(offset)
+}
+
+fn invalid_bitfield_type() -> RedisError {
+    RedisError::Custom("Invalid bitfield type. Use something like i16 u8. Note that u64 is not supported but i64 is.".to_owned())
+}
+
+/**
+ * Reads bits most significant first into the low bits of the result
+ * Bits past the end of the string read as zero
+ */
+fn read_field(value: &[u8], offset: usize, bits: u32) -> u64 {
+    (offset..offset + bits as usize).fold(0, |field, position| {
+        let bit = value.get(position / 8).map_or(0, |byte| (byte >> (7 - position % 8)) & 1);
+        (field << 1) | bit as u64
+    })
+}
+
+fn write_field(value: &mut [u8], offset: usize, bits: u32, field: u64) {
+    for (i, position) in (offset..offset + bits as usize).enumerate() {
+        let mask = 0x80 >> (position % 8);
+        if (field >> (bits as usize - 1 - i)) & 1 == 1 {
+            value[position / 8] |= mask;
+        } else {
+            value[position / 8] &= !mask;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::{insert_value, integers, interpret_inline, matches_integer};
+    use super::*;
+    use crate::{clock::{Clock, MockClockSession}, store::RedisValue};
+    use rstest::rstest;
+
+    fn matches_bytes(frame: RESPFrame, expected: &[u8]) -> bool {
+        matches!(frame, RESPFrame::Bulk(s) if s.as_ref() == expected)
+    }
+
+    #[tokio::test]
+    async fn should_set_and_get_bits() {
+        assert!(matches_integer(interpret_inline("SETBIT test_setbit_key 7 1").await, 0));
+        assert!(matches_integer(interpret_inline("SETBIT test_setbit_key 7 1").await, 1));
+        assert!(matches_integer(interpret_inline("SETBIT test_setbit_key 9 1").await, 0));
+        assert!(matches_bytes(interpret_inline("GET test_setbit_key").await, b"\x01\x40"));
+
+        assert!(matches_integer(interpret_inline("GETBIT test_setbit_key 7").await, 1));
+        assert!(matches_integer(interpret_inline("GETBIT test_setbit_key 8").await, 0));
+        assert!(matches_integer(interpret_inline("GETBIT test_setbit_key 100").await, 0));
+        assert!(matches_integer(interpret_inline("GETBIT test_setbit_missing 0").await, 0));
+
+        assert!(matches_integer(interpret_inline("SETBIT test_setbit_key 7 0").await, 1));
+        assert!(matches_bytes(interpret_inline("GET test_setbit_key").await, b"\x00\x40"));
+    }
+
+    #[tokio::test]
+    async fn should_keep_ttl_when_setting_bits() {
+        let _session = MockClockSession::new();
+        Clock::mock_freeze();
+
+        interpret_inline("SET test_setbit_ttl a EX 100").await;
+        interpret_inline("SETBIT test_setbit_ttl 6 1").await;
+
+        assert!(matches_bytes(interpret_inline("GET test_setbit_ttl").await, b"c"));
+        assert!(matches_integer(interpret_inline("TTL test_setbit_ttl").await, 100));
+    }
+
+    #[rstest]
+    #[case("", 26)]
+    #[case("0 0", 4)]
+    #[case("1 1", 6)]
+    #[case("1 1 BYTE", 6)]
+    #[case("5 30 BIT", 17)]
+    #[case("-2 -1", 7)]
+    #[case("-1 -2", 0)]
+    #[case("4 2", 0)]
+    #[case("0 -1 bit", 26)]
+    #[case("2 3 BIT", 1)]
+    #[tokio::test]
+    async fn should_count_bits(#[case] range: &str, #[case] expected: i64) {
+        interpret_inline("SET test_bitcount_key foobar").await;
+
+        assert!(matches_integer(interpret_inline(&format!("BITCOUNT test_bitcount_key {}", range)).await, expected));
+    }
+
+    #[rstest]
+    // "\xff\xf0\x00"
+    #[case("0", 12)]
+    #[case("1", 0)]
+    #[case("0 2", 16)]
+    #[case("1 2", -1)]
+    #[case("1 1 -1", 8)]
+    #[case("0 0 0", -1)]
+    #[case("0 1 -1", 12)]
+    #[case("1 7 15 BIT", 7)]
+    #[case("0 7 15 BIT", 12)]
+    #[case("0 0 7 BIT", -1)]
+    #[case("0 3 2", -1)]
+    #[tokio::test]
+    async fn should_find_bit_position(#[case] args: &str, #[case] expected: i64) {
+        interpret_inline("SET test_bitpos_key \"\\xff\\xf0\\x00\"").await;
+
+        assert!(matches_integer(interpret_inline(&format!("BITPOS test_bitpos_key {}", args)).await, expected));
+    }
+
+    #[rstest]
+    #[case("\"\\xff\\xff\"", "0", 16)]
+    #[case("\"\\xff\\xff\"", "0 0 -1", -1)]
+    #[case("\"\\x00\\x00\"", "1", -1)]
+    #[tokio::test]
+    async fn should_find_bit_position_past_range(#[case] value: &str, #[case] args: &str, #[case] expected: i64) {
+        interpret_inline(&format!("SET test_bitpos_padded {}", value)).await;
+
+        assert!(matches_integer(interpret_inline(&format!("BITPOS test_bitpos_padded {}", args)).await, expected));
+    }
+
+    #[tokio::test]
+    async fn should_find_bit_position_of_missing_key() {
+        assert!(matches_integer(interpret_inline("BITPOS test_bitpos_missing 1").await, -1));
+        assert!(matches_integer(interpret_inline("BITPOS test_bitpos_missing 0").await, 0));
+    }
+
+    #[rstest]
+    #[case("AND", b"\x01\x00\x00")]
+    #[case("OR", b"\x0f\xf0\x0f")]
+    #[case("XOR", b"\x0e\xf0\x0f")]
+    #[tokio::test]
+    async fn should_combine_bits(#[case] operation: &str, #[case] expected: &[u8]) {
+        interpret_inline("SET test_bitop_a \"\\x0f\\xf0\"").await;
+        interpret_inline("SET test_bitop_b \"\\x01\\x00\\x0f\"").await;
+
+        let response = interpret_inline(&format!("BITOP {} test_bitop_{}_dest test_bitop_a test_bitop_b", operation, operation)).await;
+
+        assert!(matches_integer(response, 3));
+        assert!(matches_bytes(interpret_inline(&format!("GET test_bitop_{}_dest", operation)).await, expected));
+    }
+
+    #[tokio::test]
+    async fn should_invert_bits() {
+        interpret_inline("SET test_bitop_not \"\\x0f\\xf0\"").await;
+
+        assert!(matches_integer(interpret_inline("BITOP not test_bitop_not_dest test_bitop_not").await, 2));
+        assert!(matches_bytes(interpret_inline("GET test_bitop_not_dest").await, b"\xf0\x0f"));
+    }
+
+    #[tokio::test]
+    async fn should_delete_destination_of_empty_bitop() {
+        interpret_inline("SET test_bitop_empty_dest value").await;
+
+        assert!(matches_integer(interpret_inline("BITOP OR test_bitop_empty_dest test_bitop_missing").await, 0));
+        assert!(matches_integer(interpret_inline("EXISTS test_bitop_empty_dest").await, 0));
+    }
+
+    #[rstest]
+    #[case("SETBIT test_bit_bad -1 1", "ERR bit offset is not an integer or out of range")]
+    #[case("SETBIT test_bit_bad 4294967296 1", "ERR bit offset is not an integer or out of range")]
+    #[case("SETBIT test_bit_bad one 1", "ERR bit offset is not an integer or out of range")]
+    #[case("SETBIT test_bit_bad 0 2", "ERR bit is not an integer or out of range")]
+    #[case("GETBIT test_bit_bad -1", "ERR bit offset is not an integer or out of range")]
+    #[case("BITCOUNT test_bit_bad 0", "ERR syntax error")]
+    #[case("BITCOUNT test_bit_bad 0 1 WORD", "ERR syntax error")]
+    #[case("BITCOUNT test_bit_bad 0 1 BIT extra", "ERR syntax error")]
+    #[case("BITCOUNT test_bit_bad zero 1", "ERR value is not an integer or out of range")]
+    #[case("BITPOS test_bit_bad 2", "ERR The bit argument must be 1 or 0.")]
+    #[case("BITPOS test_bit_bad 1 0 1 WORD", "ERR syntax error")]
+    #[case("BITOP NOT test_bit_bad a b", "ERR BITOP NOT must be called with a single source key.")]
+    #[case("BITOP NAND test_bit_bad a b", "ERR syntax error")]
+    #[tokio::test]
+    async fn should_reject_bad_bit_requests(#[case] request: &str, #[case] expected_error: &str) {
+        let response = interpret_inline(request).await;
+
+        assert!(matches!(response, RESPFrame::Error(s) if s == expected_error));
+    }
+
+    #[tokio::test]
+    async fn should_get_and_set_bitfields() {
+        assert_eq!(interpret_inline("BITFIELD test_bitfield_key SET i8 #0 100 SET i8 #1 200").await, integers(&[0, 0]));
+        assert!(matches_bytes(interpret_inline("GET test_bitfield_key").await, b"d\xc8"));
+        assert_eq!(
+            interpret_inline("BITFIELD test_bitfield_key GET i8 #0 GET i8 #1 GET u8 8 GET u4 4 GET i4 12").await,
+            integers(&[100, -56, 200, 4, -8])
+        );
+        assert_eq!(interpret_inline("BITFIELD test_bitfield_key SET u4 4 15").await, integers(&[4]));
+        assert!(matches_bytes(interpret_inline("GET test_bitfield_key").await, b"o\xc8"));
+    }
+
+    #[tokio::test]
+    async fn should_not_create_key_without_writes() {
+        assert_eq!(interpret_inline("BITFIELD test_bitfield_missing GET u8 0").await, integers(&[0]));
+        assert_eq!(interpret_inline("BITFIELD test_bitfield_missing").await, integers(&[]));
+        assert!(matches_integer(interpret_inline("EXISTS test_bitfield_missing").await, 0));
+    }
+
+    #[tokio::test]
+    async fn should_increment_bitfields_with_overflow_control() {
+        let request = "BITFIELD test_bitfield_overflow INCRBY u2 100 1 OVERFLOW SAT INCRBY u2 102 1 OVERFLOW FAIL INCRBY u2 104 1";
+
+        assert_eq!(interpret_inline(request).await, integers(&[1, 1, 1]));
+        assert_eq!(interpret_inline(request).await, integers(&[2, 2, 2]));
+        assert_eq!(interpret_inline(request).await, integers(&[3, 3, 3]));
+        assert_eq!(
+            interpret_inline(request).await,
+            RESPFrame::Array(vec![RESPFrame::Integer(0), RESPFrame::Integer(3), RESPFrame::Null])
+        );
+        assert_eq!(interpret_inline("BITFIELD test_bitfield_overflow GET u2 104").await, integers(&[3]));
+    }
+
+    #[rstest]
+    #[case("SET i8 0 127 INCRBY i8 0 1", &[0, -128])]
+    #[case("OVERFLOW SAT SET i8 0 127 INCRBY i8 0 1 INCRBY i8 0 -300", &[0, 127, -128])]
+    #[case("SET u8 0 300 GET u8 0", &[0, 44])]
+    #[case("OVERFLOW SAT SET u8 0 300 SET u8 0 -1 GET u8 0", &[0, 255, 0])]
+    #[case("SET i64 0 -9223372036854775808 INCRBY i64 0 -1", &[0, 9223372036854775807])]
+    #[case("OVERFLOW SAT SET i64 0 9223372036854775807 INCRBY i64 0 1", &[0, 9223372036854775807])]
+    #[case("SET u63 1 9223372036854775807 INCRBY u63 1 1 GET u1 0", &[0, 0, 0])]
+    #[case("OVERFLOW SAT SET u63 0 9223372036854775807 INCRBY u63 0 1", &[0, 9223372036854775807])]
+    #[case("SET i1 0 -1 GET u1 0 INCRBY i1 0 1", &[0, 1, 0])]
+    #[tokio::test]
+    async fn should_handle_bitfield_overflow(#[case] subcommands: &str, #[case] expected: &[i64]) {
+        interpret_inline("DEL test_bitfield_wrap").await;
+
+        let response = interpret_inline(&format!("BITFIELD test_bitfield_wrap {}", subcommands)).await;
+
+        assert_eq!(response, integers(expected));
+    }
+
+    #[tokio::test]
+    async fn should_keep_ttl_when_setting_bitfields() {
+        let _session = MockClockSession::new();
+        Clock::mock_freeze();
+
+        interpret_inline("SET test_bitfield_ttl a EX 100").await;
+        interpret_inline("BITFIELD test_bitfield_ttl SET u8 8 98").await;
+
+        assert!(matches_bytes(interpret_inline("GET test_bitfield_ttl").await, b"ab"));
+        assert!(matches_integer(interpret_inline("TTL test_bitfield_ttl").await, 100));
+    }
+
+    #[tokio::test]
+    async fn should_only_read_bitfields_with_ro() {
+        interpret_inline("SET test_bitfield_ro \"\\x7f\"").await;
+
+        assert_eq!(interpret_inline("BITFIELD_RO test_bitfield_ro GET i8 0 GET u4 #1").await, integers(&[127, 15]));
+    }
+
+    #[rstest]
+    #[case("BITFIELD test_bitfield_bad GET u64 0", "ERR Invalid bitfield type. Use something like i16 u8. Note that u64 is not supported but i64 is.")]
+    #[case("BITFIELD test_bitfield_bad GET i65 0", "ERR Invalid bitfield type. Use something like i16 u8. Note that u64 is not supported but i64 is.")]
+    #[case("BITFIELD test_bitfield_bad GET i0 0", "ERR Invalid bitfield type. Use something like i16 u8. Note that u64 is not supported but i64 is.")]
+    #[case("BITFIELD test_bitfield_bad GET x8 0", "ERR Invalid bitfield type. Use something like i16 u8. Note that u64 is not supported but i64 is.")]
+    #[case("BITFIELD test_bitfield_bad GET u8 -1", "ERR bit offset is not an integer or out of range")]
+    #[case("BITFIELD test_bitfield_bad GET u8 #-1", "ERR bit offset is not an integer or out of range")]
+    #[case("BITFIELD test_bitfield_bad GET u8 #536870912", "ERR bit offset is not an integer or out of range")]
+    #[case("BITFIELD test_bitfield_bad SET u8 0 one", "ERR value is not an integer or out of range")]
+    #[case("BITFIELD test_bitfield_bad OVERFLOW PANIC", "ERR Invalid OVERFLOW type specified")]
+    #[case("BITFIELD test_bitfield_bad GET u8", "ERR syntax error")]
+    #[case("BITFIELD test_bitfield_bad DECRBY u8 0 1", "ERR syntax error")]
+    #[case("BITFIELD_RO test_bitfield_bad GET u8 0 SET u8 0 1", "ERR BITFIELD_RO only supports the GET subcommand")]
+    #[tokio::test]
+    async fn should_reject_bad_bitfield_requests(#[case] request: &str, #[case] expected_error: &str) {
+        let response = interpret_inline(request).await;
+
+        assert!(matches!(response, RESPFrame::Error(s) if s == expected_error));
+    }
+
+    #[tokio::test]
+    async fn should_not_run_any_subcommand_of_bad_bitfield() {
+        interpret_inline("BITFIELD test_bitfield_atomic SET u8 0 1 GET u8").await;
+
+        assert!(matches_integer(interpret_inline("EXISTS test_bitfield_atomic").await, 0));
+    }
+
+    #[rstest]
+    #[case("SETBIT test_bit_wrongtype 0 1")]
+    #[case("GETBIT test_bit_wrongtype 0")]
+    #[case("BITCOUNT test_bit_wrongtype")]
+    #[case("BITPOS test_bit_wrongtype 1")]
+    #[case("BITOP OR test_bit_wrongtype_dest test_bit_wrongtype")]
+    #[case("BITFIELD test_bit_wrongtype GET u8 0")]
+    #[tokio::test]
+    async fn should_reject_other_types(#[case] request: &str) {
+        insert_value("test_bit_wrongtype", RedisValue::Set(Default::default())).await;
+
+        let response = interpret_inline(request).await;
+
+        assert!(matches!(response, RESPFrame::Error(s) if s == "WRONGTYPE Operation against a key holding the wrong kind of value"));
+    }
+}
@@ -0,0 +1,86 @@
+use bytes::Bytes;
+
+use crate::{error::RedisError, resp::{frame::{RESPFrame, RESPProtocol}, interpreter::REDIS_VERSION}};
+
+use super::{CommandContext, CommandResult};
+
+/**
+ * PING [message]
+ */
+pub fn ping(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    match args {
+        [] => Ok(RESPFrame::Simple("PONG".to_owned())),
+        [message] => Ok(RESPFrame::Bulk(message.clone())),
+        _ => Err(ctx.wrong_arity())
+    }
+}
+
+/**
+ * ECHO message
+ */
+pub fn echo(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    match args {
+        [message] => Ok(RESPFrame::Bulk(message.clone())),
+        _ => Err(ctx.wrong_arity())
+    }
+}
+
+/**
+ * HELLO [protover [AUTH username password] [SETNAME clientname]]
+ * Switches the connection protocol and replies with server info
+ */
+pub fn hello(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let connection = &mut ctx.connection;
+    let (protocol, mut options) = match args {
+        [version, options @ ..] => {
+            match version.as_ref() {
+                b"2" => (RESPProtocol::RESP2, options),
+                b"3" => (RESPProtocol::RESP3, options),
+                _ => return Err(RedisError::NoProto)
+            }
+        },
+        _ => (connection.protocol, args)
+    };
+
+    let mut name = None;
+    loop {
+        match options {
+            [] => break,
+            [option, username, _password, rest @ ..] if option.eq_ignore_ascii_case(b"AUTH") => {
+                // Only the default user exists, and it has no password
+                if username.as_ref() != b"default" {
+                    return Err(RedisError::WrongPass)
+                }
+                options = rest
+            },
+            [option, client_name, rest @ ..] if option.eq_ignore_ascii_case(b"SETNAME") => {
+                name = Some(client_name.to_owned());
+                options = rest
+            },
+            [option, ..] => return Err(RedisError::Custom(format!(
+                "Syntax error in HELLO option '{}'",
+                String::from_utf8_lossy(option)
+            ))),
+        }
+    }
+
+    connection.protocol = protocol;
+    if name.is_some() {
+        connection.name = name;
+    }
+
+    let protocol_version = match protocol {
+        RESPProtocol::RESP2 => 2,
+        RESPProtocol::RESP3 => 3,
+    };
+
+    Ok(RESPFrame::Map(vec![
+        (RESPFrame::Bulk(Bytes::from("server")), RESPFrame::Bulk(Bytes::from("redis"))),
+        (RESPFrame::Bulk(Bytes::from("version")), RESPFrame::Bulk(Bytes::from(REDIS_VERSION))),
+        (RESPFrame::Bulk(Bytes::from("proto")), RESPFrame::Integer(protocol_version)),
+        (RESPFrame::Bulk(Bytes::from("id")), RESPFrame::Integer(connection.id as i64)),
+        (RESPFrame::Bulk(Bytes::from("mode")), RESPFrame::Bulk(Bytes::from("standalone"))),
+        (RESPFrame::Bulk(Bytes::from("role")), RESPFrame::Bulk(Bytes::from("master"))),
+        (RESPFrame::Bulk(Bytes::from("modules")), RESPFrame::Array(vec![])),
+    ]))
+}
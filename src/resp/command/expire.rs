@@ -0,0 +1,293 @@
+use bytes::Bytes;
+
+use crate::{error::RedisError, resp::frame::RESPFrame, store::{KeyExpiry, RedisStore, EpochMillisecond}};
+
+use super::{
+    parse_integer, CommandContext, CommandResult,
+    ExpireCommandFlags, ExpireCommandExistFlag, ExpireCommandCompareFlag
+};
+
+/**
+ * EXPIRE key seconds [NX | XX | GT | LT]
+ */
+pub fn expire(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    expire_generic(ctx, args, 1000, false)
+}
+
+/**
+ * PEXPIRE key milliseconds [NX | XX | GT | LT]
+ */
+pub fn pexpire(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    expire_generic(ctx, args, 1, false)
+}
+
+/**
+ * EXPIREAT key unix-time-seconds [NX | XX | GT | LT]
+ */
+pub fn expireat(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    expire_generic(ctx, args, 1000, true)
+}
+
+/**
+ * PEXPIREAT key unix-time-milliseconds [NX | XX | GT | LT]
+ */
+pub fn pexpireat(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    expire_generic(ctx, args, 1, true)
+}
+
+/**
+ * TTL key
+ * Replies -2 if the key is missing, or -1 if it has no TTL
+ */
+pub fn ttl(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    // Round to the nearest second
+    expiry_reply(ctx, args, |deadline| (deadline - RedisStore::get_unix_time() as i64 + 500) / 1000)
+}
+
+/**
+ * PTTL key
+ */
+pub fn pttl(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    expiry_reply(ctx, args, |deadline| deadline - RedisStore::get_unix_time() as i64)
+}
+
+/**
+ * EXPIRETIME key
+ */
+pub fn expiretime(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    expiry_reply(ctx, args, |deadline| deadline / 1000)
+}
+
+/**
+ * PEXPIRETIME key
+ */
+pub fn pexpiretime(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    expiry_reply(ctx, args, |deadline| deadline)
+}
+
+/**
+ * PERSIST key
+ */
+pub fn persist(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    Ok(RESPFrame::Integer(ctx.store.persist(key) as i64))
+}
+
+/**
+ * Sets a TTL from a relative or absolute time, scaled to milliseconds by the unit
+ */
+fn expire_generic(ctx: &mut CommandContext, args: &[Bytes], unit: i64, absolute: bool) -> CommandResult {
+    let [key, time, options @ ..] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let time = parse_integer(time)?;
+    let flags = calculate_expire_flags(options)?;
+
+    let invalid_expire_time = || RedisError::InvalidExpireTime(ctx.command.name.to_owned());
+    let base = if absolute { 0 } else { RedisStore::get_unix_time() as i64 };
+    let deadline = time.checked_mul(unit)
+        .and_then(|time| time.checked_add(base))
+        .ok_or_else(invalid_expire_time)?;
+
+    // Deadlines before the epoch are already expired
+    let deadline = deadline.max(0) as EpochMillisecond;
+
+    Ok(RESPFrame::Integer(ctx.store.expire(key, deadline, &flags) as i64))
+}
+
+fn calculate_expire_flags(options: &[Bytes]) -> Result<ExpireCommandFlags, RedisError> {
+    let mut flags = ExpireCommandFlags::default();
+    let (mut nx, mut xx, mut gt, mut lt) = (false, false, false, false);
+
+    for option in options {
+        match option.to_ascii_uppercase().as_slice() {
+            b"NX" => nx = true,
+            b"XX" => xx = true,
+            b"GT" => gt = true,
+            b"LT" => lt = true,
+            _ => return Err(RedisError::Custom(format!(
+                "Unsupported option {}",
+                String::from_utf8_lossy(option)
+            )))
+        }
+    }
+
+    if nx && (xx || gt || lt) {
+        return Err(RedisError::Custom("NX and XX, GT or LT options at the same time are not compatible".to_owned()))
+    }
+    if gt && lt {
+        return Err(RedisError::Custom("GT and LT options at the same time are not compatible".to_owned()))
+    }
+
+    if nx {
+        flags.exist_flag = Some(ExpireCommandExistFlag::NX)
+    } else if xx {
+        flags.exist_flag = Some(ExpireCommandExistFlag::XX)
+    }
+    if gt {
+        flags.compare_flag = Some(ExpireCommandCompareFlag::GT)
+    } else if lt {
+        flags.compare_flag = Some(ExpireCommandCompareFlag::LT)
+    }
+
+    Ok(flags)
+}
+
+/**
+ * Replies -2 for missing keys, -1 for keys without a TTL, otherwise the converted deadline
+ */
+fn expiry_reply(ctx: &mut CommandContext, args: &[Bytes], convert: impl Fn(i64) -> i64) -> CommandResult {
+    let [key] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    Ok(RESPFrame::Integer(match ctx.store.expiry(key) {
+        KeyExpiry::Missing => -2,
+        KeyExpiry::Persistent => -1,
+        KeyExpiry::At(deadline) => convert(deadline as i64),
+    }))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::super::test_utils::{interpret_inline, matches_integer};
+    use super::*;
+    use crate::clock::{Clock, MockClockSession};
+    use rstest::rstest;
+
+    #[tokio::test]
+    async fn should_reply_missing_and_persistent_ttl() {
+        interpret_inline("SET test_ttl_persistent value").await;
+
+        assert!(matches_integer(interpret_inline("TTL test_ttl_missing").await, -2));
+        assert!(matches_integer(interpret_inline("PTTL test_ttl_missing").await, -2));
+        assert!(matches_integer(interpret_inline("EXPIRETIME test_ttl_missing").await, -2));
+        assert!(matches_integer(interpret_inline("TTL test_ttl_persistent").await, -1));
+        assert!(matches_integer(interpret_inline("PTTL test_ttl_persistent").await, -1));
+        assert!(matches_integer(interpret_inline("EXPIRETIME test_ttl_persistent").await, -1));
+    }
+
+    #[tokio::test]
+    async fn should_expire_key() {
+        let _session = MockClockSession::new();
+        Clock::mock_set_time(1_000_000);
+
+        interpret_inline("SET test_expire_key value").await;
+        assert!(matches_integer(interpret_inline("EXPIRE test_expire_key 10").await, 1));
+        assert!(matches_integer(interpret_inline("TTL test_expire_key").await, 10));
+        assert!(matches_integer(interpret_inline("PTTL test_expire_key").await, 10_000));
+        assert!(matches_integer(interpret_inline("EXPIRETIME test_expire_key").await, 1010));
+        assert!(matches_integer(interpret_inline("PEXPIRETIME test_expire_key").await, 1_010_000));
+
+        Clock::mock_advance(Duration::from_millis(9_999));
+        assert!(matches_integer(interpret_inline("PTTL test_expire_key").await, 1));
+        assert!(matches_integer(interpret_inline("TTL test_expire_key").await, 0));
+
+        Clock::mock_advance(Duration::from_millis(1));
+        assert!(matches_integer(interpret_inline("TTL test_expire_key").await, -2));
+        assert!(matches!(interpret_inline("GET test_expire_key").await, RESPFrame::Null));
+    }
+
+    #[rstest]
+    #[case("PEXPIRE test_expire_variant 2500", 2_500)]
+    #[case("EXPIREAT test_expire_variant 1003", 3_000)]
+    #[case("PEXPIREAT test_expire_variant 1000250", 250)]
+    #[tokio::test]
+    async fn should_expire_key_with_variants(#[case] request: &str, #[case] expected_pttl: i64) {
+        let _session = MockClockSession::new();
+        Clock::mock_set_time(1_000_000);
+
+        interpret_inline("SET test_expire_variant value").await;
+        assert!(matches_integer(interpret_inline(request).await, 1));
+        assert!(matches_integer(interpret_inline("PTTL test_expire_variant").await, expected_pttl));
+    }
+
+    #[rstest]
+    #[case("EXPIRE test_expire_past -1")]
+    #[case("PEXPIRE test_expire_past 0")]
+    #[case("EXPIREAT test_expire_past 1")]
+    #[case("PEXPIREAT test_expire_past -100")]
+    #[tokio::test]
+    async fn should_delete_key_with_past_deadline(#[case] request: &str) {
+        interpret_inline("SET test_expire_past value").await;
+
+        assert!(matches_integer(interpret_inline(request).await, 1));
+        assert!(matches_integer(interpret_inline("EXISTS test_expire_past").await, 0));
+    }
+
+    #[tokio::test]
+    async fn should_not_expire_missing_key() {
+        assert!(matches_integer(interpret_inline("EXPIRE test_expire_missing 10").await, 0));
+        assert!(matches_integer(interpret_inline("PERSIST test_expire_missing").await, 0));
+    }
+
+    #[rstest]
+    // Key without TTL
+    #[case("", "NX", 1)]
+    #[case("", "XX", 0)]
+    #[case("", "GT", 0)]
+    #[case("", "LT", 1)]
+    #[case("", "XX GT", 0)]
+    // Key with a 100 second TTL
+    #[case("EX 100", "NX", 0)]
+    #[case("EX 100", "XX", 1)]
+    #[case("EX 100", "GT", 1)]
+    #[case("EX 100", "LT", 0)]
+    #[case("EX 100", "xx lt", 0)]
+    // Key with a 300 second TTL
+    #[case("EX 300", "GT", 0)]
+    #[case("EX 300", "LT", 1)]
+    #[case("EX 200", "GT", 0)]
+    #[case("EX 200", "LT", 0)]
+    #[tokio::test]
+    async fn should_expire_with_conditions(#[case] set_options: &str, #[case] expire_options: &str, #[case] expected: i64) {
+        let _session = MockClockSession::new();
+        Clock::mock_freeze();
+
+        interpret_inline(&format!("SET test_expire_condition value {}", set_options)).await;
+        let response = interpret_inline(&format!("EXPIRE test_expire_condition 200 {}", expire_options)).await;
+
+        assert!(matches_integer(response, expected));
+        if expected == 1 {
+            assert!(matches_integer(interpret_inline("TTL test_expire_condition").await, 200));
+        }
+    }
+
+    #[rstest]
+    #[case("EXPIRE test_expire_bad 10 NX XX", "ERR NX and XX, GT or LT options at the same time are not compatible")]
+    #[case("EXPIRE test_expire_bad 10 NX GT", "ERR NX and XX, GT or LT options at the same time are not compatible")]
+    #[case("EXPIRE test_expire_bad 10 GT LT", "ERR GT and LT options at the same time are not compatible")]
+    #[case("EXPIRE test_expire_bad 10 ZZ", "ERR Unsupported option ZZ")]
+    #[case("EXPIRE test_expire_bad ten", "ERR value is not an integer or out of range")]
+    #[case("EXPIRE test_expire_bad +10", "ERR value is not an integer or out of range")]
+    #[case("EXPIRE test_expire_bad 9223372036854775807", "ERR invalid expire time in 'expire' command")]
+    #[case("PEXPIRE test_expire_bad 9223372036854775807", "ERR invalid expire time in 'pexpire' command")]
+    #[case("EXPIREAT test_expire_bad -9223372036854775807", "ERR invalid expire time in 'expireat' command")]
+    #[case("TTL", "ERR wrong number of arguments for 'ttl' command")]
+    #[tokio::test]
+    async fn should_reject_bad_expire(#[case] request: &str, #[case] expected_error: &str) {
+        let response = interpret_inline(request).await;
+
+        assert!(matches!(response, RESPFrame::Error(s) if s == expected_error));
+    }
+
+    #[tokio::test]
+    async fn should_persist_key() {
+        let _session = MockClockSession::new();
+        Clock::mock_freeze();
+
+        interpret_inline("SET test_persist_key value EX 10").await;
+        assert!(matches_integer(interpret_inline("PERSIST test_persist_key").await, 1));
+        assert!(matches_integer(interpret_inline("PERSIST test_persist_key").await, 0));
+        assert!(matches_integer(interpret_inline("TTL test_persist_key").await, -1));
+
+        Clock::mock_advance(Duration::from_secs(10));
+        assert!(matches!(interpret_inline("GET test_persist_key").await, RESPFrame::Bulk(_)));
+    }
+}
@@ -0,0 +1,659 @@
+use bytes::Bytes;
+
+use crate::{error::RedisError, resp::frame::{RESPFrame, RESPProtocol}};
+
+use super::{canonical_float, canonical_integer, glob_match, parse_float, parse_integer, CommandContext, CommandResult};
+
+// Entries checked per HSCAN call when COUNT isn't given
+const SCAN_DEFAULT_COUNT: usize = 10;
+
+// Fields replied by HRANDFIELD with a negative count, at most
+const RANDOM_MAX_REPEATED: i64 = 1024 * 1024;
+
+/**
+ * HSET key field value [field value ...]
+ * Replies with the number of fields added
+ */
+pub fn hset(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let added = set_fields(ctx, args)?;
+    Ok(RESPFrame::Integer(added as i64))
+}
+
+/**
+ * HMSET key field value [field value ...]
+ * Same as HSET, only replying OK
+ */
+pub fn hmset(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    set_fields(ctx, args)?;
+    Ok(RESPFrame::Simple("OK".to_owned()))
+}
+
+/**
+ * HSETNX key field value
+ * Replies with whether the field was set, which it only is if it didn't exist
+ */
+pub fn hsetnx(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, field, value] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let hash = ctx.store.get_hash_or_insert(key)?;
+    let added = hash.get(field).is_none() && hash.insert(field.clone(), value.clone());
+    Ok(RESPFrame::Integer(added as i64))
+}
+
+/**
+ * Sets the field value pairs following the key, returning how many fields were added
+ */
+fn set_fields(ctx: &mut CommandContext, args: &[Bytes]) -> Result<usize, RedisError> {
+    let [key, pairs @ ..] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    if pairs.is_empty() || !pairs.len().is_multiple_of(2) {
+        return Err(ctx.wrong_arity())
+    }
+
+    let hash = ctx.store.get_hash_or_insert(key)?;
+    let added = pairs.chunks_exact(2)
+        .filter(|pair| hash.insert(pair[0].clone(), pair[1].clone()))
+        .count();
+
+    Ok(added)
+}
+
+/**
+ * HGET key field
+ */
+pub fn hget(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, field] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let value = ctx.store.get_hash(key)?.and_then(|hash| hash.get(field));
+    Ok(value.map_or(RESPFrame::Null, |value| RESPFrame::Bulk(value.clone())))
+}
+
+/**
+ * HMGET key field [field ...]
+ */
+pub fn hmget(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, fields @ ..] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let hash = ctx.store.get_hash(key)?;
+    Ok(RESPFrame::Array(
+        fields.iter()
+            .map(|field| hash.and_then(|hash| hash.get(field))
+                .map_or(RESPFrame::Null, |value| RESPFrame::Bulk(value.clone())))
+            .collect()
+    ))
+}
+
+/**
+ * HLEN key
+ */
+pub fn hlen(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let len = ctx.store.get_hash(key)?.map_or(0, |hash| hash.len());
+    Ok(RESPFrame::Integer(len as i64))
+}
+
+/**
+ * HEXISTS key field
+ */
+pub fn hexists(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, field] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let exists = ctx.store.get_hash(key)?.and_then(|hash| hash.get(field)).is_some();
+    Ok(RESPFrame::Integer(exists as i64))
+}
+
+/**
+ * HSTRLEN key field
+ * Replies with the length of the field's value, 0 if it doesn't exist
+ */
+pub fn hstrlen(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, field] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let len = ctx.store.get_hash(key)?.and_then(|hash| hash.get(field)).map_or(0, |value| value.len());
+    Ok(RESPFrame::Integer(len as i64))
+}
+
+/**
+ * HDEL key field [field ...]
+ * Replies with the number of fields removed, deleting the hash once empty
+ */
+pub fn hdel(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, fields @ ..] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let Some(hash) = ctx.store.get_hash_mut(key)? else {
+        return Ok(RESPFrame::Integer(0))
+    };
+    let removed = fields.iter().filter(|field| hash.remove(field)).count();
+
+    if hash.is_empty() {
+        ctx.store.del(key);
+    }
+    Ok(RESPFrame::Integer(removed as i64))
+}
+
+/**
+ * HGETALL key
+ * Replies with a map, flattened to fields followed by their values in RESP2
+ */
+pub fn hgetall(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let pairs = ctx.store.get_hash(key)?.map_or_else(Vec::new, |hash| hash.iter()
+        .map(|(field, value)| (RESPFrame::Bulk(field.clone()), RESPFrame::Bulk(value.clone())))
+        .collect());
+
+    Ok(RESPFrame::Map(pairs))
+}
+
+/**
+ * HKEYS key
+ */
+pub fn hkeys(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let fields = ctx.store.get_hash(key)?.map_or_else(Vec::new, |hash| hash.iter()
+        .map(|(field, _)| RESPFrame::Bulk(field.clone()))
+        .collect());
+
+    Ok(RESPFrame::Array(fields))
+}
+
+/**
+ * HVALS key
+ */
+pub fn hvals(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let values = ctx.store.get_hash(key)?.map_or_else(Vec::new, |hash| hash.iter()
+        .map(|(_, value)| RESPFrame::Bulk(value.clone()))
+        .collect());
+
+    Ok(RESPFrame::Array(values))
+}
+
+/**
+ * HINCRBY key field increment
+ */
+pub fn hincrby(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, field, increment] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let increment = parse_integer(increment)?;
+
+    let hash = ctx.store.get_hash_or_insert(key)?;
+    let current = match hash.get(field) {
+        None => 0,
+        Some(value) => canonical_integer(value)
+            .ok_or_else(|| RedisError::Custom("hash value is not an integer".to_owned()))?,
+    };
+    let value = current.checked_add(increment).ok_or(RedisError::Overflow)?;
+
+    hash.insert(field.clone(), Bytes::from(value.to_string()));
+    Ok(RESPFrame::Integer(value))
+}
+
+/**
+ * HINCRBYFLOAT key field increment
+ */
+pub fn hincrbyfloat(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, field, increment] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let increment = parse_float(increment)?;
+    if !increment.is_finite() {
+        return Err(RedisError::Custom("value is NaN or Infinity".to_owned()))
+    }
+
+    let hash = ctx.store.get_hash_or_insert(key)?;
+    let current = match hash.get(field) {
+        None => 0.0,
+        Some(value) => canonical_float(value)
+            .ok_or_else(|| RedisError::Custom("hash value is not a float".to_owned()))?,
+    };
+    let value = current + increment;
+    if !value.is_finite() {
+        return Err(RedisError::Custom("increment would produce NaN or Infinity".to_owned()))
+    }
+
+    let value = Bytes::from(value.to_string());
+    hash.insert(field.clone(), value.clone());
+    Ok(RESPFrame::Bulk(value))
+}
+
+/**
+ * HSCAN key cursor [MATCH pattern] [COUNT count]
+ * Replies with the next cursor, and fields followed by their values
+ */
+pub fn hscan(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, cursor, options @ ..] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let cursor = std::str::from_utf8(cursor).ok()
+        .and_then(|cursor| cursor.parse::<u64>().ok())
+        .ok_or_else(|| RedisError::Custom("invalid cursor".to_owned()))?;
+
+    let (mut pattern, mut count) = (None, SCAN_DEFAULT_COUNT);
+    let mut options = options;
+    loop {
+        match options {
+            [] => break,
+            [option, value, rest @ ..] if option.eq_ignore_ascii_case(b"MATCH") => {
+                pattern = Some(value);
+                options = rest
+            },
+            [option, value, rest @ ..] if option.eq_ignore_ascii_case(b"COUNT") => {
+                count = match parse_integer(value)? {
+                    count if count < 1 => return Err(RedisError::Syntax),
+                    count => count as usize,
+                };
+                options = rest
+            },
+            _ => return Err(RedisError::Syntax)
+        }
+    }
+
+    let (next_cursor, entries) = match ctx.store.get_hash(key)? {
+        Some(hash) => hash.scan(cursor, count),
+        None => (0, vec![]),
+    };
+
+    // Entries are filtered after being scanned, so a call may reply with none
+    let entries = entries.into_iter()
+        .filter(|(field, _)| pattern.is_none_or(|pattern| glob_match(pattern, field)))
+        .flat_map(|(field, value)| vec![RESPFrame::Bulk(field.clone()), RESPFrame::Bulk(value.clone())])
+        .collect();
+
+    Ok(RESPFrame::Array(vec![
+        RESPFrame::Bulk(Bytes::from(next_cursor.to_string())),
+        RESPFrame::Array(entries)
+    ]))
+}
+
+/**
+ * HRANDFIELD key [count [WITHVALUES]]
+ * A positive count picks distinct fields, while a negative count may pick the same field again
+ * With values, RESP3 replies with field and value pairs
+ */
+pub fn hrandfield(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let (key, count, with_values) = match args {
+        [key] => (key, None, false),
+        [key, count] => (key, Some(parse_integer(count)?), false),
+        [key, count, option] if option.eq_ignore_ascii_case(b"WITHVALUES") => (key, Some(parse_integer(count)?), true),
+        [_, _, _] => return Err(RedisError::Syntax),
+        _ => return Err(ctx.wrong_arity())
+    };
+
+    let Some(count) = count else {
+        let field = ctx.store.get_hash(key)?
+            .and_then(|hash| hash.random_entries(1, true).pop())
+            .map(|(field, _)| field.clone());
+
+        return Ok(field.map_or(RESPFrame::Null, RESPFrame::Bulk))
+    };
+
+    // A negative count repeats fields, so the reply can be far larger than the hash
+    if count < -RANDOM_MAX_REPEATED {
+        return Err(RedisError::Custom("value is out of range".to_owned()))
+    }
+
+    let protocol = ctx.connection.protocol;
+    let entries = ctx.store.get_hash(key)?
+        .map_or_else(Vec::new, |hash| hash.random_entries(count.unsigned_abs() as usize, count >= 0));
+
+    Ok(RESPFrame::Array(match (with_values, protocol) {
+        (false, _) => entries.into_iter()
+            .map(|(field, _)| RESPFrame::Bulk(field.clone()))
+            .collect(),
+        (true, RESPProtocol::RESP2) => entries.into_iter()
+            .flat_map(|(field, value)| vec![RESPFrame::Bulk(field.clone()), RESPFrame::Bulk(value.clone())])
+            .collect(),
+        (true, RESPProtocol::RESP3) => entries.into_iter()
+            .map(|(field, value)| RESPFrame::Array(vec![RESPFrame::Bulk(field.clone()), RESPFrame::Bulk(value.clone())]))
+            .collect(),
+    }))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::super::test_utils::{bulk, bulks, insert_value, interpret_inline, interpret_inline_with, matches_bulk, matches_error, matches_integer};
+    use super::*;
+    use crate::{resp::interpreter::ConnectionState, store::RedisValue};
+    use rstest::rstest;
+
+    fn large_hash(entries: usize) -> RedisValue {
+        RedisValue::Hash((0..entries).map(|i| (Bytes::from(format!("field{}", i)), Bytes::from(i.to_string()))).collect())
+    }
+
+    /**
+     * Fields followed by their values, as a set of pairs
+     */
+    fn pairs(frame: RESPFrame) -> HashSet<(Bytes, Bytes)> {
+        let RESPFrame::Array(elements) = frame else { panic!("expected an array") };
+
+        elements.chunks_exact(2)
+            .map(|pair| match pair {
+                [RESPFrame::Bulk(field), RESPFrame::Bulk(value)] => (field.clone(), value.clone()),
+                _ => panic!("expected bulk strings")
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn should_set_and_get_fields() {
+        assert!(matches_integer(interpret_inline("HSET test_hset_hash name alice age 30").await, 2));
+        assert!(matches_integer(interpret_inline("HSET test_hset_hash age 31 city paris").await, 1));
+
+        assert!(matches_bulk(interpret_inline("HGET test_hset_hash age").await, "31"));
+        assert!(matches!(interpret_inline("HGET test_hset_hash missing").await, RESPFrame::Null));
+        assert!(matches!(interpret_inline("HGET test_hset_missing age").await, RESPFrame::Null));
+        assert_eq!(
+            interpret_inline("HMGET test_hset_hash name missing city").await,
+            RESPFrame::Array(vec![bulk("alice"), RESPFrame::Null, bulk("paris")])
+        );
+        assert_eq!(
+            interpret_inline("HMGET test_hset_missing name").await,
+            RESPFrame::Array(vec![RESPFrame::Null])
+        );
+
+        assert!(matches!(interpret_inline("TYPE test_hset_hash").await, RESPFrame::Simple(s) if s == "hash"));
+        assert!(matches_bulk(interpret_inline("OBJECT ENCODING test_hset_hash").await, "listpack"));
+    }
+
+    #[rstest]
+    #[case("HSET test_hset_arity_hash field")]
+    #[case("HSET test_hset_arity_hash field value other")]
+    #[tokio::test]
+    async fn should_reject_unpaired_fields(#[case] request: &str) {
+        assert!(matches_error(interpret_inline(request).await, "ERR wrong number of arguments for 'hset' command"));
+        assert!(matches_integer(interpret_inline("EXISTS test_hset_arity_hash").await, 0));
+    }
+
+    #[tokio::test]
+    async fn should_set_multiple_fields_replying_ok() {
+        assert!(matches!(interpret_inline("HMSET test_hmset_hash a 1 b 2").await, RESPFrame::Simple(s) if s == "OK"));
+        assert!(matches!(interpret_inline("HMSET test_hmset_hash b 3 c 4").await, RESPFrame::Simple(s) if s == "OK"));
+        assert_eq!(interpret_inline("HMGET test_hmset_hash a b c").await, bulks(&["1", "3", "4"]));
+
+        assert!(matches_error(
+            interpret_inline("HMSET test_hmset_arity_hash field").await,
+            "ERR wrong number of arguments for 'hmset' command"
+        ));
+        assert!(matches_integer(interpret_inline("EXISTS test_hmset_arity_hash").await, 0));
+    }
+
+    #[tokio::test]
+    async fn should_only_set_missing_field() {
+        assert!(matches_integer(interpret_inline("HSETNX test_hsetnx_hash field first").await, 1));
+        assert!(matches_integer(interpret_inline("HSETNX test_hsetnx_hash field second").await, 0));
+        assert!(matches_bulk(interpret_inline("HGET test_hsetnx_hash field").await, "first"));
+    }
+
+    #[tokio::test]
+    async fn should_describe_fields() {
+        interpret_inline("HSET test_hlen_hash name alice empty \"\"").await;
+
+        assert!(matches_integer(interpret_inline("HLEN test_hlen_hash").await, 2));
+        assert!(matches_integer(interpret_inline("HLEN test_hlen_missing").await, 0));
+
+        assert!(matches_integer(interpret_inline("HEXISTS test_hlen_hash empty").await, 1));
+        assert!(matches_integer(interpret_inline("HEXISTS test_hlen_hash missing").await, 0));
+        assert!(matches_integer(interpret_inline("HEXISTS test_hlen_missing name").await, 0));
+
+        assert!(matches_integer(interpret_inline("HSTRLEN test_hlen_hash name").await, 5));
+        assert!(matches_integer(interpret_inline("HSTRLEN test_hlen_hash empty").await, 0));
+        assert!(matches_integer(interpret_inline("HSTRLEN test_hlen_missing name").await, 0));
+    }
+
+    #[tokio::test]
+    async fn should_get_fields_and_values() {
+        interpret_inline("HSET test_hkeys_hash a 1 b 2").await;
+
+        assert_eq!(interpret_inline("HKEYS test_hkeys_hash").await, bulks(&["a", "b"]));
+        assert_eq!(interpret_inline("HVALS test_hkeys_hash").await, bulks(&["1", "2"]));
+        assert_eq!(interpret_inline("HKEYS test_hkeys_missing").await, bulks(&[]));
+        assert_eq!(interpret_inline("HVALS test_hkeys_missing").await, bulks(&[]));
+    }
+
+    #[tokio::test]
+    async fn should_convert_to_hashtable_past_limits() {
+        let long_value = "a".repeat(65);
+        interpret_inline("HSET test_hset_long_hash short value").await;
+        interpret_inline(&format!("HSET test_hset_long_hash long {}", long_value)).await;
+        assert!(matches_bulk(interpret_inline("OBJECT ENCODING test_hset_long_hash").await, "hashtable"));
+        assert!(matches_bulk(interpret_inline("HGET test_hset_long_hash long").await, &long_value));
+
+        let fields: Vec<String> = (0..129).map(|i| format!("f{} {}", i, i)).collect();
+        interpret_inline(&format!("HSET test_hset_many_hash {}", fields[..128].join(" "))).await;
+        assert!(matches_bulk(interpret_inline("OBJECT ENCODING test_hset_many_hash").await, "listpack"));
+        interpret_inline(&format!("HSET test_hset_many_hash {}", fields[128])).await;
+        assert!(matches_bulk(interpret_inline("OBJECT ENCODING test_hset_many_hash").await, "hashtable"));
+        assert!(matches_bulk(interpret_inline("HGET test_hset_many_hash f100").await, "100"));
+    }
+
+    #[tokio::test]
+    async fn should_delete_fields_and_empty_hash() {
+        interpret_inline("HSET test_hdel_hash a 1 b 2 c 3").await;
+
+        assert!(matches_integer(interpret_inline("HDEL test_hdel_hash a missing b").await, 2));
+        assert!(matches_integer(interpret_inline("HDEL test_hdel_hash a").await, 0));
+        assert!(matches_integer(interpret_inline("HDEL test_hdel_hash c").await, 1));
+        assert!(matches_integer(interpret_inline("EXISTS test_hdel_hash").await, 0));
+        assert!(matches_integer(interpret_inline("HDEL test_hdel_hash c").await, 0));
+    }
+
+    #[tokio::test]
+    async fn should_get_all_as_map() {
+        interpret_inline("HSET test_hgetall_hash a 1 b 2").await;
+
+        let response = interpret_inline("HGETALL test_hgetall_hash").await;
+        assert_eq!(response, RESPFrame::Map(vec![(bulk("a"), bulk("1")), (bulk("b"), bulk("2"))]));
+        assert_eq!(response.into_protocol(RESPProtocol::RESP2), bulks(&["a", "1", "b", "2"]));
+
+        assert_eq!(interpret_inline("HGETALL test_hgetall_missing").await, RESPFrame::Map(vec![]));
+    }
+
+    #[tokio::test]
+    async fn should_increment_integer_fields() {
+        assert!(matches_integer(interpret_inline("HINCRBY test_hincrby_hash counter 5").await, 5));
+        assert!(matches_integer(interpret_inline("HINCRBY test_hincrby_hash counter -7").await, -2));
+        assert!(matches_bulk(interpret_inline("HGET test_hincrby_hash counter").await, "-2"));
+
+        interpret_inline("HSET test_hincrby_hash text hello max 9223372036854775807").await;
+        assert!(matches_error(interpret_inline("HINCRBY test_hincrby_hash text 1").await, "ERR hash value is not an integer"));
+        assert!(matches_error(interpret_inline("HINCRBY test_hincrby_hash max 1").await, "ERR increment or decrement would overflow"));
+        assert!(matches_error(interpret_inline("HINCRBY test_hincrby_hash counter 1.5").await, "ERR value is not an integer or out of range"));
+    }
+
+    #[tokio::test]
+    async fn should_increment_float_fields() {
+        assert!(matches_bulk(interpret_inline("HINCRBYFLOAT test_hincrbyfloat_hash price 10.5").await, "10.5"));
+        assert!(matches_bulk(interpret_inline("HINCRBYFLOAT test_hincrbyfloat_hash price 0.1").await, "10.6"));
+
+        interpret_inline("HSET test_hincrbyfloat_hash exponent 5.0e3 text hello").await;
+        assert!(matches_bulk(interpret_inline("HINCRBYFLOAT test_hincrbyfloat_hash exponent 2.0e2").await, "5200"));
+        assert!(matches_error(interpret_inline("HINCRBYFLOAT test_hincrbyfloat_hash text 1").await, "ERR hash value is not a float"));
+        assert!(matches_error(interpret_inline("HINCRBYFLOAT test_hincrbyfloat_hash price abc").await, "ERR value is not a valid float"));
+        assert!(matches_error(
+            interpret_inline("HINCRBYFLOAT test_hincrbyfloat_hash price inf").await,
+            "ERR value is NaN or Infinity"
+        ));
+        interpret_inline("HSET test_hincrbyfloat_hash max 1.7e308").await;
+        assert!(matches_error(
+            interpret_inline("HINCRBYFLOAT test_hincrbyfloat_hash max 1.7e308").await,
+            "ERR increment would produce NaN or Infinity"
+        ));
+        assert!(matches_bulk(interpret_inline("HGET test_hincrbyfloat_hash price").await, "10.6"));
+    }
+
+    #[tokio::test]
+    async fn should_not_create_hash_for_invalid_float_increment() {
+        assert!(matches_error(
+            interpret_inline("HINCRBYFLOAT test_hincrbyfloat_fresh_hash field inf").await,
+            "ERR value is NaN or Infinity"
+        ));
+        assert!(matches_integer(interpret_inline("EXISTS test_hincrbyfloat_fresh_hash").await, 0));
+    }
+
+    #[tokio::test]
+    async fn should_scan_small_hash_at_once() {
+        interpret_inline("HSET test_hscan_small_hash name alice age 30 city paris").await;
+
+        let RESPFrame::Array(reply) = interpret_inline("HSCAN test_hscan_small_hash 0 COUNT 1").await else { panic!() };
+        assert_eq!(reply[0], bulk("0"));
+        assert_eq!(reply[1], bulks(&["name", "alice", "age", "30", "city", "paris"]));
+
+        let RESPFrame::Array(reply) = interpret_inline("HSCAN test_hscan_small_hash 0 MATCH *a*e").await else { panic!() };
+        assert_eq!(reply[1], bulks(&["name", "alice", "age", "30"]));
+
+        assert_eq!(interpret_inline("HSCAN test_hscan_missing 0").await, RESPFrame::Array(vec![bulk("0"), bulks(&[])]));
+    }
+
+    #[tokio::test]
+    async fn should_scan_large_hash_with_cursor() {
+        insert_value("test_hscan_large_hash", large_hash(500)).await;
+
+        let mut seen = HashSet::new();
+        let mut cursor = "0".to_owned();
+        let mut calls = 0;
+        loop {
+            let request = format!("HSCAN test_hscan_large_hash {} MATCH field1* COUNT 50", cursor);
+            let RESPFrame::Array(mut reply) = interpret_inline(&request).await else { panic!() };
+            let entries = reply.pop().unwrap();
+            let RESPFrame::Bulk(next) = reply.pop().unwrap() else { panic!() };
+
+            for (field, value) in pairs(entries) {
+                assert!(field.starts_with(b"field1"));
+                assert_eq!(field[5..], value[..]);
+                assert!(seen.insert(field));
+            }
+
+            calls += 1;
+            cursor = String::from_utf8(next.to_vec()).unwrap();
+            if cursor == "0" { break }
+        }
+
+        // field1, field10-19 and field100-199
+        assert_eq!(111, seen.len());
+        assert!(calls >= 10);
+    }
+
+    #[rstest]
+    #[case("HSCAN test_hscan_bad_hash abc", "ERR invalid cursor")]
+    #[case("HSCAN test_hscan_bad_hash -1", "ERR invalid cursor")]
+    #[case("HSCAN test_hscan_bad_hash 0 COUNT 0", "ERR syntax error")]
+    #[case("HSCAN test_hscan_bad_hash 0 COUNT many", "ERR value is not an integer or out of range")]
+    #[case("HSCAN test_hscan_bad_hash 0 MATCH", "ERR syntax error")]
+    #[case("HSCAN test_hscan_bad_hash 0 TYPE string", "ERR syntax error")]
+    #[tokio::test]
+    async fn should_reject_bad_scan_arguments(#[case] request: &str, #[case] expected_error: &str) {
+        assert!(matches_error(interpret_inline(request).await, expected_error));
+    }
+
+    #[tokio::test]
+    async fn should_pick_random_fields() {
+        interpret_inline("HSET test_hrandfield_hash a 1 b 2 c 3").await;
+        let fields = ["a", "b", "c"];
+
+        let RESPFrame::Bulk(field) = interpret_inline("HRANDFIELD test_hrandfield_hash").await else { panic!() };
+        assert!(fields.iter().any(|&expected| field == expected));
+
+        let RESPFrame::Array(picked) = interpret_inline("HRANDFIELD test_hrandfield_hash 5").await else { panic!() };
+        assert_eq!(3, picked.len());
+
+        let RESPFrame::Array(picked) = interpret_inline("HRANDFIELD test_hrandfield_hash 2").await else { panic!() };
+        assert_eq!(2, picked.len());
+        assert_ne!(picked[0], picked[1]);
+
+        let RESPFrame::Array(picked) = interpret_inline("HRANDFIELD test_hrandfield_hash -10").await else { panic!() };
+        assert_eq!(10, picked.len());
+        assert!(picked.iter().all(|field| fields.iter().any(|&expected| *field == bulk(expected))));
+
+        assert_eq!(interpret_inline("HRANDFIELD test_hrandfield_hash 0").await, bulks(&[]));
+        assert!(matches!(interpret_inline("HRANDFIELD test_hrandfield_missing").await, RESPFrame::Null));
+        assert_eq!(interpret_inline("HRANDFIELD test_hrandfield_missing 3").await, bulks(&[]));
+    }
+
+    #[tokio::test]
+    async fn should_pick_random_fields_with_values() {
+        interpret_inline("HSET test_hrandfield_values_hash a 1 b 2").await;
+        let expected: HashSet<(Bytes, Bytes)> = vec![
+            (Bytes::from("a"), Bytes::from("1")),
+            (Bytes::from("b"), Bytes::from("2")),
+        ].into_iter().collect();
+
+        let response = interpret_inline("HRANDFIELD test_hrandfield_values_hash 2 withvalues").await;
+        assert_eq!(expected, pairs(response));
+
+        let mut connection = ConnectionState { protocol: RESPProtocol::RESP3, ..ConnectionState::default() };
+        let RESPFrame::Array(picked) = interpret_inline_with(&mut connection, "HRANDFIELD test_hrandfield_values_hash -3 WITHVALUES").await
+        else { panic!() };
+        assert_eq!(3, picked.len());
+        assert!(picked.into_iter().all(|pair| match pair {
+            RESPFrame::Array(pair) => expected.iter().any(|(field, value)| pair == vec![RESPFrame::Bulk(field.clone()), RESPFrame::Bulk(value.clone())]),
+            _ => false,
+        }));
+    }
+
+    #[rstest]
+    #[case("HRANDFIELD test_hrandfield_bad_hash 1 VALUES", "ERR syntax error")]
+    #[case("HRANDFIELD test_hrandfield_bad_hash one", "ERR value is not an integer or out of range")]
+    #[case("HRANDFIELD test_hrandfield_bad_hash -9223372036854775807 WITHVALUES", "ERR value is out of range")]
+    #[case("HRANDFIELD test_hrandfield_bad_hash -100000000000", "ERR value is out of range")]
+    #[case("HRANDFIELD test_hrandfield_bad_hash -1048577", "ERR value is out of range")]
+    #[case("HRANDFIELD test_hrandfield_bad_hash 1 WITHVALUES extra", "ERR wrong number of arguments for 'hrandfield' command")]
+    #[tokio::test]
+    async fn should_reject_bad_random_arguments(#[case] request: &str, #[case] expected_error: &str) {
+        assert!(matches_error(interpret_inline(request).await, expected_error));
+    }
+
+    #[rstest]
+    #[case("HSET test_hash_wrongtype field value")]
+    #[case("HGET test_hash_wrongtype field")]
+    #[case("HMGET test_hash_wrongtype field")]
+    #[case("HDEL test_hash_wrongtype field")]
+    #[case("HGETALL test_hash_wrongtype")]
+    #[case("HINCRBY test_hash_wrongtype field 1")]
+    #[case("HINCRBYFLOAT test_hash_wrongtype field 1")]
+    #[case("HSCAN test_hash_wrongtype 0")]
+    #[case("HRANDFIELD test_hash_wrongtype")]
+    #[case("HMSET test_hash_wrongtype field value")]
+    #[case("HSETNX test_hash_wrongtype field value")]
+    #[case("HLEN test_hash_wrongtype")]
+    #[case("HEXISTS test_hash_wrongtype field")]
+    #[case("HSTRLEN test_hash_wrongtype field")]
+    #[case("HKEYS test_hash_wrongtype")]
+    #[case("HVALS test_hash_wrongtype")]
+    #[tokio::test]
+    async fn should_reject_other_types(#[case] request: &str) {
+        interpret_inline("SET test_hash_wrongtype value").await;
+
+        let response = interpret_inline(request).await;
+
+        assert!(matches_error(response, "WRONGTYPE Operation against a key holding the wrong kind of value"));
+    }
+}
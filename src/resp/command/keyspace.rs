@@ -0,0 +1,308 @@
+use bytes::Bytes;
+
+use crate::{error::RedisError, resp::frame::RESPFrame};
+
+use super::{parse_integer, CommandContext, CommandResult};
+
+/**
+ * DEL key [key ...]
+ */
+pub fn del(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let deleted = args.iter().filter(|key| ctx.store.del(key)).count();
+
+    Ok(RESPFrame::Integer(deleted as i64))
+}
+
+/**
+ * EXISTS key [key ...]
+ * Keys given more than once are counted each time
+ */
+pub fn exists(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let existing = args.iter().filter(|key| ctx.store.exists(key)).count();
+
+    Ok(RESPFrame::Integer(existing as i64))
+}
+
+/**
+ * TYPE key
+ */
+pub fn type_(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    Ok(RESPFrame::Simple(ctx.store.type_name(key).to_owned()))
+}
+
+/**
+ * RENAME key newkey
+ */
+pub fn rename(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, new_key] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    if !ctx.store.rename(key, new_key) {
+        return Err(RedisError::NoSuchKey)
+    }
+
+    Ok(RESPFrame::Simple("OK".to_owned()))
+}
+
+/**
+ * RENAMENX key newkey
+ * Only renames if newkey doesn't exist
+ */
+pub fn renamenx(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, new_key] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    if !ctx.store.exists(key) {
+        return Err(RedisError::NoSuchKey)
+    }
+    if ctx.store.exists(new_key) {
+        return Ok(RESPFrame::Integer(0))
+    }
+
+    ctx.store.rename(key, new_key);
+    Ok(RESPFrame::Integer(1))
+}
+
+/**
+ * COPY source destination [DB destination-db] [REPLACE]
+ * Only database 0 exists
+ */
+pub fn copy(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [source, destination, options @ ..] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let mut options = options;
+
+    let mut replace = false;
+    loop {
+        match options {
+            [] => break,
+            [option, rest @ ..] if option.eq_ignore_ascii_case(b"REPLACE") => {
+                replace = true;
+                options = rest
+            },
+            [option, db, rest @ ..] if option.eq_ignore_ascii_case(b"DB") => {
+                if parse_integer(db)? != 0 {
+                    return Err(RedisError::Custom("DB index is out of range".to_owned()))
+                }
+                options = rest
+            },
+            _ => return Err(RedisError::Syntax)
+        }
+    }
+
+    if source == destination {
+        return Err(RedisError::Custom("source and destination objects are the same".to_owned()))
+    }
+
+    let copied = ctx.store.copy(source, destination, replace);
+    Ok(RESPFrame::Integer(copied as i64))
+}
+
+/**
+ * OBJECT <subcommand>
+ * Only runs with a subcommand
+ */
+pub fn object(ctx: &mut CommandContext, _args: &[Bytes]) -> CommandResult {
+    Err(ctx.wrong_arity())
+}
+
+/**
+ * OBJECT ENCODING key
+ */
+pub fn object_encoding(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    Ok(ctx.store.encoding(key).map_or(RESPFrame::Null, |encoding| RESPFrame::Bulk(Bytes::from(encoding))))
+}
+
+/**
+ * OBJECT HELP
+ */
+pub fn object_help(_ctx: &mut CommandContext, _args: &[Bytes]) -> CommandResult {
+    let help = [
+        "OBJECT <subcommand> [<arg> [value] [opt] ...]. Subcommands are:",
+        "ENCODING <key>",
+        "    Return the kind of internal representation used in order to store the value",
+        "    associated with a <key>.",
+        "HELP",
+        "    Print this help.",
+    ];
+
+    Ok(RESPFrame::Array(help.iter().map(|line| RESPFrame::Simple(line.to_string())).collect()))
+}
+
+/**
+ * RANDOMKEY
+ */
+pub fn randomkey(ctx: &mut CommandContext, _args: &[Bytes]) -> CommandResult {
+    Ok(ctx.store.random_key().map_or(RESPFrame::Null, RESPFrame::Bulk))
+}
+
+/**
+ * DBSIZE
+ */
+pub fn dbsize(ctx: &mut CommandContext, _args: &[Bytes]) -> CommandResult {
+    Ok(RESPFrame::Integer(ctx.store.dbsize() as i64))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::super::test_utils::{insert_value, interpret_inline, matches_bulk, matches_error, matches_integer};
+    use super::*;
+    use crate::{clock::{Clock, MockClockSession}, store::RedisValue};
+    use rstest::rstest;
+
+    #[tokio::test]
+    async fn should_delete_keys() {
+        interpret_inline("SET test_del_a 1").await;
+        interpret_inline("SET test_del_b 2").await;
+
+        assert!(matches_integer(interpret_inline("DEL test_del_a test_del_b test_del_missing").await, 2));
+        assert!(matches_integer(interpret_inline("DEL test_del_a").await, 0));
+        assert!(matches!(interpret_inline("GET test_del_b").await, RESPFrame::Null));
+    }
+
+    #[tokio::test]
+    async fn should_count_existing_keys() {
+        interpret_inline("SET test_exists_key value").await;
+
+        assert!(matches_integer(
+            interpret_inline("EXISTS test_exists_key test_exists_missing test_exists_key").await,
+            2
+        ));
+    }
+
+    #[rstest]
+    #[case("test_type_string", "string")]
+    #[case("test_type_list", "list")]
+    #[case("test_type_hash", "hash")]
+    #[case("test_type_set", "set")]
+    #[case("test_type_zset", "zset")]
+    #[case("test_type_stream", "stream")]
+    #[case("test_type_missing", "none")]
+    #[tokio::test]
+    async fn should_reply_type(#[case] key: &str, #[case] expected: &str) {
+        interpret_inline("SET test_type_string value").await;
+        insert_value("test_type_list", RedisValue::List(Default::default())).await;
+        insert_value("test_type_hash", RedisValue::Hash(Default::default())).await;
+        insert_value("test_type_set", RedisValue::Set(Default::default())).await;
+        insert_value("test_type_zset", RedisValue::SortedSet(Default::default())).await;
+        insert_value("test_type_stream", RedisValue::Stream(Default::default())).await;
+
+        let response = interpret_inline(&format!("TYPE {}", key)).await;
+
+        assert!(matches!(response, RESPFrame::Simple(s) if s == expected));
+    }
+
+    #[tokio::test]
+    async fn should_rename_key() {
+        interpret_inline("SET test_rename_from value").await;
+        interpret_inline("SET test_rename_to overwritten").await;
+
+        assert!(matches!(interpret_inline("RENAME test_rename_from test_rename_to").await, RESPFrame::Simple(s) if s == "OK"));
+        assert!(matches!(interpret_inline("GET test_rename_from").await, RESPFrame::Null));
+        assert!(matches_bulk(interpret_inline("GET test_rename_to").await, "value"));
+        assert!(matches_error(interpret_inline("RENAME test_rename_from test_rename_to").await, "ERR no such key"));
+    }
+
+    #[tokio::test]
+    async fn should_rename_only_to_missing_key() {
+        interpret_inline("SET test_renamenx_from value").await;
+        interpret_inline("SET test_renamenx_existing other").await;
+
+        assert!(matches_integer(interpret_inline("RENAMENX test_renamenx_from test_renamenx_existing").await, 0));
+        assert!(matches_integer(interpret_inline("RENAMENX test_renamenx_from test_renamenx_to").await, 1));
+        assert!(matches_bulk(interpret_inline("GET test_renamenx_to").await, "value"));
+        assert!(matches_bulk(interpret_inline("GET test_renamenx_existing").await, "other"));
+        assert!(matches_error(interpret_inline("RENAMENX test_renamenx_from test_renamenx_to").await, "ERR no such key"));
+    }
+
+    #[tokio::test]
+    async fn should_move_ttl_with_rename_and_copy() {
+        let _session = MockClockSession::new();
+        Clock::mock_freeze();
+
+        interpret_inline("SET test_ttl_move_from value PX 100").await;
+        interpret_inline("SET test_ttl_move_to persistent").await;
+        interpret_inline("SET test_ttl_copy_to persistent").await;
+
+        interpret_inline("RENAME test_ttl_move_from test_ttl_move_to").await;
+        assert!(matches_integer(interpret_inline("COPY test_ttl_move_to test_ttl_copy_to REPLACE").await, 1));
+        assert!(matches_bulk(interpret_inline("GET test_ttl_copy_to").await, "value"));
+
+        Clock::mock_advance(Duration::from_millis(100));
+        assert!(matches!(interpret_inline("GET test_ttl_move_to").await, RESPFrame::Null));
+        assert!(matches!(interpret_inline("GET test_ttl_copy_to").await, RESPFrame::Null));
+    }
+
+    #[tokio::test]
+    async fn should_copy_key() {
+        interpret_inline("SET test_copy_source value").await;
+        interpret_inline("SET test_copy_existing other").await;
+
+        assert!(matches_integer(interpret_inline("COPY test_copy_source test_copy_destination DB 0").await, 1));
+        assert!(matches_integer(interpret_inline("COPY test_copy_source test_copy_existing").await, 0));
+        assert!(matches_integer(interpret_inline("COPY test_copy_missing test_copy_destination REPLACE").await, 0));
+        assert!(matches_bulk(interpret_inline("GET test_copy_source").await, "value"));
+        assert!(matches_bulk(interpret_inline("GET test_copy_destination").await, "value"));
+        assert!(matches_bulk(interpret_inline("GET test_copy_existing").await, "other"));
+    }
+
+    #[rstest]
+    #[case("COPY test_copy_same test_copy_same", "ERR source and destination objects are the same")]
+    #[case("COPY test_copy_a test_copy_b DB 1", "ERR DB index is out of range")]
+    #[case("COPY test_copy_a test_copy_b DB zero", "ERR value is not an integer or out of range")]
+    #[case("COPY test_copy_a test_copy_b REPLACE extra", "ERR syntax error")]
+    #[tokio::test]
+    async fn should_reject_bad_copy(#[case] request: &str, #[case] expected_error: &str) {
+        assert!(matches_error(interpret_inline(request).await, expected_error));
+    }
+
+    #[rstest]
+    #[case("12345", "int")]
+    #[case("hello", "embstr")]
+    #[case("\"0123456789012345678901234567890123456789012345\"", "raw")]
+    #[tokio::test]
+    async fn should_reply_object_encoding(#[case] value: &str, #[case] expected: &str) {
+        interpret_inline(&format!("SET test_object_encoding_{} {}", expected, value)).await;
+
+        let response = interpret_inline(&format!("OBJECT ENCODING test_object_encoding_{}", expected)).await;
+
+        assert!(matches_bulk(response, expected));
+    }
+
+    #[tokio::test]
+    async fn should_reply_object_encoding_of_collections() {
+        insert_value("test_object_encoding_list", RedisValue::List(Default::default())).await;
+
+        assert!(matches_bulk(interpret_inline("OBJECT ENCODING test_object_encoding_list").await, "listpack"));
+        assert!(matches!(interpret_inline("OBJECT ENCODING test_object_encoding_missing").await, RESPFrame::Null));
+    }
+
+    #[tokio::test]
+    async fn should_reply_object_help() {
+        assert!(matches!(interpret_inline("OBJECT HELP").await, RESPFrame::Array(lines) if !lines.is_empty()));
+        assert!(matches_error(interpret_inline("OBJECT").await, "ERR wrong number of arguments for 'object' command"));
+        assert!(matches_error(interpret_inline("OBJECT FREQ key").await, "ERR unknown subcommand 'FREQ'. Try OBJECT HELP."));
+    }
+
+    #[tokio::test]
+    async fn should_reply_random_key_and_dbsize() {
+        interpret_inline("SET test_randomkey value").await;
+
+        assert!(matches!(interpret_inline("RANDOMKEY").await, RESPFrame::Bulk(_)));
+        assert!(matches!(interpret_inline("DBSIZE").await, RESPFrame::Integer(n) if n >= 1));
+    }
+}
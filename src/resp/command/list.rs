@@ -0,0 +1,1121 @@
+use std::sync::Arc;
+
+use bytes::Bytes;
+
+use crate::{error::RedisError, resp::frame::RESPFrame, store::{EpochMillisecond, QuickList, RedisStore, Served}};
+
+use super::{canonical_float, canonical_integer, clamp_range, parse_integer, CommandContext, CommandResult};
+
+/**
+ * End of a list, named as in LMOVE and BLMOVE
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListDirection {
+    LEFT,
+    RIGHT,
+}
+
+impl ListDirection {
+    fn parse(arg: &[u8]) -> Result<Self, RedisError> {
+        match arg.to_ascii_uppercase().as_slice() {
+            b"LEFT" => Ok(ListDirection::LEFT),
+            b"RIGHT" => Ok(ListDirection::RIGHT),
+            _ => Err(RedisError::Syntax)
+        }
+    }
+
+    fn push(&self, list: &mut QuickList, value: Bytes) {
+        match self {
+            ListDirection::LEFT => list.push_front(value),
+            ListDirection::RIGHT => list.push_back(value),
+        }
+    }
+
+    fn pop(&self, list: &mut QuickList) -> Option<Bytes> {
+        match self {
+            ListDirection::LEFT => list.pop_front(),
+            ListDirection::RIGHT => list.pop_back(),
+        }
+    }
+}
+
+/**
+ * LPUSH key element [element ...]
+ * Elements are pushed one at a time, so end up in reverse order
+ */
+pub fn lpush(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    push(ctx, args, ListDirection::LEFT, false)
+}
+
+/**
+ * RPUSH key element [element ...]
+ */
+pub fn rpush(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    push(ctx, args, ListDirection::RIGHT, false)
+}
+
+/**
+ * LPUSHX key element [element ...]
+ * Only pushes if the list exists
+ */
+pub fn lpushx(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    push(ctx, args, ListDirection::LEFT, true)
+}
+
+/**
+ * RPUSHX key element [element ...]
+ * Only pushes if the list exists
+ */
+pub fn rpushx(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    push(ctx, args, ListDirection::RIGHT, true)
+}
+
+/**
+ * LPOP key [count]
+ */
+pub fn lpop(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    pop(ctx, args, ListDirection::LEFT)
+}
+
+/**
+ * RPOP key [count]
+ */
+pub fn rpop(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    pop(ctx, args, ListDirection::RIGHT)
+}
+
+/**
+ * LLEN key
+ */
+pub fn llen(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let length = ctx.store.get_list(key)?.map_or(0, QuickList::len);
+    Ok(RESPFrame::Integer(length as i64))
+}
+
+/**
+ * LRANGE key start stop
+ */
+pub fn lrange(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, start, stop] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let (start, stop) = (parse_integer(start)?, parse_integer(stop)?);
+
+    let Some(list) = ctx.store.get_list(key)? else {
+        return Ok(RESPFrame::Array(vec![]))
+    };
+    let Some(range) = clamp_range(start, stop, list.len()) else {
+        return Ok(RESPFrame::Array(vec![]))
+    };
+
+    Ok(RESPFrame::Array(
+        list.iter_from(*range.start())
+            .take(range.end() - range.start() + 1)
+            .map(|element| RESPFrame::Bulk(element.clone()))
+            .collect()
+    ))
+}
+
+/**
+ * LINDEX key index
+ * Negative indexes count back from the end of the list
+ */
+pub fn lindex(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, index] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let index = parse_integer(index)?;
+
+    let element = ctx.store.get_list(key)?
+        .and_then(|list| resolve_index(index, list.len()).and_then(|index| list.get(index)));
+
+    Ok(element.map_or(RESPFrame::Null, |element| RESPFrame::Bulk(element.clone())))
+}
+
+/**
+ * LSET key index element
+ */
+pub fn lset(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, index, element] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let index = parse_integer(index)?;
+
+    let Some(list) = ctx.store.get_list_mut(key)? else {
+        return Err(RedisError::NoSuchKey)
+    };
+    let updated = resolve_index(index, list.len()).is_some_and(|index| list.set(index, element.clone()));
+    if !updated {
+        return Err(RedisError::Custom("index out of range".to_owned()))
+    }
+
+    Ok(RESPFrame::Simple("OK".to_owned()))
+}
+
+/**
+ * LREM key count element
+ * Removes from the head if count is positive, the tail if negative, or everywhere if zero
+ */
+pub fn lrem(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, count, element] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let count = parse_integer(count)?;
+
+    let Some(list) = ctx.store.get_list_mut(key)? else {
+        return Ok(RESPFrame::Integer(0))
+    };
+    let limit = if count == 0 { usize::MAX } else { count.unsigned_abs() as usize };
+    let removed = list.remove_matching(element, limit, count < 0);
+
+    if list.is_empty() {
+        ctx.store.del(key);
+    }
+    Ok(RESPFrame::Integer(removed as i64))
+}
+
+/**
+ * LTRIM key start stop
+ */
+pub fn ltrim(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, start, stop] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let (start, stop) = (parse_integer(start)?, parse_integer(stop)?);
+
+    if let Some(list) = ctx.store.get_list_mut(key)? {
+        list.trim(clamp_range(start, stop, list.len()).map(|range| (*range.start(), *range.end())));
+
+        if list.is_empty() {
+            ctx.store.del(key);
+        }
+    }
+
+    Ok(RESPFrame::Simple("OK".to_owned()))
+}
+
+/**
+ * LINSERT key <BEFORE | AFTER> pivot element
+ * Replies with the new length, or -1 if the pivot isn't found
+ */
+pub fn linsert(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, position, pivot, element] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let after = match position.to_ascii_uppercase().as_slice() {
+        b"BEFORE" => false,
+        b"AFTER" => true,
+        _ => return Err(RedisError::Syntax)
+    };
+
+    let Some(list) = ctx.store.get_list_mut(key)? else {
+        return Ok(RESPFrame::Integer(0))
+    };
+    let Some(index) = list.iter().position(|existing| existing == pivot) else {
+        return Ok(RESPFrame::Integer(-1))
+    };
+
+    list.insert(if after { index + 1 } else { index }, element.clone());
+    Ok(RESPFrame::Integer(list.len() as i64))
+}
+
+/**
+ * LPOS key element [RANK rank] [COUNT num-matches] [MAXLEN len]
+ * A negative rank searches from the tail, and COUNT 0 returns every match
+ */
+pub fn lpos(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, element, options @ ..] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let (mut rank, mut count, mut maxlen) = (1, None, 0);
+    let mut options = options;
+    loop {
+        match options {
+            [] => break,
+            [option, value, rest @ ..] if option.eq_ignore_ascii_case(b"RANK") => {
+                rank = parse_integer(value)?;
+                if rank == i64::MIN {
+                    return Err(RedisError::Custom(format!(
+                        "value is out of range, value must between {} and {}", -i64::MAX, i64::MAX
+                    )))
+                }
+                if rank == 0 {
+                    return Err(RedisError::Custom(
+                        "RANK can't be zero: use 1 to start from the first match, 2 from the second ... \
+                        or use negative to start from the end of the list".to_owned()
+                    ))
+                }
+                options = rest
+            },
+            [option, value, rest @ ..] if option.eq_ignore_ascii_case(b"COUNT") => {
+                count = Some(parse_non_negative(value, "COUNT can't be negative")?);
+                options = rest
+            },
+            [option, value, rest @ ..] if option.eq_ignore_ascii_case(b"MAXLEN") => {
+                maxlen = parse_non_negative(value, "MAXLEN can't be negative")?;
+                options = rest
+            },
+            _ => return Err(RedisError::Syntax)
+        }
+    }
+
+    let positions = match ctx.store.get_list(key)? {
+        None => vec![],
+        Some(list) => {
+            let skip = rank.unsigned_abs() as usize - 1;
+            let limit = match count {
+                Some(0) => usize::MAX,
+                Some(count) => count,
+                None => 1,
+            };
+
+            if rank > 0 {
+                find_positions(list.iter().enumerate(), element, skip, limit, maxlen)
+            } else {
+                let last = list.len() - 1;
+                find_positions(list.iter().rev().enumerate().map(|(i, existing)| (last - i, existing)), element, skip, limit, maxlen)
+            }
+        }
+    };
+
+    match count {
+        Some(_) => Ok(RESPFrame::Array(
+            positions.into_iter().map(|position| RESPFrame::Integer(position as i64)).collect()
+        )),
+        None => Ok(positions.first().map_or(RESPFrame::Null, |&position| RESPFrame::Integer(position as i64))),
+    }
+}
+
+/**
+ * LMOVE source destination LEFT|RIGHT LEFT|RIGHT
+ */
+pub fn lmove(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [source, destination, from, to] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let (from, to) = (ListDirection::parse(from)?, ListDirection::parse(to)?);
+
+    let moved = move_element(ctx.store, source, destination, from, to)?;
+    Ok(moved.map_or(RESPFrame::Null, RESPFrame::Bulk))
+}
+
+/**
+ * RPOPLPUSH source destination
+ * Same as LMOVE source destination RIGHT LEFT
+ */
+pub fn rpoplpush(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [source, destination] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let moved = move_element(ctx.store, source, destination, ListDirection::RIGHT, ListDirection::LEFT)?;
+    Ok(moved.map_or(RESPFrame::Null, RESPFrame::Bulk))
+}
+
+/**
+ * LMPOP numkeys key [key ...] LEFT|RIGHT [COUNT count]
+ * Pops from the first non-empty list only
+ */
+pub fn lmpop(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let (keys, direction, count) = parse_multi_pop(args)?;
+
+    Ok(pop_first(ctx.store, &keys, direction, count)?.map_or(RESPFrame::NullArray, multi_pop_reply))
+}
+
+/**
+ * BLPOP key [key ...] timeout
+ * Pops from the first non-empty list, blocking until one is pushed to if all are empty
+ */
+pub fn blpop(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    blocking_pop(ctx, args, ListDirection::LEFT)
+}
+
+/**
+ * BRPOP key [key ...] timeout
+ */
+pub fn brpop(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    blocking_pop(ctx, args, ListDirection::RIGHT)
+}
+
+/**
+ * BLMOVE source destination LEFT|RIGHT LEFT|RIGHT timeout
+ * Blocks on the source only
+ */
+pub fn blmove(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [source, destination, from, to, timeout] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let (from, to) = (ListDirection::parse(from)?, ListDirection::parse(to)?);
+    let deadline = parse_timeout(timeout)?;
+
+    let (source, destination) = (source.clone(), destination.clone());
+    ctx.block_on(vec![source.clone()], deadline, RESPFrame::Null, Arc::new(move |store| {
+        match move_element(store, &source, &destination, from, to) {
+            Ok(Some(element)) => {
+                let (source, destination) = (source.clone(), destination.clone());
+                Some(Served::new(Ok(RESPFrame::Bulk(element)), move |store| {
+                    // Moving back the other way undoes it, and nothing ran since to make that fail
+                    move_element(store, &destination, &source, to, from).unwrap();
+                }))
+            },
+            Ok(None) => None,
+            Err(err) => Some(Served::from(Err(err))),
+        }
+    }))
+}
+
+/**
+ * BLMPOP timeout numkeys key [key ...] LEFT|RIGHT [COUNT count]
+ */
+pub fn blmpop(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [timeout, args @ ..] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let deadline = parse_timeout(timeout)?;
+    let (keys, direction, count) = parse_multi_pop(args)?;
+
+    let watched = keys.clone();
+    ctx.block_on(watched, deadline, RESPFrame::NullArray, Arc::new(move |store| {
+        served_pop(pop_first(store, &keys, direction, count), direction, multi_pop_reply)
+    }))
+}
+
+fn blocking_pop(ctx: &mut CommandContext, args: &[Bytes], direction: ListDirection) -> CommandResult {
+    let [keys @ .., timeout] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let deadline = parse_timeout(timeout)?;
+
+    let keys = keys.to_vec();
+    let watched = keys.clone();
+    ctx.block_on(watched, deadline, RESPFrame::NullArray, Arc::new(move |store| {
+        served_pop(pop_first(store, &keys, direction, 1), direction, |(key, mut elements)| RESPFrame::Array(vec![
+            RESPFrame::Bulk(key),
+            RESPFrame::Bulk(elements.remove(0))
+        ]))
+    }))
+}
+
+/**
+ * Serves a blocked client what was popped, pushing it back where it came from if the client is gone
+ */
+fn served_pop(
+    popped: Result<Option<(Bytes, Vec<Bytes>)>, RedisError>,
+    direction: ListDirection,
+    reply: fn((Bytes, Vec<Bytes>)) -> RESPFrame
+) -> Option<Served> {
+    match popped {
+        Ok(Some((key, elements))) => {
+            let restored = elements.clone();
+            Some(Served::new(Ok(reply((key.clone(), elements))), move |store| {
+                // Nothing ran since the pop, so key is still a list or missing
+                let list = store.get_list_or_insert(&key).unwrap();
+                for element in restored.into_iter().rev() {
+                    direction.push(list, element);
+                }
+            }))
+        },
+        Ok(None) => None,
+        Err(err) => Some(Served::from(Err(err))),
+    }
+}
+
+/**
+ * Pops up to count elements from the first non-empty list, along with its key
+ */
+fn pop_first(
+    store: &mut RedisStore,
+    keys: &[Bytes],
+    direction: ListDirection,
+    count: usize
+) -> Result<Option<(Bytes, Vec<Bytes>)>, RedisError> {
+    for key in keys {
+        let Some(list) = store.get_list_mut(key)? else { continue };
+
+        let elements = (0..count).map_while(|_| direction.pop(list)).collect();
+        if list.is_empty() {
+            store.del(key);
+        }
+        return Ok(Some((key.clone(), elements)))
+    }
+
+    Ok(None)
+}
+
+/**
+ * Moves an element from one end of source to an end of destination
+ * Moving within the same list rotates it, so the key is never deleted
+ */
+fn move_element(
+    store: &mut RedisStore,
+    source: &Bytes,
+    destination: &Bytes,
+    from: ListDirection,
+    to: ListDirection
+) -> Result<Option<Bytes>, RedisError> {
+    if store.get_list(source)?.is_none() {
+        return Ok(None)
+    }
+    // Checked before popping, so a failed move loses nothing
+    store.get_list(destination)?;
+
+    let list = store.get_list_mut(source)?.unwrap();
+    let element = from.pop(list).unwrap();
+    if source == destination {
+        to.push(list, element.clone());
+    } else {
+        if list.is_empty() {
+            store.del(source);
+        }
+        to.push(store.get_list_or_insert(destination)?, element.clone());
+    }
+
+    Ok(Some(element))
+}
+
+/**
+ * Key popped from, along with its popped elements
+ */
+fn multi_pop_reply((key, elements): (Bytes, Vec<Bytes>)) -> RESPFrame {
+    RESPFrame::Array(vec![
+        RESPFrame::Bulk(key),
+        RESPFrame::Array(elements.into_iter().map(RESPFrame::Bulk).collect())
+    ])
+}
+
+/**
+ * numkeys key [key ...] LEFT|RIGHT [COUNT count], as taken by LMPOP and BLMPOP
+ */
+fn parse_multi_pop(args: &[Bytes]) -> Result<(Vec<Bytes>, ListDirection, usize), RedisError> {
+    let numkeys_error = || RedisError::Custom("numkeys should be greater than 0".to_owned());
+
+    let [numkeys, args @ ..] = args else {
+        return Err(RedisError::Syntax)
+    };
+    let numkeys = canonical_integer(numkeys).filter(|&n| n > 0).ok_or_else(numkeys_error)? as usize;
+    if numkeys >= args.len() {
+        return Err(RedisError::Syntax)
+    }
+
+    let (keys, options) = args.split_at(numkeys);
+    let direction = ListDirection::parse(&options[0])?;
+    let count = match &options[1..] {
+        [] => 1,
+        [option, count] if option.eq_ignore_ascii_case(b"COUNT") => canonical_integer(count)
+            .filter(|&n| n > 0)
+            .ok_or_else(|| RedisError::Custom("count should be greater than 0".to_owned()))? as usize,
+        _ => return Err(RedisError::Syntax)
+    };
+
+    Ok((keys.to_vec(), direction, count))
+}
+
+/**
+ * Deadline for a timeout in seconds, where 0 blocks forever
+ */
+fn parse_timeout(arg: &[u8]) -> Result<Option<EpochMillisecond>, RedisError> {
+    let seconds = canonical_float(arg)
+        .ok_or_else(|| RedisError::Custom("timeout is not a float or out of range".to_owned()))?;
+    if seconds < 0.0 {
+        return Err(RedisError::Custom("timeout is negative".to_owned()))
+    }
+    if !seconds.is_finite() || seconds * 1000.0 > i64::MAX as f64 {
+        return Err(RedisError::Custom("timeout is out of range".to_owned()))
+    }
+
+    let milliseconds = (seconds * 1000.0) as EpochMillisecond;
+    Ok((milliseconds > 0).then(|| RedisStore::get_unix_time().saturating_add(milliseconds)))
+}
+
+fn push(ctx: &mut CommandContext, args: &[Bytes], direction: ListDirection, only_existing: bool) -> CommandResult {
+    let [key, elements @ ..] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let list = if only_existing {
+        match ctx.store.get_list_mut(key)? {
+            Some(list) => list,
+            None => return Ok(RESPFrame::Integer(0)),
+        }
+    } else {
+        ctx.store.get_list_or_insert(key)?
+    };
+
+    for element in elements {
+        direction.push(list, element.clone());
+    }
+    Ok(RESPFrame::Integer(list.len() as i64))
+}
+
+/**
+ * Pops a single element, or with a count replies with an array of up to that many
+ */
+fn pop(ctx: &mut CommandContext, args: &[Bytes], direction: ListDirection) -> CommandResult {
+    let (key, count) = match args {
+        [key] => (key, None),
+        [key, count] => (key, Some(parse_non_negative(count, "value is out of range, must be positive")?)),
+        _ => return Err(ctx.wrong_arity())
+    };
+
+    let Some(list) = ctx.store.get_list_mut(key)? else {
+        return Ok(if count.is_some() { RESPFrame::NullArray } else { RESPFrame::Null })
+    };
+
+    let reply = match count {
+        None => direction.pop(list).map_or(RESPFrame::Null, RESPFrame::Bulk),
+        Some(count) => RESPFrame::Array(
+            (0..count).map_while(|_| direction.pop(list)).map(RESPFrame::Bulk).collect()
+        ),
+    };
+
+    if list.is_empty() {
+        ctx.store.del(key);
+    }
+    Ok(reply)
+}
+
+/**
+ * Index from the head, where negative indexes count back from the tail
+ */
+fn resolve_index(index: i64, length: usize) -> Option<usize> {
+    let index = if index < 0 { index + length as i64 } else { index };
+
+    (0..length as i64).contains(&index).then_some(index as usize)
+}
+
+fn parse_non_negative(arg: &[u8], message: &str) -> Result<usize, RedisError> {
+    let value = parse_integer(arg)?;
+    if value < 0 {
+        return Err(RedisError::Custom(message.to_owned()))
+    }
+
+    Ok(value as usize)
+}
+
+/**
+ * Positions of matching elements within the first maxlen scanned, if it's non-zero
+ */
+fn find_positions<'a>(
+    elements: impl Iterator<Item = (usize, &'a Bytes)>,
+    element: &[u8],
+    skip: usize,
+    limit: usize,
+    maxlen: usize
+) -> Vec<usize> {
+    elements
+        .take(if maxlen == 0 { usize::MAX } else { maxlen })
+        .filter(|(_, existing)| *existing == element)
+        .map(|(position, _)| position)
+        .skip(skip)
+        .take(limit)
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::super::test_utils::{bulks, insert_value, integers, interpret_inline, matches_bulk, matches_integer, matches_ok, spawn_blocked};
+    use super::*;
+    use crate::{clock::{Clock, MockClockSession}, store::RedisValue};
+    use rstest::rstest;
+
+    #[tokio::test]
+    async fn should_push_to_both_ends() {
+        assert!(matches_integer(interpret_inline("RPUSH test_push_list b c").await, 2));
+        assert!(matches_integer(interpret_inline("LPUSH test_push_list a z").await, 4));
+        assert!(matches_integer(interpret_inline("LLEN test_push_list").await, 4));
+
+        assert_eq!(interpret_inline("LRANGE test_push_list 0 -1").await, bulks(&["z", "a", "b", "c"]));
+        assert!(matches!(interpret_inline("TYPE test_push_list").await, RESPFrame::Simple(s) if s == "list"));
+        assert!(matches_bulk(interpret_inline("OBJECT ENCODING test_push_list").await, "listpack"));
+    }
+
+    #[tokio::test]
+    async fn should_push_only_to_existing_list() {
+        assert!(matches_integer(interpret_inline("LPUSHX test_pushx_list a").await, 0));
+        assert!(matches_integer(interpret_inline("RPUSHX test_pushx_list a").await, 0));
+        assert!(matches_integer(interpret_inline("EXISTS test_pushx_list").await, 0));
+
+        interpret_inline("RPUSH test_pushx_list b").await;
+        assert!(matches_integer(interpret_inline("LPUSHX test_pushx_list a").await, 2));
+        assert!(matches_integer(interpret_inline("RPUSHX test_pushx_list c d").await, 4));
+        assert_eq!(interpret_inline("LRANGE test_pushx_list 0 -1").await, bulks(&["a", "b", "c", "d"]));
+    }
+
+    #[tokio::test]
+    async fn should_pop_from_both_ends() {
+        interpret_inline("RPUSH test_pop_list a b c d e").await;
+
+        assert!(matches_bulk(interpret_inline("LPOP test_pop_list").await, "a"));
+        assert!(matches_bulk(interpret_inline("RPOP test_pop_list").await, "e"));
+        assert_eq!(interpret_inline("LPOP test_pop_list 2").await, bulks(&["b", "c"]));
+        assert_eq!(interpret_inline("RPOP test_pop_list 0").await, bulks(&[]));
+        assert_eq!(interpret_inline("RPOP test_pop_list 5").await, bulks(&["d"]));
+
+        // Popping the last element deletes the list
+        assert!(matches_integer(interpret_inline("EXISTS test_pop_list").await, 0));
+        assert!(matches!(interpret_inline("LPOP test_pop_list").await, RESPFrame::Null));
+        assert!(matches!(interpret_inline("LPOP test_pop_list 1").await, RESPFrame::NullArray));
+    }
+
+    #[tokio::test]
+    async fn should_keep_ttl_when_pushing_and_popping() {
+        let _session = MockClockSession::new();
+        Clock::mock_freeze();
+
+        interpret_inline("RPUSH test_list_ttl a b").await;
+        interpret_inline("EXPIRE test_list_ttl 100").await;
+        interpret_inline("RPUSH test_list_ttl c").await;
+        interpret_inline("LPOP test_list_ttl").await;
+
+        assert!(matches_integer(interpret_inline("TTL test_list_ttl").await, 100));
+    }
+
+    #[rstest]
+    #[case("0 -1", &["a", "b", "c", "d", "e"])]
+    #[case("1 2", &["b", "c"])]
+    #[case("-3 -2", &["c", "d"])]
+    #[case("-100 1", &["a", "b"])]
+    #[case("3 100", &["d", "e"])]
+    #[case("3 1", &[])]
+    #[case("5 10", &[])]
+    #[tokio::test]
+    async fn should_reply_list_range(#[case] range: &str, #[case] expected: &[&str]) {
+        interpret_inline("DEL test_lrange_list").await;
+        interpret_inline("RPUSH test_lrange_list a b c d e").await;
+
+        assert_eq!(interpret_inline(&format!("LRANGE test_lrange_list {}", range)).await, bulks(expected));
+    }
+
+    #[rstest]
+    #[case("0", Some("a"))]
+    #[case("2", Some("c"))]
+    #[case("-1", Some("c"))]
+    #[case("-3", Some("a"))]
+    #[case("3", None)]
+    #[case("-4", None)]
+    #[tokio::test]
+    async fn should_reply_list_index(#[case] index: &str, #[case] expected: Option<&str>) {
+        interpret_inline("DEL test_lindex_list").await;
+        interpret_inline("RPUSH test_lindex_list a b c").await;
+
+        let response = interpret_inline(&format!("LINDEX test_lindex_list {}", index)).await;
+
+        match expected {
+            Some(expected) => assert!(matches_bulk(response, expected)),
+            None => assert!(matches!(response, RESPFrame::Null)),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_set_list_element() {
+        interpret_inline("RPUSH test_lset_list a b c").await;
+
+        assert!(matches_ok(interpret_inline("LSET test_lset_list 0 x").await));
+        assert!(matches_ok(interpret_inline("LSET test_lset_list -1 z").await));
+        assert_eq!(interpret_inline("LRANGE test_lset_list 0 -1").await, bulks(&["x", "b", "z"]));
+    }
+
+    #[rstest]
+    #[case("2", 2, &["b", "c", "a", "b", "a"])]
+    #[case("-2", 2, &["a", "b", "a", "c", "b"])]
+    #[case("0", 4, &["b", "c", "b"])]
+    #[case("1", 1, &["b", "a", "c", "a", "b", "a"])]
+    #[tokio::test]
+    async fn should_remove_list_elements(#[case] count: &str, #[case] expected_removed: i64, #[case] expected: &[&str]) {
+        let key = format!("test_lrem_list_{}", count);
+        interpret_inline(&format!("RPUSH {} a b a c a b a", key)).await;
+
+        assert!(matches_integer(interpret_inline(&format!("LREM {} {} a", key, count)).await, expected_removed));
+        assert_eq!(interpret_inline(&format!("LRANGE {} 0 -1", key)).await, bulks(expected));
+    }
+
+    #[tokio::test]
+    async fn should_delete_list_with_every_element_removed() {
+        interpret_inline("RPUSH test_lrem_all a a").await;
+        interpret_inline("RPUSH test_ltrim_all a b").await;
+
+        assert!(matches_integer(interpret_inline("LREM test_lrem_all 0 a").await, 2));
+        assert!(matches_ok(interpret_inline("LTRIM test_ltrim_all 2 1").await));
+        assert!(matches_integer(interpret_inline("EXISTS test_lrem_all test_ltrim_all").await, 0));
+        assert!(matches_integer(interpret_inline("LREM test_lrem_all 0 a").await, 0));
+    }
+
+    #[rstest]
+    #[case("1 -1", &["b", "c", "d", "e"])]
+    #[case("0 1", &["a", "b"])]
+    #[case("-2 100", &["d", "e"])]
+    #[case("0 -1", &["a", "b", "c", "d", "e"])]
+    #[tokio::test]
+    async fn should_trim_list(#[case] range: &str, #[case] expected: &[&str]) {
+        let key = format!("test_ltrim_list_{}", range.replace(' ', "_"));
+        interpret_inline(&format!("RPUSH {} a b c d e", key)).await;
+
+        assert!(matches_ok(interpret_inline(&format!("LTRIM {} {}", key, range)).await));
+        assert_eq!(interpret_inline(&format!("LRANGE {} 0 -1", key)).await, bulks(expected));
+    }
+
+    #[tokio::test]
+    async fn should_insert_around_pivot() {
+        interpret_inline("RPUSH test_linsert_list a c").await;
+
+        assert!(matches_integer(interpret_inline("LINSERT test_linsert_list BEFORE c b").await, 3));
+        assert!(matches_integer(interpret_inline("LINSERT test_linsert_list after c d").await, 4));
+        assert!(matches_integer(interpret_inline("LINSERT test_linsert_list AFTER missing x").await, -1));
+        assert!(matches_integer(interpret_inline("LINSERT test_linsert_missing AFTER a x").await, 0));
+        assert_eq!(interpret_inline("LRANGE test_linsert_list 0 -1").await, bulks(&["a", "b", "c", "d"]));
+    }
+
+    #[rstest]
+    // a b c 1 2 3 c c
+    #[case("c", Some(2))]
+    #[case("x", None)]
+    #[case("c RANK 2", Some(6))]
+    #[case("c RANK -1", Some(7))]
+    #[case("c RANK -3", Some(2))]
+    #[case("c RANK 4", None)]
+    #[case("c MAXLEN 2", None)]
+    #[case("c RANK -1 MAXLEN 1", Some(7))]
+    #[tokio::test]
+    async fn should_find_list_position(#[case] args: &str, #[case] expected: Option<i64>) {
+        interpret_inline("DEL test_lpos_list").await;
+        interpret_inline("RPUSH test_lpos_list a b c 1 2 3 c c").await;
+
+        let response = interpret_inline(&format!("LPOS test_lpos_list {}", args)).await;
+
+        match expected {
+            Some(expected) => assert!(matches_integer(response, expected)),
+            None => assert!(matches!(response, RESPFrame::Null)),
+        }
+    }
+
+    #[rstest]
+    #[case("c COUNT 2", &[2, 6])]
+    #[case("c COUNT 0", &[2, 6, 7])]
+    #[case("c COUNT 0 RANK 2", &[6, 7])]
+    #[case("c COUNT 2 RANK -1", &[7, 6])]
+    #[case("c COUNT 0 MAXLEN 7", &[2, 6])]
+    #[case("x COUNT 0", &[])]
+    #[tokio::test]
+    async fn should_find_list_positions(#[case] args: &str, #[case] expected: &[i64]) {
+        interpret_inline("DEL test_lpos_count_list").await;
+        interpret_inline("RPUSH test_lpos_count_list a b c 1 2 3 c c").await;
+
+        assert_eq!(interpret_inline(&format!("LPOS test_lpos_count_list {}", args)).await, integers(expected));
+    }
+
+    #[tokio::test]
+    async fn should_read_missing_list_as_empty() {
+        assert!(matches_integer(interpret_inline("LLEN test_list_missing").await, 0));
+        assert_eq!(interpret_inline("LRANGE test_list_missing 0 -1").await, bulks(&[]));
+        assert!(matches!(interpret_inline("LINDEX test_list_missing 0").await, RESPFrame::Null));
+        assert!(matches!(interpret_inline("LPOS test_list_missing a").await, RESPFrame::Null));
+        assert_eq!(interpret_inline("LPOS test_list_missing a COUNT 0").await, integers(&[]));
+        assert!(matches_ok(interpret_inline("LTRIM test_list_missing 0 1").await));
+    }
+
+    #[rstest]
+    #[case("LSET test_list_bad_missing 0 a", "ERR no such key")]
+    #[case("LSET test_list_bad 5 a", "ERR index out of range")]
+    #[case("LSET test_list_bad one a", "ERR value is not an integer or out of range")]
+    #[case("LPOP test_list_bad -1", "ERR value is out of range, must be positive")]
+    #[case("LPOP test_list_bad one", "ERR value is not an integer or out of range")]
+    #[case("RPOP test_list_bad 1 2", "ERR wrong number of arguments for 'rpop' command")]
+    #[case("LRANGE test_list_bad a 1", "ERR value is not an integer or out of range")]
+    #[case("LINSERT test_list_bad BESIDE a b", "ERR syntax error")]
+    #[case("LPOS test_list_bad a RANK 0", "ERR RANK can't be zero: use 1 to start from the first match, 2 from the second ... or use negative to start from the end of the list")]
+    #[case("LPOS test_list_bad a RANK -9223372036854775808", "ERR value is out of range, value must between -9223372036854775807 and 9223372036854775807")]
+    #[case("LPOS test_list_bad a COUNT -1", "ERR COUNT can't be negative")]
+    #[case("LPOS test_list_bad a MAXLEN -1", "ERR MAXLEN can't be negative")]
+    #[case("LPOS test_list_bad a RANK", "ERR syntax error")]
+    #[case("LPOS test_list_bad a FIRST 1", "ERR syntax error")]
+    #[tokio::test]
+    async fn should_reject_bad_list_requests(#[case] request: &str, #[case] expected_error: &str) {
+        interpret_inline("RPUSH test_list_bad a").await;
+
+        let response = interpret_inline(request).await;
+
+        assert!(matches!(response, RESPFrame::Error(s) if s == expected_error));
+    }
+
+    #[rstest]
+    #[case("LPUSH test_list_wrongtype a")]
+    #[case("RPUSHX test_list_wrongtype a")]
+    #[case("LPOP test_list_wrongtype")]
+    #[case("LLEN test_list_wrongtype")]
+    #[case("LRANGE test_list_wrongtype 0 -1")]
+    #[case("LINDEX test_list_wrongtype 0")]
+    #[case("LSET test_list_wrongtype 0 a")]
+    #[case("LREM test_list_wrongtype 0 a")]
+    #[case("LTRIM test_list_wrongtype 0 1")]
+    #[case("LINSERT test_list_wrongtype BEFORE a b")]
+    #[case("LPOS test_list_wrongtype a")]
+    #[case("LMOVE test_list_wrongtype other LEFT LEFT")]
+    #[case("RPOPLPUSH test_list_wrongtype other")]
+    #[case("LMPOP 1 test_list_wrongtype LEFT")]
+    #[case("BLPOP test_list_wrongtype 0")]
+    #[case("BLMOVE test_list_wrongtype other LEFT LEFT 0")]
+    #[case("BLMPOP 0 1 test_list_wrongtype LEFT")]
+    #[tokio::test]
+    async fn should_reject_other_types(#[case] request: &str) {
+        interpret_inline("SET test_list_wrongtype value").await;
+
+        let response = interpret_inline(request).await;
+
+        assert!(matches!(response, RESPFrame::Error(s) if s == "WRONGTYPE Operation against a key holding the wrong kind of value"));
+    }
+
+    #[tokio::test]
+    async fn should_handle_long_lists() {
+        let elements: Vec<String> = (0..1000).map(|i| format!("{:0>20}", i)).collect();
+        insert_value("test_long_list", RedisValue::List(elements.iter().map(|e| Bytes::from(e.clone())).collect())).await;
+
+        assert!(matches_bulk(interpret_inline("OBJECT ENCODING test_long_list").await, "quicklist"));
+        assert!(matches_bulk(interpret_inline("LINDEX test_long_list 600").await, &elements[600]));
+        assert!(matches_integer(interpret_inline(&format!("LPOS test_long_list {}", elements[999])).await, 999));
+
+        let response = interpret_inline("LRANGE test_long_list 500 502").await;
+        let expected: Vec<&str> = elements[500..=502].iter().map(String::as_str).collect();
+        assert_eq!(response, bulks(&expected));
+
+        interpret_inline("LTRIM test_long_list 0 9").await;
+        assert!(matches_bulk(interpret_inline("OBJECT ENCODING test_long_list").await, "listpack"));
+    }
+
+    #[rstest]
+    #[case("LEFT LEFT", "a", &["b", "c"], &["a", "x"])]
+    #[case("LEFT RIGHT", "a", &["b", "c"], &["x", "a"])]
+    #[case("RIGHT LEFT", "c", &["a", "b"], &["c", "x"])]
+    #[case("RIGHT RIGHT", "c", &["a", "b"], &["x", "c"])]
+    #[tokio::test]
+    async fn should_move_between_lists(
+        #[case] directions: &str,
+        #[case] moved: &str,
+        #[case] source: &[&str],
+        #[case] destination: &[&str]
+    ) {
+        let suffix = directions.replace(' ', "_");
+        let (source_key, destination_key) = (format!("test_lmove_source_{}", suffix), format!("test_lmove_destination_{}", suffix));
+        interpret_inline(&format!("RPUSH {} a b c", source_key)).await;
+        interpret_inline(&format!("RPUSH {} x", destination_key)).await;
+
+        let response = interpret_inline(&format!("LMOVE {} {} {}", source_key, destination_key, directions)).await;
+
+        assert!(matches_bulk(response, moved));
+        assert_eq!(interpret_inline(&format!("LRANGE {} 0 -1", source_key)).await, bulks(source));
+        assert_eq!(interpret_inline(&format!("LRANGE {} 0 -1", destination_key)).await, bulks(destination));
+    }
+
+    #[tokio::test]
+    async fn should_move_last_element_to_new_list() {
+        interpret_inline("RPUSH test_rpoplpush_source a").await;
+
+        assert!(matches_bulk(interpret_inline("RPOPLPUSH test_rpoplpush_source test_rpoplpush_destination").await, "a"));
+        assert!(matches_integer(interpret_inline("EXISTS test_rpoplpush_source").await, 0));
+        assert_eq!(interpret_inline("LRANGE test_rpoplpush_destination 0 -1").await, bulks(&["a"]));
+
+        assert!(matches!(interpret_inline("RPOPLPUSH test_rpoplpush_source test_rpoplpush_destination").await, RESPFrame::Null));
+        assert!(matches!(interpret_inline("LMOVE test_rpoplpush_source test_rpoplpush_destination LEFT LEFT").await, RESPFrame::Null));
+    }
+
+    #[rstest]
+    #[case("test_rotate_rpoplpush_list", "RIGHT LEFT", "c", &["c", "a", "b"])]
+    #[case("test_rotate_left_right_list", "LEFT RIGHT", "a", &["b", "c", "a"])]
+    #[case("test_rotate_right_right_list", "RIGHT RIGHT", "c", &["a", "b", "c"])]
+    #[tokio::test]
+    async fn should_rotate_same_list(#[case] key: &str, #[case] directions: &str, #[case] moved: &str, #[case] expected: &[&str]) {
+        interpret_inline(&format!("RPUSH {} a b c", key)).await;
+
+        let response = interpret_inline(&format!("LMOVE {} {} {}", key, key, directions)).await;
+
+        assert!(matches_bulk(response, moved));
+        assert_eq!(interpret_inline(&format!("LRANGE {} 0 -1", key)).await, bulks(expected));
+    }
+
+    #[tokio::test]
+    async fn should_keep_single_element_list_when_rotating() {
+        interpret_inline("RPUSH test_rotate_single_list a").await;
+        interpret_inline("PEXPIRE test_rotate_single_list 100000").await;
+
+        assert!(matches_bulk(interpret_inline("RPOPLPUSH test_rotate_single_list test_rotate_single_list").await, "a"));
+        assert_eq!(interpret_inline("LRANGE test_rotate_single_list 0 -1").await, bulks(&["a"]));
+        assert!(matches!(interpret_inline("PTTL test_rotate_single_list").await, RESPFrame::Integer(ttl) if ttl > 0));
+    }
+
+    #[tokio::test]
+    async fn should_not_lose_element_moving_to_other_type() {
+        interpret_inline("RPUSH test_lmove_safe_source a").await;
+        interpret_inline("SET test_lmove_safe_destination value").await;
+
+        let response = interpret_inline("LMOVE test_lmove_safe_source test_lmove_safe_destination LEFT LEFT").await;
+        assert!(matches!(response, RESPFrame::Error(s) if s.starts_with("WRONGTYPE")));
+        assert_eq!(interpret_inline("LRANGE test_lmove_safe_source 0 -1").await, bulks(&["a"]));
+
+        // A missing source moves nothing, whatever the destination holds
+        let response = interpret_inline("LMOVE test_lmove_safe_missing test_lmove_safe_destination LEFT LEFT").await;
+        assert!(matches!(response, RESPFrame::Null));
+    }
+
+    #[tokio::test]
+    async fn should_pop_from_first_non_empty_list() {
+        interpret_inline("RPUSH test_lmpop_second a b c").await;
+        interpret_inline("RPUSH test_lmpop_third d").await;
+
+        assert_eq!(
+            interpret_inline("LMPOP 3 test_lmpop_first test_lmpop_second test_lmpop_third LEFT").await,
+            RESPFrame::Array(vec![RESPFrame::Bulk(Bytes::from("test_lmpop_second")), bulks(&["a"])])
+        );
+        assert_eq!(
+            interpret_inline("LMPOP 3 test_lmpop_first test_lmpop_second test_lmpop_third RIGHT COUNT 10").await,
+            RESPFrame::Array(vec![RESPFrame::Bulk(Bytes::from("test_lmpop_second")), bulks(&["c", "b"])])
+        );
+        assert!(matches_integer(interpret_inline("EXISTS test_lmpop_second").await, 0));
+        assert_eq!(
+            interpret_inline("LMPOP 2 test_lmpop_first test_lmpop_second left").await,
+            RESPFrame::NullArray
+        );
+    }
+
+    #[rstest]
+    #[case("LMOVE test_lmove_bad_list other UP LEFT", "ERR syntax error")]
+    #[case("LMOVE test_lmove_bad_list other LEFT DOWN", "ERR syntax error")]
+    #[case("LMPOP 0 test_lmove_bad_list LEFT", "ERR numkeys should be greater than 0")]
+    #[case("LMPOP 2 test_lmove_bad_list LEFT", "ERR syntax error")]
+    #[case("LMPOP 1 test_lmove_bad_list LEFT COUNT -1", "ERR count should be greater than 0")]
+    #[case("LMPOP 1 test_lmove_bad_list LEFT LIMIT 1", "ERR syntax error")]
+    #[tokio::test]
+    async fn should_reject_bad_transfer_arguments(#[case] request: &str, #[case] expected_error: &str) {
+        let response = interpret_inline(request).await;
+
+        assert!(matches!(response, RESPFrame::Error(s) if s == expected_error));
+    }
+
+    #[tokio::test]
+    async fn should_serve_client_blocked_on_moved_to_list() {
+        let blocked = spawn_blocked("BRPOP test_lmove_wake_destination 0", "test_lmove_wake_destination").await;
+        interpret_inline("RPUSH test_lmove_wake_source a").await;
+
+        assert!(matches_bulk(interpret_inline("LMOVE test_lmove_wake_source test_lmove_wake_destination LEFT LEFT").await, "a"));
+        assert_eq!(blocked.await.unwrap(), bulks(&["test_lmove_wake_destination", "a"]));
+        assert!(matches_integer(interpret_inline("EXISTS test_lmove_wake_destination").await, 0));
+    }
+
+    #[tokio::test]
+    async fn should_pop_without_blocking_when_available() {
+        interpret_inline("RPUSH test_bpop_ready_list a b c").await;
+
+        assert_eq!(interpret_inline("BLPOP test_bpop_ready_missing test_bpop_ready_list 0").await, bulks(&["test_bpop_ready_list", "a"]));
+        assert_eq!(interpret_inline("BRPOP test_bpop_ready_list 0").await, bulks(&["test_bpop_ready_list", "c"]));
+        assert!(matches_bulk(interpret_inline("BLMOVE test_bpop_ready_list test_bpop_ready_other RIGHT LEFT 0").await, "b"));
+        assert!(matches_integer(interpret_inline("EXISTS test_bpop_ready_list").await, 0));
+
+        interpret_inline("RPUSH test_bpop_ready_list d e f").await;
+        assert_eq!(
+            interpret_inline("BLMPOP 0 2 test_bpop_ready_missing test_bpop_ready_list RIGHT COUNT 2").await,
+            RESPFrame::Array(vec![RESPFrame::Bulk(Bytes::from("test_bpop_ready_list")), bulks(&["f", "e"])])
+        );
+    }
+
+    #[tokio::test]
+    async fn should_block_until_pushed() {
+        let blocked = spawn_blocked("BLPOP test_bpop_wait_list 0", "test_bpop_wait_list").await;
+
+        // The store stays usable while a client is blocked
+        assert!(matches_ok(interpret_inline("SET test_bpop_wait_other value").await));
+        assert!(matches_bulk(interpret_inline("GET test_bpop_wait_other").await, "value"));
+
+        assert!(matches_integer(interpret_inline("RPUSH test_bpop_wait_list a b").await, 2));
+        assert_eq!(blocked.await.unwrap(), bulks(&["test_bpop_wait_list", "a"]));
+        assert_eq!(interpret_inline("LRANGE test_bpop_wait_list 0 -1").await, bulks(&["b"]));
+    }
+
+    #[tokio::test]
+    async fn should_serve_blocked_clients_in_order() {
+        let first = spawn_blocked("BRPOP test_bpop_fifo_list 0", "test_bpop_fifo_list").await;
+        let second = spawn_blocked("BLPOP test_bpop_other_list test_bpop_fifo_list 0", "test_bpop_other_list").await;
+        let third = spawn_blocked("BLMPOP 0 1 test_bpop_fifo_list LEFT COUNT 5", "test_bpop_fifo_list").await;
+
+        interpret_inline("RPUSH test_bpop_fifo_list a").await;
+        assert_eq!(first.await.unwrap(), bulks(&["test_bpop_fifo_list", "a"]));
+        assert!(!second.is_finished());
+
+        interpret_inline("RPUSH test_bpop_fifo_list b c d").await;
+        assert_eq!(second.await.unwrap(), bulks(&["test_bpop_fifo_list", "b"]));
+        assert_eq!(
+            third.await.unwrap(),
+            RESPFrame::Array(vec![RESPFrame::Bulk(Bytes::from("test_bpop_fifo_list")), bulks(&["c", "d"])])
+        );
+        assert!(matches_integer(interpret_inline("EXISTS test_bpop_fifo_list").await, 0));
+    }
+
+    #[tokio::test]
+    async fn should_serve_clients_blocked_on_moved_elements() {
+        let waiting_on_destination = spawn_blocked("BLPOP test_blmove_chain_destination 0", "test_blmove_chain_destination").await;
+        let moving = spawn_blocked("BLMOVE test_blmove_chain_source test_blmove_chain_destination LEFT RIGHT 0", "test_blmove_chain_source").await;
+
+        interpret_inline("LPUSH test_blmove_chain_source a").await;
+        assert!(matches_bulk(moving.await.unwrap(), "a"));
+        assert_eq!(waiting_on_destination.await.unwrap(), bulks(&["test_blmove_chain_destination", "a"]));
+        assert!(matches_integer(interpret_inline("EXISTS test_blmove_chain_source test_blmove_chain_destination").await, 0));
+    }
+
+    #[tokio::test]
+    async fn should_rotate_list_onto_itself() {
+        interpret_inline("RPUSH test_blmove_rotate_list a").await;
+        interpret_inline("PEXPIRE test_blmove_rotate_list 100000").await;
+
+        assert!(matches_bulk(interpret_inline("BLMOVE test_blmove_rotate_list test_blmove_rotate_list LEFT RIGHT 0").await, "a"));
+        assert_eq!(interpret_inline("LRANGE test_blmove_rotate_list 0 -1").await, bulks(&["a"]));
+        assert!(matches!(interpret_inline("PTTL test_blmove_rotate_list").await, RESPFrame::Integer(ttl) if ttl > 0));
+    }
+
+    #[rstest]
+    #[case("BLPOP test_bpop_timeout_list 1.5", RESPFrame::NullArray)]
+    #[case("BLMOVE test_bpop_timeout_list test_bpop_timeout_other LEFT LEFT 1.5", RESPFrame::Null)]
+    #[case("BLMPOP 1.5 1 test_bpop_timeout_list RIGHT", RESPFrame::NullArray)]
+    #[tokio::test(start_paused = true)]
+    async fn should_time_out_against_clock(#[case] request: &'static str, #[case] expected: RESPFrame) {
+        let _session = MockClockSession::new();
+        Clock::mock_freeze();
+
+        let blocked = spawn_blocked(request, "test_bpop_timeout_list").await;
+
+        // Paused time lets the client check its deadline a few times over
+        Clock::mock_advance(Duration::from_millis(1499));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!blocked.is_finished());
+
+        Clock::mock_advance(Duration::from_millis(1));
+        assert_eq!(blocked.await.unwrap(), expected);
+
+        // Timed out clients are no longer served
+        interpret_inline("RPUSH test_bpop_timeout_list a").await;
+        assert!(matches_integer(interpret_inline("LLEN test_bpop_timeout_list").await, 1));
+        interpret_inline("DEL test_bpop_timeout_list").await;
+    }
+
+    #[rstest]
+    #[case("BLPOP test_bpop_bad_list -1", "ERR timeout is negative")]
+    #[case("BLPOP test_bpop_bad_list soon", "ERR timeout is not a float or out of range")]
+    #[case("BRPOP test_bpop_bad_list inf", "ERR timeout is out of range")]
+    #[case("BLMOVE test_bpop_bad_list other UP LEFT 0", "ERR syntax error")]
+    #[case("BLMPOP 0 0 test_bpop_bad_list LEFT", "ERR numkeys should be greater than 0")]
+    #[case("BLMPOP 0 two test_bpop_bad_list LEFT", "ERR numkeys should be greater than 0")]
+    #[case("BLMPOP 0 2 test_bpop_bad_list LEFT", "ERR syntax error")]
+    #[case("BLMPOP 0 1 test_bpop_bad_list UP", "ERR syntax error")]
+    #[case("BLMPOP 0 1 test_bpop_bad_list LEFT COUNT 0", "ERR count should be greater than 0")]
+    #[case("BLMPOP 0 1 test_bpop_bad_list LEFT COUNT 1 COUNT 2", "ERR syntax error")]
+    #[tokio::test]
+    async fn should_reject_bad_blocking_arguments(#[case] request: &str, #[case] expected_error: &str) {
+        let response = interpret_inline(request).await;
+
+        assert!(matches!(response, RESPFrame::Error(s) if s == expected_error));
+    }
+}
@@ -0,0 +1,1155 @@
+use std::{collections::HashMap, ops::RangeInclusive, sync::OnceLock};
+
+use bytes::Bytes;
+
+use crate::{error::RedisError, store::{BlockedAttempt, EpochMillisecond, RedisStore}};
+
+use super::{frame::RESPFrame, interpreter::ConnectionState};
+
+mod bitmap;
+mod connection;
+mod expire;
+mod hash;
+mod keyspace;
+mod list;
+mod server;
+mod string;
+
+pub type CommandResult = Result<RESPFrame, RedisError>;
+
+/**
+ * Handlers run with the store already locked, so each command is atomic
+ * Arguments exclude the command name
+ */
+pub type CommandHandler = fn(&mut CommandContext, &[Bytes]) -> CommandResult;
+
+pub struct CommandContext<'a> {
+    pub command: &'static RedisCommand,
+    pub connection: &'a mut ConnectionState,
+    pub store: &'a mut RedisStore,
+    pub blocking: Option<BlockingRequest>,
+}
+
+impl CommandContext<'_> {
+    pub fn wrong_arity(&self) -> RedisError {
+        self.command.wrong_arity()
+    }
+
+    /**
+     * Replies with the attempt if it succeeds now, otherwise blocks the client on keys until it does
+     * A blocked client is replied to once served, or with the timeout reply past the deadline
+     */
+    pub fn block_on(
+        &mut self,
+        keys: Vec<Bytes>,
+        deadline: Option<EpochMillisecond>,
+        timeout_reply: RESPFrame,
+        attempt: BlockedAttempt
+    ) -> CommandResult {
+        if let Some(served) = attempt(self.store) {
+            return served.reply
+        }
+
+        self.blocking = Some(BlockingRequest { keys, deadline, timeout_reply, attempt });
+        Ok(RESPFrame::Null)
+    }
+}
+
+/**
+ * Keys a command blocks the client on, parked by the interpreter once the store is unlocked
+ * No deadline blocks until served
+ */
+pub struct BlockingRequest {
+    pub keys: Vec<Bytes>,
+    pub deadline: Option<EpochMillisecond>,
+    pub timeout_reply: RESPFrame,
+    pub attempt: BlockedAttempt,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandFlag {
+    Write,      // May modify the keyspace
+    ReadOnly,   // Only reads from the keyspace
+    Admin,      // Server administration
+    Fast,       // Runs in constant or log time
+    Blocking,   // May block the client
+}
+
+impl CommandFlag {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Write => "write",
+            ReadOnly => "readonly",
+            Admin => "admin",
+            Fast => "fast",
+            Blocking => "blocking",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandGroup {
+    Bitmap,
+    Connection,
+    Generic,
+    Hash,
+    List,
+    Server,
+    String,
+}
+
+impl CommandGroup {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CommandGroup::Bitmap => "bitmap",
+            CommandGroup::Connection => "connection",
+            CommandGroup::Generic => "generic",
+            CommandGroup::Hash => "hash",
+            CommandGroup::List => "list",
+            CommandGroup::Server => "server",
+            CommandGroup::String => "string",
+        }
+    }
+}
+
+/**
+ * Legacy key positions within the request, counting the command name as 0
+ * A negative last key counts back from the end of the request
+ * Keys can instead follow a numkeys argument, whose position is given with no legacy positions
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeySpec {
+    pub first: i64,
+    pub last: i64,
+    pub step: i64,
+    pub numkeys: i64,
+}
+
+impl KeySpec {
+    pub const NONE: KeySpec = KeySpec { first: 0, last: 0, step: 0, numkeys: 0 };
+    pub const FIRST: KeySpec = KeySpec { first: 1, last: 1, step: 1, numkeys: 0 };
+    pub const ALL: KeySpec = KeySpec { first: 1, last: -1, step: 1, numkeys: 0 };
+
+    pub const fn numkeys(index: i64) -> KeySpec {
+        KeySpec { numkeys: index, ..KeySpec::NONE }
+    }
+}
+
+/**
+ * Redis CLI commands
+ * Container commands (e.g. COMMAND) dispatch to their subcommands, named "<command>|<subcommand>"
+ */
+pub struct RedisCommand {
+    pub name: &'static str,
+    // Exact number of arguments including the command name, or the negative minimum
+    pub arity: i64,
+    pub flags: &'static [CommandFlag],
+    pub keys: KeySpec,
+    pub group: CommandGroup,
+    pub since: &'static str,
+    pub summary: &'static str,
+    pub subcommands: &'static [RedisCommand],
+    pub handler: CommandHandler,
+}
+
+use CommandFlag::*;
+
+pub static COMMAND_TABLE: &[RedisCommand] = &[
+    // Bitmap
+    RedisCommand {
+        name: "bitcount", arity: -2, flags: &[ReadOnly], keys: KeySpec::FIRST,
+        group: CommandGroup::Bitmap, since: "2.6.0", summary: "Counts the number of set bits (population counting) in a string.",
+        subcommands: &[], handler: bitmap::bitcount,
+    },
+    RedisCommand {
+        name: "bitfield", arity: -2, flags: &[Write], keys: KeySpec::FIRST,
+        group: CommandGroup::Bitmap, since: "3.2.0", summary: "Performs arbitrary bitfield integer operations on strings.",
+        subcommands: &[], handler: bitmap::bitfield,
+    },
+    RedisCommand {
+        name: "bitfield_ro", arity: -2, flags: &[ReadOnly, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Bitmap, since: "6.0.0", summary: "Performs arbitrary read-only bitfield integer operations on strings.",
+        subcommands: &[], handler: bitmap::bitfield_ro,
+    },
+    RedisCommand {
+        name: "bitop", arity: -4, flags: &[Write], keys: KeySpec { first: 2, last: -1, step: 1, numkeys: 0 },
+        group: CommandGroup::Bitmap, since: "2.6.0", summary: "Performs bitwise operations on multiple strings, and stores the result.",
+        subcommands: &[], handler: bitmap::bitop,
+    },
+    RedisCommand {
+        name: "bitpos", arity: -3, flags: &[ReadOnly], keys: KeySpec::FIRST,
+        group: CommandGroup::Bitmap, since: "2.8.7", summary: "Finds the first set (1) or clear (0) bit in a string.",
+        subcommands: &[], handler: bitmap::bitpos,
+    },
+    RedisCommand {
+        name: "getbit", arity: 3, flags: &[ReadOnly, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Bitmap, since: "2.2.0", summary: "Returns a bit value by offset.",
+        subcommands: &[], handler: bitmap::getbit,
+    },
+    RedisCommand {
+        name: "setbit", arity: 4, flags: &[Write], keys: KeySpec::FIRST,
+        group: CommandGroup::Bitmap, since: "2.2.0", summary: "Sets or clears the bit at offset of the string value. Creates the key if it doesn't exist.",
+        subcommands: &[], handler: bitmap::setbit,
+    },
+    // Connection
+    RedisCommand {
+        name: "ping", arity: -1, flags: &[Fast], keys: KeySpec::NONE,
+        group: CommandGroup::Connection, since: "1.0.0", summary: "Returns the server's liveliness response.",
+        subcommands: &[], handler: connection::ping,
+    },
+    RedisCommand {
+        name: "echo", arity: 2, flags: &[Fast], keys: KeySpec::NONE,
+        group: CommandGroup::Connection, since: "1.0.0", summary: "Returns the given string.",
+        subcommands: &[], handler: connection::echo,
+    },
+    RedisCommand {
+        name: "hello", arity: -1, flags: &[Fast], keys: KeySpec::NONE,
+        group: CommandGroup::Connection, since: "6.0.0", summary: "Handshakes with the Redis server.",
+        subcommands: &[], handler: connection::hello,
+    },
+    // Generic
+    RedisCommand {
+        name: "copy", arity: -3, flags: &[Write], keys: KeySpec { first: 1, last: 2, step: 1, numkeys: 0 },
+        group: CommandGroup::Generic, since: "6.2.0", summary: "Copies the value of a key to a new key.",
+        subcommands: &[], handler: keyspace::copy,
+    },
+    RedisCommand {
+        name: "del", arity: -2, flags: &[Write], keys: KeySpec::ALL,
+        group: CommandGroup::Generic, since: "1.0.0", summary: "Deletes one or more keys.",
+        subcommands: &[], handler: keyspace::del,
+    },
+    RedisCommand {
+        name: "exists", arity: -2, flags: &[ReadOnly, Fast], keys: KeySpec::ALL,
+        group: CommandGroup::Generic, since: "1.0.0", summary: "Determines whether one or more keys exist.",
+        subcommands: &[], handler: keyspace::exists,
+    },
+    RedisCommand {
+        name: "expire", arity: -3, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Generic, since: "1.0.0", summary: "Sets the expiration time of a key in seconds.",
+        subcommands: &[], handler: expire::expire,
+    },
+    RedisCommand {
+        name: "expireat", arity: -3, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Generic, since: "1.2.0", summary: "Sets the expiration time of a key to a Unix timestamp.",
+        subcommands: &[], handler: expire::expireat,
+    },
+    RedisCommand {
+        name: "expiretime", arity: 2, flags: &[ReadOnly, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Generic, since: "7.0.0", summary: "Returns the expiration time of a key as a Unix timestamp.",
+        subcommands: &[], handler: expire::expiretime,
+    },
+    RedisCommand {
+        name: "object", arity: -2, flags: &[], keys: KeySpec::NONE,
+        group: CommandGroup::Generic, since: "2.2.3", summary: "A container for object introspection commands.",
+        subcommands: &[
+            RedisCommand {
+                name: "object|encoding", arity: 3, flags: &[ReadOnly], keys: KeySpec { first: 2, last: 2, step: 1, numkeys: 0 },
+                group: CommandGroup::Generic, since: "2.2.3", summary: "Returns the internal encoding of a Redis object.",
+                subcommands: &[], handler: keyspace::object_encoding,
+            },
+            RedisCommand {
+                name: "object|help", arity: 2, flags: &[], keys: KeySpec::NONE,
+                group: CommandGroup::Generic, since: "6.2.0", summary: "Returns helpful text about the different subcommands.",
+                subcommands: &[], handler: keyspace::object_help,
+            },
+        ],
+        handler: keyspace::object,
+    },
+    RedisCommand {
+        name: "persist", arity: 2, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Generic, since: "2.2.0", summary: "Removes the expiration time of a key.",
+        subcommands: &[], handler: expire::persist,
+    },
+    RedisCommand {
+        name: "pexpire", arity: -3, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Generic, since: "2.6.0", summary: "Sets the expiration time of a key in milliseconds.",
+        subcommands: &[], handler: expire::pexpire,
+    },
+    RedisCommand {
+        name: "pexpireat", arity: -3, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Generic, since: "2.6.0", summary: "Sets the expiration time of a key to a Unix milliseconds timestamp.",
+        subcommands: &[], handler: expire::pexpireat,
+    },
+    RedisCommand {
+        name: "pexpiretime", arity: 2, flags: &[ReadOnly, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Generic, since: "7.0.0", summary: "Returns the expiration time of a key as a Unix milliseconds timestamp.",
+        subcommands: &[], handler: expire::pexpiretime,
+    },
+    RedisCommand {
+        name: "pttl", arity: 2, flags: &[ReadOnly, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Generic, since: "2.6.0", summary: "Returns the expiration time in milliseconds of a key.",
+        subcommands: &[], handler: expire::pttl,
+    },
+    RedisCommand {
+        name: "randomkey", arity: 1, flags: &[ReadOnly], keys: KeySpec::NONE,
+        group: CommandGroup::Generic, since: "1.0.0", summary: "Returns a random key name from the database.",
+        subcommands: &[], handler: keyspace::randomkey,
+    },
+    RedisCommand {
+        name: "rename", arity: 3, flags: &[Write], keys: KeySpec { first: 1, last: 2, step: 1, numkeys: 0 },
+        group: CommandGroup::Generic, since: "1.0.0", summary: "Renames a key and overwrites the destination.",
+        subcommands: &[], handler: keyspace::rename,
+    },
+    RedisCommand {
+        name: "renamenx", arity: 3, flags: &[Write, Fast], keys: KeySpec { first: 1, last: 2, step: 1, numkeys: 0 },
+        group: CommandGroup::Generic, since: "1.0.0", summary: "Renames a key only when the target key name doesn't exist.",
+        subcommands: &[], handler: keyspace::renamenx,
+    },
+    RedisCommand {
+        name: "ttl", arity: 2, flags: &[ReadOnly, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Generic, since: "1.0.0", summary: "Returns the expiration time in seconds of a key.",
+        subcommands: &[], handler: expire::ttl,
+    },
+    RedisCommand {
+        name: "type", arity: 2, flags: &[ReadOnly, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Generic, since: "1.0.0", summary: "Determines the type of value stored at a key.",
+        subcommands: &[], handler: keyspace::type_,
+    },
+    // Hash
+    RedisCommand {
+        name: "hdel", arity: -3, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Hash, since: "2.0.0", summary: "Deletes one or more fields and their values from a hash. Deletes the hash if no fields remain.",
+        subcommands: &[], handler: hash::hdel,
+    },
+    RedisCommand {
+        name: "hexists", arity: 3, flags: &[ReadOnly, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Hash, since: "2.0.0", summary: "Determines whether a field exists in a hash.",
+        subcommands: &[], handler: hash::hexists,
+    },
+    RedisCommand {
+        name: "hget", arity: 3, flags: &[ReadOnly, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Hash, since: "2.0.0", summary: "Returns the value of a field in a hash.",
+        subcommands: &[], handler: hash::hget,
+    },
+    RedisCommand {
+        name: "hgetall", arity: 2, flags: &[ReadOnly], keys: KeySpec::FIRST,
+        group: CommandGroup::Hash, since: "2.0.0", summary: "Returns all fields and values in a hash.",
+        subcommands: &[], handler: hash::hgetall,
+    },
+    RedisCommand {
+        name: "hincrby", arity: 4, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Hash, since: "2.0.0", summary: "Increments the integer value of a field in a hash by a number. Uses 0 as initial value if the field doesn't exist.",
+        subcommands: &[], handler: hash::hincrby,
+    },
+    RedisCommand {
+        name: "hincrbyfloat", arity: 4, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Hash, since: "2.6.0", summary: "Increments the floating point value of a field by a number. Uses 0 as initial value if the field doesn't exist.",
+        subcommands: &[], handler: hash::hincrbyfloat,
+    },
+    RedisCommand {
+        name: "hkeys", arity: 2, flags: &[ReadOnly], keys: KeySpec::FIRST,
+        group: CommandGroup::Hash, since: "2.0.0", summary: "Returns all fields in a hash.",
+        subcommands: &[], handler: hash::hkeys,
+    },
+    RedisCommand {
+        name: "hlen", arity: 2, flags: &[ReadOnly, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Hash, since: "2.0.0", summary: "Returns the number of fields in a hash.",
+        subcommands: &[], handler: hash::hlen,
+    },
+    RedisCommand {
+        name: "hmget", arity: -3, flags: &[ReadOnly, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Hash, since: "2.0.0", summary: "Returns the values of all fields in a hash.",
+        subcommands: &[], handler: hash::hmget,
+    },
+    RedisCommand {
+        name: "hmset", arity: -4, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Hash, since: "2.0.0", summary: "Sets the values of multiple fields.",
+        subcommands: &[], handler: hash::hmset,
+    },
+    RedisCommand {
+        name: "hrandfield", arity: -2, flags: &[ReadOnly], keys: KeySpec::FIRST,
+        group: CommandGroup::Hash, since: "6.2.0", summary: "Returns one or more random fields from a hash.",
+        subcommands: &[], handler: hash::hrandfield,
+    },
+    RedisCommand {
+        name: "hscan", arity: -3, flags: &[ReadOnly], keys: KeySpec::FIRST,
+        group: CommandGroup::Hash, since: "2.8.0", summary: "Iterates over fields and values of a hash.",
+        subcommands: &[], handler: hash::hscan,
+    },
+    RedisCommand {
+        name: "hset", arity: -4, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Hash, since: "2.0.0", summary: "Creates or modifies the value of a field in a hash.",
+        subcommands: &[], handler: hash::hset,
+    },
+    RedisCommand {
+        name: "hsetnx", arity: 4, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Hash, since: "2.0.0", summary: "Sets the value of a field in a hash only when the field doesn't exist.",
+        subcommands: &[], handler: hash::hsetnx,
+    },
+    RedisCommand {
+        name: "hstrlen", arity: 3, flags: &[ReadOnly, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::Hash, since: "3.2.0", summary: "Returns the length of the value of a field.",
+        subcommands: &[], handler: hash::hstrlen,
+    },
+    RedisCommand {
+        name: "hvals", arity: 2, flags: &[ReadOnly], keys: KeySpec::FIRST,
+        group: CommandGroup::Hash, since: "2.0.0", summary: "Returns all values in a hash.",
+        subcommands: &[], handler: hash::hvals,
+    },
+    // List
+    RedisCommand {
+        name: "blmove", arity: 6, flags: &[Write, Blocking], keys: KeySpec { first: 1, last: 2, step: 1, numkeys: 0 },
+        group: CommandGroup::List, since: "6.2.0", summary: "Pops an element from a list, pushes it to another list and returns it. Blocks until an element is available otherwise. Deletes the list if the last element was moved.",
+        subcommands: &[], handler: list::blmove,
+    },
+    RedisCommand {
+        name: "blmpop", arity: -5, flags: &[Write, Blocking], keys: KeySpec::numkeys(2),
+        group: CommandGroup::List, since: "7.0.0", summary: "Pops the first element from one of multiple lists. Blocks until an element is available otherwise. Deletes the list if the last element was popped.",
+        subcommands: &[], handler: list::blmpop,
+    },
+    RedisCommand {
+        name: "blpop", arity: -3, flags: &[Write, Blocking], keys: KeySpec { first: 1, last: -2, step: 1, numkeys: 0 },
+        group: CommandGroup::List, since: "2.0.0", summary: "Removes and returns the first element in a list. Blocks until an element is available otherwise. Deletes the list if the last element was popped.",
+        subcommands: &[], handler: list::blpop,
+    },
+    RedisCommand {
+        name: "brpop", arity: -3, flags: &[Write, Blocking], keys: KeySpec { first: 1, last: -2, step: 1, numkeys: 0 },
+        group: CommandGroup::List, since: "2.0.0", summary: "Removes and returns the last element in a list. Blocks until an element is available otherwise. Deletes the list if the last element was popped.",
+        subcommands: &[], handler: list::brpop,
+    },
+    RedisCommand {
+        name: "lindex", arity: 3, flags: &[ReadOnly], keys: KeySpec::FIRST,
+        group: CommandGroup::List, since: "1.0.0", summary: "Returns an element from a list by its index.",
+        subcommands: &[], handler: list::lindex,
+    },
+    RedisCommand {
+        name: "linsert", arity: 5, flags: &[Write], keys: KeySpec::FIRST,
+        group: CommandGroup::List, since: "2.2.0", summary: "Inserts an element before or after another element in a list.",
+        subcommands: &[], handler: list::linsert,
+    },
+    RedisCommand {
+        name: "llen", arity: 2, flags: &[ReadOnly, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::List, since: "1.0.0", summary: "Returns the length of a list.",
+        subcommands: &[], handler: list::llen,
+    },
+    RedisCommand {
+        name: "lmove", arity: 5, flags: &[Write], keys: KeySpec { first: 1, last: 2, step: 1, numkeys: 0 },
+        group: CommandGroup::List, since: "6.2.0", summary: "Returns an element after popping it from one list and pushing it to another. Deletes the list if the last element was moved.",
+        subcommands: &[], handler: list::lmove,
+    },
+    RedisCommand {
+        name: "lmpop", arity: -4, flags: &[Write], keys: KeySpec::numkeys(1),
+        group: CommandGroup::List, since: "7.0.0", summary: "Returns multiple elements from a list after removing them. Deletes the list if the last element was popped.",
+        subcommands: &[], handler: list::lmpop,
+    },
+    RedisCommand {
+        name: "lpop", arity: -2, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::List, since: "1.0.0", summary: "Returns the first elements in a list after removing it. Deletes the list if the last element was popped.",
+        subcommands: &[], handler: list::lpop,
+    },
+    RedisCommand {
+        name: "lpos", arity: -3, flags: &[ReadOnly], keys: KeySpec::FIRST,
+        group: CommandGroup::List, since: "6.0.6", summary: "Returns the index of matching elements in a list.",
+        subcommands: &[], handler: list::lpos,
+    },
+    RedisCommand {
+        name: "lpush", arity: -3, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::List, since: "1.0.0", summary: "Prepends one or more elements to a list. Creates the key if it doesn't exist.",
+        subcommands: &[], handler: list::lpush,
+    },
+    RedisCommand {
+        name: "lpushx", arity: -3, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::List, since: "2.2.0", summary: "Prepends one or more elements to a list only when the list exists.",
+        subcommands: &[], handler: list::lpushx,
+    },
+    RedisCommand {
+        name: "lrange", arity: 4, flags: &[ReadOnly], keys: KeySpec::FIRST,
+        group: CommandGroup::List, since: "1.0.0", summary: "Returns a range of elements from a list.",
+        subcommands: &[], handler: list::lrange,
+    },
+    RedisCommand {
+        name: "lrem", arity: 4, flags: &[Write], keys: KeySpec::FIRST,
+        group: CommandGroup::List, since: "1.0.0", summary: "Removes elements from a list. Deletes the list if the last element was removed.",
+        subcommands: &[], handler: list::lrem,
+    },
+    RedisCommand {
+        name: "lset", arity: 4, flags: &[Write], keys: KeySpec::FIRST,
+        group: CommandGroup::List, since: "1.0.0", summary: "Sets the value of an element in a list by its index.",
+        subcommands: &[], handler: list::lset,
+    },
+    RedisCommand {
+        name: "ltrim", arity: 4, flags: &[Write], keys: KeySpec::FIRST,
+        group: CommandGroup::List, since: "1.0.0", summary: "Removes elements from both ends a list. Deletes the list if all elements were trimmed.",
+        subcommands: &[], handler: list::ltrim,
+    },
+    RedisCommand {
+        name: "rpop", arity: -2, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::List, since: "1.0.0", summary: "Returns and removes the last elements of a list. Deletes the list if the last element was popped.",
+        subcommands: &[], handler: list::rpop,
+    },
+    RedisCommand {
+        name: "rpoplpush", arity: 3, flags: &[Write], keys: KeySpec { first: 1, last: 2, step: 1, numkeys: 0 },
+        group: CommandGroup::List, since: "1.2.0", summary: "Returns the last element of a list after removing and pushing it to another list. Deletes the list if the last element was popped.",
+        subcommands: &[], handler: list::rpoplpush,
+    },
+    RedisCommand {
+        name: "rpush", arity: -3, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::List, since: "1.0.0", summary: "Appends one or more elements to a list. Creates the key if it doesn't exist.",
+        subcommands: &[], handler: list::rpush,
+    },
+    RedisCommand {
+        name: "rpushx", arity: -3, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::List, since: "2.2.0", summary: "Appends an element to a list only when the list exists.",
+        subcommands: &[], handler: list::rpushx,
+    },
+    // Server
+    RedisCommand {
+        name: "command", arity: -1, flags: &[], keys: KeySpec::NONE,
+        group: CommandGroup::Server, since: "2.8.13", summary: "Returns detailed information about all commands.",
+        subcommands: &[
+            RedisCommand {
+                name: "command|count", arity: 2, flags: &[], keys: KeySpec::NONE,
+                group: CommandGroup::Server, since: "2.8.13", summary: "Returns a count of commands.",
+                subcommands: &[], handler: server::command_count,
+            },
+            RedisCommand {
+                name: "command|docs", arity: -2, flags: &[], keys: KeySpec::NONE,
+                group: CommandGroup::Server, since: "7.0.0", summary: "Returns documentary information about one, multiple or all commands.",
+                subcommands: &[], handler: server::command_docs,
+            },
+            RedisCommand {
+                name: "command|getkeys", arity: -3, flags: &[], keys: KeySpec::NONE,
+                group: CommandGroup::Server, since: "2.8.13", summary: "Extracts the key names from an arbitrary command.",
+                subcommands: &[], handler: server::command_getkeys,
+            },
+            RedisCommand {
+                name: "command|help", arity: 2, flags: &[], keys: KeySpec::NONE,
+                group: CommandGroup::Server, since: "5.0.0", summary: "Returns helpful text about the different subcommands.",
+                subcommands: &[], handler: server::command_help,
+            },
+            RedisCommand {
+                name: "command|info", arity: -2, flags: &[], keys: KeySpec::NONE,
+                group: CommandGroup::Server, since: "2.8.13", summary: "Returns information about one, multiple or all commands.",
+                subcommands: &[], handler: server::command_info,
+            },
+        ],
+        handler: server::command,
+    },
+    RedisCommand {
+        name: "dbsize", arity: 1, flags: &[ReadOnly, Fast], keys: KeySpec::NONE,
+        group: CommandGroup::Server, since: "1.0.0", summary: "Returns the number of keys in the database.",
+        subcommands: &[], handler: keyspace::dbsize,
+    },
+    // String
+    RedisCommand {
+        name: "append", arity: 3, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::String, since: "2.0.0", summary: "Appends a string to the value of a key. Creates the key if it doesn't exist.",
+        subcommands: &[], handler: string::append,
+    },
+    RedisCommand {
+        name: "decr", arity: 2, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::String, since: "1.0.0", summary: "Decrements the integer value of a key by one. Uses 0 as initial value if the key doesn't exist.",
+        subcommands: &[], handler: string::decr,
+    },
+    RedisCommand {
+        name: "decrby", arity: 3, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::String, since: "1.0.0", summary: "Decrements a number from the integer value of a key. Uses 0 as initial value if the key doesn't exist.",
+        subcommands: &[], handler: string::decrby,
+    },
+    RedisCommand {
+        name: "get", arity: 2, flags: &[ReadOnly, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::String, since: "1.0.0", summary: "Returns the string value of a key.",
+        subcommands: &[], handler: string::get,
+    },
+    RedisCommand {
+        name: "getdel", arity: 2, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::String, since: "6.2.0", summary: "Returns the string value of a key after deleting the key.",
+        subcommands: &[], handler: string::getdel,
+    },
+    RedisCommand {
+        name: "getex", arity: -2, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::String, since: "6.2.0", summary: "Returns the string value of a key after setting its expiration time.",
+        subcommands: &[], handler: string::getex,
+    },
+    RedisCommand {
+        name: "getrange", arity: 4, flags: &[ReadOnly], keys: KeySpec::FIRST,
+        group: CommandGroup::String, since: "2.4.0", summary: "Returns a substring of the string stored at a key.",
+        subcommands: &[], handler: string::getrange,
+    },
+    RedisCommand {
+        name: "getset", arity: 3, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::String, since: "1.0.0", summary: "Returns the previous string value of a key after setting it to a new value.",
+        subcommands: &[], handler: string::getset,
+    },
+    RedisCommand {
+        name: "incr", arity: 2, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::String, since: "1.0.0", summary: "Increments the integer value of a key by one. Uses 0 as initial value if the key doesn't exist.",
+        subcommands: &[], handler: string::incr,
+    },
+    RedisCommand {
+        name: "incrby", arity: 3, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::String, since: "1.0.0", summary: "Increments the integer value of a key by a number. Uses 0 as initial value if the key doesn't exist.",
+        subcommands: &[], handler: string::incrby,
+    },
+    RedisCommand {
+        name: "incrbyfloat", arity: 3, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::String, since: "2.6.0", summary: "Increment the floating point value of a key by a number. Uses 0 as initial value if the key doesn't exist.",
+        subcommands: &[], handler: string::incrbyfloat,
+    },
+    RedisCommand {
+        name: "lcs", arity: -3, flags: &[ReadOnly], keys: KeySpec { first: 1, last: 2, step: 1, numkeys: 0 },
+        group: CommandGroup::String, since: "7.0.0", summary: "Finds the longest common substring.",
+        subcommands: &[], handler: string::lcs,
+    },
+    RedisCommand {
+        name: "mget", arity: -2, flags: &[ReadOnly, Fast], keys: KeySpec::ALL,
+        group: CommandGroup::String, since: "1.0.0", summary: "Atomically returns the string values of one or more keys.",
+        subcommands: &[], handler: string::mget,
+    },
+    RedisCommand {
+        name: "mset", arity: -3, flags: &[Write], keys: KeySpec { first: 1, last: -1, step: 2, numkeys: 0 },
+        group: CommandGroup::String, since: "1.0.1", summary: "Atomically creates or modifies the string values of one or more keys.",
+        subcommands: &[], handler: string::mset,
+    },
+    RedisCommand {
+        name: "msetnx", arity: -3, flags: &[Write], keys: KeySpec { first: 1, last: -1, step: 2, numkeys: 0 },
+        group: CommandGroup::String, since: "1.0.1", summary: "Atomically modifies the string values of one or more keys only when all keys don't exist.",
+        subcommands: &[], handler: string::msetnx,
+    },
+    RedisCommand {
+        name: "psetex", arity: 4, flags: &[Write], keys: KeySpec::FIRST,
+        group: CommandGroup::String, since: "2.6.0", summary: "Sets both string value and expiration time in milliseconds of a key. The key is created if it doesn't exist.",
+        subcommands: &[], handler: string::psetex,
+    },
+    RedisCommand {
+        name: "set", arity: -3, flags: &[Write], keys: KeySpec::FIRST,
+        group: CommandGroup::String, since: "1.0.0", summary: "Sets the string value of a key, ignoring its type. The key is created if it doesn't exist.",
+        subcommands: &[], handler: string::set,
+    },
+    RedisCommand {
+        name: "setex", arity: 4, flags: &[Write], keys: KeySpec::FIRST,
+        group: CommandGroup::String, since: "2.0.0", summary: "Sets the string value and expiration time of a key. Creates the key if it doesn't exist.",
+        subcommands: &[], handler: string::setex,
+    },
+    RedisCommand {
+        name: "setnx", arity: 3, flags: &[Write, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::String, since: "1.0.0", summary: "Set the string value of a key only when the key doesn't exist.",
+        subcommands: &[], handler: string::setnx,
+    },
+    RedisCommand {
+        name: "setrange", arity: 4, flags: &[Write], keys: KeySpec::FIRST,
+        group: CommandGroup::String, since: "2.2.0", summary: "Overwrites a part of a string value with another by an offset. Creates the key if it doesn't exist.",
+        subcommands: &[], handler: string::setrange,
+    },
+    RedisCommand {
+        name: "strlen", arity: 2, flags: &[ReadOnly, Fast], keys: KeySpec::FIRST,
+        group: CommandGroup::String, since: "2.2.0", summary: "Returns the length of a string value.",
+        subcommands: &[], handler: string::strlen,
+    },
+];
+
+static COMMAND_LOOKUP: OnceLock<HashMap<&'static str, &'static RedisCommand>> = OnceLock::new();
+
+impl RedisCommand {
+    /**
+     * Finds a command by name, ignoring case
+     */
+    pub fn lookup(name: &[u8]) -> Option<&'static RedisCommand> {
+        let lookup = COMMAND_LOOKUP.get_or_init(|| {
+            COMMAND_TABLE.iter().map(|command| (command.name, command)).collect()
+        });
+
+        let name = String::from_utf8(name.to_ascii_lowercase()).ok()?;
+        lookup.get(name.as_str()).copied()
+    }
+
+    /**
+     * Finds a subcommand by name, ignoring case
+     */
+    pub fn lookup_subcommand(&self, name: &[u8]) -> Option<&'static RedisCommand> {
+        self.subcommands.iter().find(|subcommand| {
+            subcommand.subcommand_name().as_bytes().eq_ignore_ascii_case(name)
+        })
+    }
+
+    /**
+     * Name of a subcommand without its container, e.g. "info" for "command|info"
+     */
+    pub fn subcommand_name(&self) -> &'static str {
+        self.name.split_once('|').map_or(self.name, |(_, subcommand)| subcommand)
+    }
+
+    pub fn wrong_arity(&self) -> RedisError {
+        RedisError::WrongArity(self.name.to_owned())
+    }
+
+    pub fn unknown_subcommand(&self, subcommand: &Bytes) -> RedisError {
+        RedisError::Custom(format!(
+            "unknown subcommand '{}'. Try {} HELP.",
+            String::from_utf8_lossy(subcommand),
+            self.name.to_uppercase()
+        ))
+    }
+
+    /**
+     * Checks the number of arguments, including the command name
+     */
+    pub fn accepts_arity(&self, argc: usize) -> bool {
+        if self.arity >= 0 {
+            argc as i64 == self.arity
+        } else {
+            argc as i64 >= -self.arity
+        }
+    }
+
+    /**
+     * Positions of keys within a request, including the command name
+     * None if numkeys isn't a positive number of the arguments that follow it
+     */
+    pub fn key_positions(&self, args: &[Bytes]) -> Option<Vec<usize>> {
+        let argc = args.len();
+        if self.keys.numkeys > 0 {
+            let first = self.keys.numkeys as usize + 1;
+            let count = args.get(first - 1)
+                .and_then(|numkeys| canonical_integer(numkeys))
+                .filter(|&count| count > 0 && count as usize <= argc.saturating_sub(first))?;
+
+            return Some((first..first + count as usize).collect())
+        }
+        if self.keys == KeySpec::NONE {
+            return Some(vec![])
+        }
+
+        let last = if self.keys.last < 0 { argc as i64 + self.keys.last } else { self.keys.last };
+        Some((self.keys.first..=last.min(argc as i64 - 1))
+            .step_by(self.keys.step as usize)
+            .map(|position| position as usize)
+            .collect())
+    }
+
+    /**
+     * ACL categories derived from the command flags and group
+     */
+    pub fn acl_categories(&self) -> Vec<&'static str> {
+        let mut categories = vec![];
+
+        for flag in self.flags {
+            match flag {
+                Write => categories.push("@write"),
+                ReadOnly => categories.push("@read"),
+                Admin => categories.extend(["@admin", "@dangerous"]),
+                Blocking => categories.push("@blocking"),
+                Fast => {}
+            }
+        }
+        if self.flags.contains(&Fast) {
+            categories.push("@fast")
+        } else {
+            categories.push("@slow")
+        }
+
+        match self.group {
+            CommandGroup::Bitmap => categories.push("@bitmap"),
+            CommandGroup::Connection => categories.push("@connection"),
+            CommandGroup::Generic => categories.push("@keyspace"),
+            CommandGroup::Hash => categories.push("@hash"),
+            CommandGroup::List => categories.push("@list"),
+            CommandGroup::String => categories.push("@string"),
+            CommandGroup::Server => {}
+        }
+
+        categories
+    }
+}
+
+#[derive(Default)]
+pub struct SetCommandFlags {
+    pub exist_flag: Option<SetCommandExistFlag>,
+    pub get_flag: bool,
+    pub ttl_flag: Option<SetCommandTTLFlag>,
+}
+
+pub enum SetCommandExistFlag {
+    NX, // Only set if it doesn't exist
+    XX, // Only set if it exists already
+}
+
+pub enum SetCommandTTLFlag {
+    EX(u64),    // TTL duration (seconds)
+    PX(u64),   // TTL duration (milliseconds)
+    EXAT(u64),  // Set expiry at exact unix time (seconds)
+    PXAT(u64), // Set expiry at exact unix time (milliseconds)
+    KEEPTTL,    // Keep existing TTL when setting value
+}
+
+impl SetCommandTTLFlag {
+    /**
+     * Absolute expiry time, or None when keeping the TTL or if the time overflows
+     */
+    pub fn deadline(&self, now: EpochMillisecond) -> Option<EpochMillisecond> {
+        match self {
+            SetCommandTTLFlag::EX(seconds) => seconds.checked_mul(1000)?.checked_add(now),
+            SetCommandTTLFlag::PX(milliseconds) => milliseconds.checked_add(now),
+            SetCommandTTLFlag::EXAT(seconds) => seconds.checked_mul(1000),
+            SetCommandTTLFlag::PXAT(milliseconds) => Some(*milliseconds),
+            SetCommandTTLFlag::KEEPTTL => None,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ExpireCommandFlags {
+    pub exist_flag: Option<ExpireCommandExistFlag>,
+    pub compare_flag: Option<ExpireCommandCompareFlag>,
+}
+
+pub enum ExpireCommandExistFlag {
+    NX, // Only set if the key has no TTL
+    XX, // Only set if the key has a TTL
+}
+
+pub enum ExpireCommandCompareFlag {
+    GT, // Only set if the new TTL is greater, treating no TTL as infinite
+    LT, // Only set if the new TTL is less, treating no TTL as infinite
+}
+
+/**
+ * Parses an integer in canonical form, as Redis does (e.g. rejecting "+1", "01" and " 1")
+ */
+pub fn canonical_integer(bytes: &[u8]) -> Option<i64> {
+    std::str::from_utf8(bytes).ok()
+        .and_then(|s| s.parse::<i64>().ok())
+        .filter(|n| n.to_string().as_bytes() == bytes)
+}
+
+/**
+ * Parses a float, rejecting NaN and surrounding whitespace
+ */
+pub fn canonical_float(bytes: &[u8]) -> Option<f64> {
+    std::str::from_utf8(bytes).ok()
+        .and_then(|s| s.parse::<f64>().ok())
+        .filter(|n| !n.is_nan())
+}
+
+/**
+ * Resolves an inclusive range, where negative indices count back from the end
+ * Returns None if the range is empty once clamped to the length
+ */
+fn clamp_range(start: i64, end: i64, length: usize) -> Option<RangeInclusive<usize>> {
+    let length = length as i64;
+    if start < 0 && end < 0 && start > end {
+        return None
+    }
+
+    let start = if start < 0 { length + start } else { start }.max(0);
+    let end = if end < 0 { length + end } else { end }.max(0).min(length - 1);
+    if start > end || length == 0 {
+        return None
+    }
+
+    Some(start as usize..=end as usize)
+}
+
+/**
+ * Glob-style match, as used by MATCH options
+ * Supports `*`, `?`, classes such as `[a-z]` or `[^abc]`, and `\` escapes
+ * Only the last star is backtracked to, so matching takes at most pattern length times string length steps
+ */
+pub fn glob_match(pattern: &[u8], string: &[u8]) -> bool {
+    let (mut pattern, mut string) = (pattern, string);
+    // Pattern past the last star, and the string it is matched from next should the rest fail
+    let mut backtrack: Option<(&[u8], &[u8])> = None;
+
+    loop {
+        let matched = match (pattern, string.split_first()) {
+            ([], None) => return true,
+            ([b'*', rest @ ..], _) => {
+                backtrack = Some((rest, string));
+                pattern = rest;
+                continue
+            },
+            ([], Some(_)) | (_, None) => None,
+            ([b'?', rest @ ..], Some((_, string))) => Some((rest, string)),
+            ([b'[', class @ ..], Some((&c, string))) => {
+                let (matched, rest) = match_class(class, c);
+                matched.then_some((rest, string))
+            },
+            ([b'\\', literal, rest @ ..] | [literal, rest @ ..], Some((c, string))) =>
+                (literal == c).then_some((rest, string)),
+        };
+
+        match (matched, backtrack) {
+            (Some(next), _) => (pattern, string) = next,
+            // The last star takes one more byte
+            (None, Some((rest, [_, skipped @ ..]))) => {
+                backtrack = Some((rest, skipped));
+                (pattern, string) = (rest, skipped);
+            },
+            (None, _) => return false,
+        }
+    }
+}
+
+/**
+ * Whether c is in the class following `[`, and the pattern past its closing `]`
+ * An unterminated class runs to the end of the pattern
+ */
+fn match_class(class: &[u8], c: u8) -> (bool, &[u8]) {
+    let (negated, mut class) = match class {
+        [b'^', rest @ ..] => (true, rest),
+        _ => (false, class),
+    };
+
+    let mut matched = false;
+    loop {
+        match class {
+            [] => break,
+            [b']', rest @ ..] => {
+                class = rest;
+                break
+            },
+            [b'\\', literal, rest @ ..] => {
+                matched |= *literal == c;
+                class = rest
+            },
+            [start, b'-', end, rest @ ..] => {
+                let (low, high) = if start <= end { (*start, *end) } else { (*end, *start) };
+                matched |= (low..=high).contains(&c);
+                class = rest
+            },
+            [literal, rest @ ..] => {
+                matched |= *literal == c;
+                class = rest
+            },
+        }
+    }
+
+    (matched != negated, class)
+}
+
+fn parse_integer(arg: &[u8]) -> Result<i64, RedisError> {
+    canonical_integer(arg).ok_or(RedisError::NotInteger)
+}
+
+fn parse_float(arg: &[u8]) -> Result<f64, RedisError> {
+    canonical_float(arg).ok_or(RedisError::NotFloat)
+}
+
+#[cfg(test)]
+pub mod test_utils {
+    use bytes::{Bytes, BytesMut};
+    use tokio::task::JoinHandle;
+
+    use crate::{
+        resp::{frame::RESPFrame, interpreter::{ConnectionState, RESPInterpreter}, parser::RESPParser},
+        store::{RedisStore, RedisValue}
+    };
+
+    /**
+     * Interprets an inline request on a fresh connection, e.g. `SET key "hello world"`
+     */
+    pub async fn interpret_inline(request: &str) -> RESPFrame {
+        interpret_inline_with(&mut ConnectionState::default(), request).await
+    }
+
+    pub async fn interpret_inline_with(connection: &mut ConnectionState, request: &str) -> RESPFrame {
+        let mut buf = BytesMut::from(format!("{}\r\n", request).as_str());
+        let frame = RESPParser::default().decode(&mut buf).unwrap().unwrap();
+
+        RESPInterpreter::interpret(connection, &frame).await
+    }
+
+    /**
+     * Stores a value of a type that can't be created by a command yet
+     */
+    pub async fn insert_value(key: &str, value: RedisValue) {
+        RedisStore::get_shared_store().lock().await.insert_value(&Bytes::from(key.to_owned()), value);
+    }
+
+    /**
+     * Runs a request expected to block on key, returning once it is parked there
+     */
+    pub async fn spawn_blocked(request: &'static str, key: &str) -> JoinHandle<RESPFrame> {
+        let blocked = blocked_on(key).await;
+        let handle = tokio::spawn(interpret_inline(request));
+
+        while blocked_on(key).await == blocked {
+            assert!(!handle.is_finished(), "{} should block", request);
+            tokio::task::yield_now().await;
+        }
+        handle
+    }
+
+    /**
+     * Waits until exactly clients are blocked on key
+     */
+    pub async fn wait_blocked_on(key: &str, clients: usize) {
+        while blocked_on(key).await != clients {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    async fn blocked_on(key: &str) -> usize {
+        RedisStore::get_shared_store().lock().await.blocked_on(key.as_bytes())
+    }
+
+    pub fn matches_integer(frame: RESPFrame, expected: i64) -> bool {
+        matches!(frame, RESPFrame::Integer(n) if n == expected)
+    }
+
+    pub fn matches_bulk(frame: RESPFrame, expected: &str) -> bool {
+        matches!(frame, RESPFrame::Bulk(s) if s == expected)
+    }
+
+    pub fn matches_ok(frame: RESPFrame) -> bool {
+        matches!(frame, RESPFrame::Simple(s) if s == "OK")
+    }
+
+    pub fn matches_error(frame: RESPFrame, expected: &str) -> bool {
+        matches!(frame, RESPFrame::Error(s) if s == expected)
+    }
+
+    pub fn bulk(value: &str) -> RESPFrame {
+        RESPFrame::Bulk(Bytes::from(value.to_owned()))
+    }
+
+    pub fn bulks(values: &[&str]) -> RESPFrame {
+        RESPFrame::Array(values.iter().map(|value| bulk(value)).collect())
+    }
+
+    pub fn integers(values: &[i64]) -> RESPFrame {
+        RESPFrame::Array(values.iter().map(|&n| RESPFrame::Integer(n)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(b"get", "get")]
+    #[case(b"GET", "get")]
+    #[case(b"gEt", "get")]
+    #[case(b"HELLO", "hello")]
+    fn should_lookup_command_ignoring_case(#[case] name: &[u8], #[case] expected_name: &str) {
+        assert_eq!(expected_name, RedisCommand::lookup(name).unwrap().name);
+    }
+
+    #[rstest]
+    #[case(b"")]
+    #[case(b"undefined")]
+    #[case(b"\xff")]
+    fn should_not_lookup_unknown_command(#[case] name: &[u8]) {
+        assert!(RedisCommand::lookup(name).is_none());
+    }
+
+    #[rstest]
+    #[case("get", 1, false)]
+    #[case("get", 2, true)]
+    #[case("get", 3, false)]
+    #[case("set", 2, false)]
+    #[case("set", 3, true)]
+    #[case("set", 8, true)]
+    #[case("ping", 1, true)]
+    fn should_check_arity(#[case] name: &str, #[case] argc: usize, #[case] accepted: bool) {
+        assert_eq!(accepted, RedisCommand::lookup(name.as_bytes()).unwrap().accepts_arity(argc));
+    }
+
+    #[rstest]
+    #[case(b"info", Some("command|info"))]
+    #[case(b"DOCS", Some("command|docs"))]
+    #[case(b"missing", None)]
+    fn should_lookup_subcommand_ignoring_case(#[case] name: &[u8], #[case] expected_name: Option<&str>) {
+        let command = RedisCommand::lookup(b"command").unwrap();
+
+        assert_eq!(expected_name, command.lookup_subcommand(name).map(|subcommand| subcommand.name));
+    }
+
+    #[rstest]
+    #[case(KeySpec::NONE, 3, vec![])]
+    #[case(KeySpec::FIRST, 3, vec![1])]
+    #[case(KeySpec { first: 1, last: -1, step: 1, numkeys: 0 }, 4, vec![1, 2, 3])]
+    #[case(KeySpec { first: 1, last: -1, step: 2, numkeys: 0 }, 5, vec![1, 3])]
+    #[case(KeySpec { first: 1, last: -2, step: 1, numkeys: 0 }, 4, vec![1, 2])]
+    #[case(KeySpec { first: 1, last: 2, step: 1, numkeys: 0 }, 2, vec![1])]
+    fn should_find_key_positions(#[case] keys: KeySpec, #[case] argc: usize, #[case] expected: Vec<usize>) {
+        let command = RedisCommand { keys, ..*RedisCommand::lookup(b"get").unwrap() };
+
+        assert_eq!(Some(expected), command.key_positions(&vec![Bytes::from("arg"); argc]));
+    }
+
+    #[rstest]
+    #[case("BLMPOP 0 2 a b LEFT", Some(vec![3, 4]))]
+    #[case("BLMPOP 0 3 a b LEFT", Some(vec![3, 4, 5]))]
+    #[case("BLMPOP 0 4 a b LEFT", None)]
+    #[case("BLMPOP 0 0 a LEFT", None)]
+    #[case("BLMPOP 0 one a LEFT", None)]
+    fn should_find_key_positions_after_numkeys(#[case] request: &str, #[case] expected: Option<Vec<usize>>) {
+        let args: Vec<Bytes> = request.split(' ').map(|arg| Bytes::from(arg.to_owned())).collect();
+
+        assert_eq!(expected, RedisCommand::lookup(b"blmpop").unwrap().key_positions(&args));
+    }
+
+    #[rstest]
+    #[case("get", vec!["@read", "@fast", "@string"])]
+    #[case("set", vec!["@write", "@slow", "@string"])]
+    #[case("ping", vec!["@fast", "@connection"])]
+    fn should_derive_acl_categories(#[case] name: &str, #[case] expected: Vec<&str>) {
+        assert_eq!(expected, RedisCommand::lookup(name.as_bytes()).unwrap().acl_categories());
+    }
+
+    #[test]
+    fn command_table_should_have_unique_lowercase_names() {
+        let mut names = HashSet::new();
+
+        for command in COMMAND_TABLE {
+            assert_eq!(command.name.to_lowercase(), command.name);
+            assert!(names.insert(command.name), "duplicate command {}", command.name);
+
+            for subcommand in command.subcommands {
+                assert!(subcommand.name.starts_with(&format!("{}|", command.name)));
+                assert!(names.insert(subcommand.name), "duplicate command {}", subcommand.name);
+            }
+        }
+    }
+
+    #[test]
+    fn command_table_should_have_consistent_flags() {
+        for command in COMMAND_TABLE {
+            assert!(
+                !(command.flags.contains(&Write) && command.flags.contains(&ReadOnly)),
+                "{} can't be both write and readonly", command.name
+            );
+            assert!(
+                command.keys == KeySpec::NONE
+                    || command.flags.contains(&Write)
+                    || command.flags.contains(&ReadOnly),
+                "{} should only access keys if it reads or writes", command.name
+            );
+        }
+    }
+
+    #[rstest]
+    #[case("*", "anything", true)]
+    #[case("*", "", true)]
+    #[case("user:*", "user:1000", true)]
+    #[case("user:*", "users", false)]
+    #[case("h?llo", "hello", true)]
+    #[case("h?llo", "hllo", false)]
+    #[case("h*llo", "heeeello", true)]
+    #[case("h**o", "ho", true)]
+    #[case("h[ae]llo", "hallo", true)]
+    #[case("h[ae]llo", "hillo", false)]
+    #[case("h[^e]llo", "hallo", true)]
+    #[case("h[^e]llo", "hello", false)]
+    #[case("h[a-b]llo", "hbllo", true)]
+    #[case("h[b-a]llo", "hallo", true)]
+    #[case("h[a-b]llo", "hcllo", false)]
+    #[case("h\\*llo", "h*llo", true)]
+    #[case("h\\*llo", "hello", false)]
+    #[case("[\\]]", "]", true)]
+    #[case("field[", "field", false)]
+    #[case("a*b*c", "aXbYc", true)]
+    #[case("a*b*c", "aXbY", false)]
+    #[case("*a", "ba", true)]
+    #[case("a*", "", false)]
+    #[case("*?", "", false)]
+    #[case("a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*b", &"a".repeat(10_000), false)]
+    fn should_glob_match(#[case] pattern: &str, #[case] string: &str, #[case] expected: bool) {
+        assert_eq!(expected, glob_match(pattern.as_bytes(), string.as_bytes()));
+    }
+}
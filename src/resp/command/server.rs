@@ -0,0 +1,386 @@
+use bytes::Bytes;
+
+use crate::{error::RedisError, resp::frame::RESPFrame};
+
+use super::{CommandContext, CommandFlag, CommandResult, KeySpec, RedisCommand, COMMAND_TABLE};
+
+/**
+ * COMMAND
+ * Replies with the details of every command
+ */
+pub fn command(_ctx: &mut CommandContext, _args: &[Bytes]) -> CommandResult {
+    Ok(RESPFrame::Array(COMMAND_TABLE.iter().map(info_frame).collect()))
+}
+
+/**
+ * COMMAND COUNT
+ */
+pub fn command_count(_ctx: &mut CommandContext, _args: &[Bytes]) -> CommandResult {
+    Ok(RESPFrame::Integer(COMMAND_TABLE.len() as i64))
+}
+
+/**
+ * COMMAND INFO [command-name [command-name ...]]
+ * Unknown commands reply with null in their position
+ */
+pub fn command_info(_ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    if args.is_empty() {
+        return Ok(RESPFrame::Array(COMMAND_TABLE.iter().map(info_frame).collect()))
+    }
+
+    Ok(RESPFrame::Array(
+        args.iter()
+            .map(|name| RedisCommand::lookup(name).map_or(RESPFrame::Null, info_frame))
+            .collect()
+    ))
+}
+
+/**
+ * COMMAND DOCS [command-name [command-name ...]]
+ * Unknown commands are left out of the reply
+ */
+pub fn command_docs(_ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let commands: Vec<&RedisCommand> = if args.is_empty() {
+        COMMAND_TABLE.iter().collect()
+    } else {
+        args.iter().filter_map(|name| RedisCommand::lookup(name)).collect()
+    };
+
+    Ok(RESPFrame::Map(
+        commands.into_iter()
+            .map(|command| (RESPFrame::Bulk(Bytes::from(command.name)), docs_frame(command)))
+            .collect()
+    ))
+}
+
+/**
+ * COMMAND GETKEYS command [arg [arg ...]]
+ */
+pub fn command_getkeys(_ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let invalid_command = || RedisError::Custom("Invalid command specified".to_owned());
+
+    let command = args.first()
+        .and_then(|name| RedisCommand::lookup(name))
+        .ok_or_else(invalid_command)?;
+    let command = match args.get(1) {
+        Some(subcommand) if !command.subcommands.is_empty() => command.lookup_subcommand(subcommand)
+            .ok_or_else(invalid_command)?,
+        _ => command
+    };
+
+    if !command.accepts_arity(args.len()) {
+        return Err(RedisError::Custom("Invalid number of arguments specified for command".to_owned()))
+    }
+
+    let keys = command.key_positions(args)
+        .ok_or_else(|| RedisError::Custom("Invalid arguments specified for command".to_owned()))?;
+    if keys.is_empty() {
+        return Err(RedisError::Custom("The command has no key arguments".to_owned()))
+    }
+
+    Ok(RESPFrame::Array(
+        keys.into_iter().map(|position| RESPFrame::Bulk(args[position].clone())).collect()
+    ))
+}
+
+/**
+ * COMMAND HELP
+ */
+pub fn command_help(_ctx: &mut CommandContext, _args: &[Bytes]) -> CommandResult {
+    let help = [
+        "COMMAND <subcommand> [<arg> [value] [opt] ...]. Subcommands are:",
+        "(no subcommand)",
+        "    Return details about all Redis commands.",
+        "COUNT",
+        "    Return the total number of commands in this Redis server.",
+        "INFO [<command-name> ...]",
+        "    Return details about multiple Redis commands.",
+        "    If no command names are given, documentation details for all",
+        "    commands are returned.",
+        "DOCS [<command-name> ...]",
+        "    Return documentation details about multiple Redis commands.",
+        "    If no command names are given, documentation details for all",
+        "    commands are returned.",
+        "GETKEYS <full-command>",
+        "    Return the keys from a full Redis command.",
+        "HELP",
+        "    Print this help.",
+    ];
+
+    Ok(RESPFrame::Array(help.iter().map(|line| RESPFrame::Simple(line.to_string())).collect()))
+}
+
+/**
+ * [name, arity, flags, first key, last key, step, ACL categories, tips, key specs, subcommands]
+ */
+fn info_frame(command: &RedisCommand) -> RESPFrame {
+    RESPFrame::Array(vec![
+        RESPFrame::Bulk(Bytes::from(command.name)),
+        RESPFrame::Integer(command.arity),
+        RESPFrame::Set(
+            command.flags.iter().map(|flag| flag.name())
+                // Keys found from the arguments rather than their positions
+                .chain((command.keys.numkeys > 0).then_some("movablekeys"))
+                .map(|flag| RESPFrame::Simple(flag.to_owned()))
+                .collect()
+        ),
+        RESPFrame::Integer(command.keys.first),
+        RESPFrame::Integer(command.keys.last),
+        RESPFrame::Integer(command.keys.step),
+        RESPFrame::Set(
+            command.acl_categories().into_iter().map(|category| RESPFrame::Simple(category.to_owned())).collect()
+        ),
+        RESPFrame::Array(vec![]),
+        RESPFrame::Array(key_specs_frames(command)),
+        RESPFrame::Array(command.subcommands.iter().map(info_frame).collect()),
+    ])
+}
+
+/**
+ * Key specs in the Redis 7 format, derived from the legacy key positions
+ */
+fn key_specs_frames(command: &RedisCommand) -> Vec<RESPFrame> {
+    if command.keys == KeySpec::NONE {
+        return vec![]
+    }
+
+    let bulk = |s: &'static str| RESPFrame::Bulk(Bytes::from(s));
+    let access = if command.flags.contains(&CommandFlag::Write) { "RW" } else { "RO" };
+    let begin_search = |index| RESPFrame::Map(vec![
+        (bulk("type"), bulk("index")),
+        (bulk("spec"), RESPFrame::Map(vec![(bulk("index"), RESPFrame::Integer(index))])),
+    ]);
+
+    // Keys follow numkeys, relative to it
+    if command.keys.numkeys > 0 {
+        return vec![RESPFrame::Map(vec![
+            (bulk("flags"), RESPFrame::Set(vec![RESPFrame::Simple(access.to_owned())])),
+            (bulk("begin_search"), begin_search(command.keys.numkeys)),
+            (bulk("find_keys"), RESPFrame::Map(vec![
+                (bulk("type"), bulk("keynum")),
+                (bulk("spec"), RESPFrame::Map(vec![
+                    (bulk("keynumidx"), RESPFrame::Integer(0)),
+                    (bulk("firstkey"), RESPFrame::Integer(1)),
+                    (bulk("keystep"), RESPFrame::Integer(1)),
+                ])),
+            ])),
+        ])]
+    }
+
+    // Last key is relative to the first key, unless it counts back from the end
+    let last_key = if command.keys.last < 0 {
+        command.keys.last
+    } else {
+        command.keys.last - command.keys.first
+    };
+
+    vec![RESPFrame::Map(vec![
+        (bulk("flags"), RESPFrame::Set(vec![RESPFrame::Simple(access.to_owned())])),
+        (bulk("begin_search"), begin_search(command.keys.first)),
+        (bulk("find_keys"), RESPFrame::Map(vec![
+            (bulk("type"), bulk("range")),
+            (bulk("spec"), RESPFrame::Map(vec![
+                (bulk("lastkey"), RESPFrame::Integer(last_key)),
+                (bulk("keystep"), RESPFrame::Integer(command.keys.step)),
+                (bulk("limit"), RESPFrame::Integer(0)),
+            ])),
+        ])),
+    ])]
+}
+
+fn docs_frame(command: &RedisCommand) -> RESPFrame {
+    let bulk = |s: &'static str| RESPFrame::Bulk(Bytes::from(s));
+    let mut docs = vec![
+        (bulk("summary"), bulk(command.summary)),
+        (bulk("since"), bulk(command.since)),
+        (bulk("group"), bulk(command.group.name())),
+    ];
+
+    if !command.subcommands.is_empty() {
+        docs.push((bulk("subcommands"), RESPFrame::Map(
+            command.subcommands.iter()
+                .map(|subcommand| (bulk(subcommand.name), docs_frame(subcommand)))
+                .collect()
+        )))
+    }
+
+    RESPFrame::Map(docs)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::interpret_inline;
+    use super::*;
+    use rstest::rstest;
+
+    fn find_pair<'a>(pairs: &'a [(RESPFrame, RESPFrame)], key: &str) -> Option<&'a RESPFrame> {
+        pairs.iter()
+            .find(|(pair_key, _)| matches!(pair_key, RESPFrame::Bulk(s) if s == key))
+            .map(|(_, value)| value)
+    }
+
+    #[tokio::test]
+    async fn should_list_all_commands() {
+        let response = interpret_inline("COMMAND").await;
+
+        assert!(matches!(
+            response,
+            RESPFrame::Array(commands) if commands.len() == COMMAND_TABLE.len()
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_count_commands() {
+        let response = interpret_inline("command count").await;
+
+        assert!(matches!(response, RESPFrame::Integer(n) if n == COMMAND_TABLE.len() as i64));
+    }
+
+    #[tokio::test]
+    async fn should_reply_command_info() {
+        let response = interpret_inline("COMMAND INFO get unknown").await;
+
+        assert!(matches!(
+            response,
+            RESPFrame::Array(infos) if matches!(
+                infos.as_slice(),
+                [RESPFrame::Array(get_info), RESPFrame::Null] if matches!(
+                    get_info.as_slice(),
+                    [
+                        RESPFrame::Bulk(name),
+                        RESPFrame::Integer(2),
+                        RESPFrame::Set(flags),
+                        RESPFrame::Integer(1),
+                        RESPFrame::Integer(1),
+                        RESPFrame::Integer(1),
+                        RESPFrame::Set(categories),
+                        RESPFrame::Array(tips),
+                        RESPFrame::Array(key_specs),
+                        RESPFrame::Array(subcommands),
+                    ] if name == "get"
+                        && flags.len() == 2
+                        && categories.len() == 3
+                        && tips.is_empty()
+                        && key_specs.len() == 1
+                        && subcommands.is_empty()
+                )
+            )
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_reply_info_of_command_with_numkeys() {
+        let response = interpret_inline("COMMAND INFO blmpop").await;
+
+        let RESPFrame::Array(infos) = response else { panic!("expected an array") };
+        let [RESPFrame::Array(info)] = infos.as_slice() else { panic!("expected one command") };
+        assert!(matches!(&info[2], RESPFrame::Set(flags) if flags.iter().any(|flag| matches!(flag, RESPFrame::Simple(s) if s == "movablekeys"))));
+        assert!(matches!(&info[3..6], [RESPFrame::Integer(0), RESPFrame::Integer(0), RESPFrame::Integer(0)]));
+
+        let RESPFrame::Array(key_specs) = &info[8] else { panic!("expected key specs") };
+        let [RESPFrame::Map(key_spec)] = key_specs.as_slice() else { panic!("expected one key spec") };
+        assert!(matches!(
+            find_pair(key_spec, "begin_search"),
+            Some(RESPFrame::Map(begin_search)) if matches!(
+                find_pair(begin_search, "spec"),
+                Some(RESPFrame::Map(spec)) if matches!(find_pair(spec, "index"), Some(RESPFrame::Integer(2)))
+            )
+        ));
+        assert!(matches!(
+            find_pair(key_spec, "find_keys"),
+            Some(RESPFrame::Map(find_keys)) if matches!(find_pair(find_keys, "type"), Some(RESPFrame::Bulk(s)) if s == "keynum")
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_reply_subcommand_info() {
+        let response = interpret_inline("COMMAND INFO command").await;
+
+        assert!(matches!(
+            response,
+            RESPFrame::Array(infos) if matches!(
+                infos.as_slice(),
+                [RESPFrame::Array(info)] if matches!(
+                    info.last(),
+                    Some(RESPFrame::Array(subcommands)) if subcommands.len() == 5
+                )
+            )
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_reply_command_docs() {
+        let response = interpret_inline("COMMAND DOCS set command missing").await;
+
+        let RESPFrame::Map(docs) = response else { panic!("Expected docs map") };
+        assert_eq!(2, docs.len());
+        assert!(matches!(
+            find_pair(&docs, "set"),
+            Some(RESPFrame::Map(set_docs)) if matches!(
+                find_pair(set_docs, "group"),
+                Some(RESPFrame::Bulk(group)) if group == "string"
+            ) && matches!(
+                find_pair(set_docs, "since"),
+                Some(RESPFrame::Bulk(since)) if since == "1.0.0"
+            )
+        ));
+        assert!(matches!(
+            find_pair(&docs, "command"),
+            Some(RESPFrame::Map(command_docs)) if matches!(
+                find_pair(command_docs, "subcommands"),
+                Some(RESPFrame::Map(subcommands)) if find_pair(subcommands, "command|docs").is_some()
+            )
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_reply_all_command_docs() {
+        let response = interpret_inline("COMMAND DOCS").await;
+
+        assert!(matches!(response, RESPFrame::Map(docs) if docs.len() == COMMAND_TABLE.len()));
+    }
+
+    #[rstest]
+    #[case("COMMAND GETKEYS GET key", vec!["key"])]
+    #[case("COMMAND GETKEYS set key value EX 10", vec!["key"])]
+    #[case("COMMAND GETKEYS MSET a 1 b 2 c 3", vec!["a", "b", "c"])]
+    #[case("COMMAND GETKEYS DEL a b", vec!["a", "b"])]
+    #[case("COMMAND GETKEYS BLMPOP 0 2 a b LEFT COUNT 2", vec!["a", "b"])]
+    #[case("COMMAND GETKEYS LMPOP 1 a RIGHT", vec!["a"])]
+    #[tokio::test]
+    async fn should_get_command_keys(#[case] request: &str, #[case] expected_keys: Vec<&str>) {
+        let response = interpret_inline(request).await;
+
+        assert!(matches!(
+            response,
+            RESPFrame::Array(keys) if keys.len() == expected_keys.len()
+                && keys.iter().zip(expected_keys).all(|(key, expected)| matches!(
+                    key,
+                    RESPFrame::Bulk(key) if key == expected
+                ))
+        ));
+    }
+
+    #[rstest]
+    #[case("COMMAND GETKEYS", "ERR wrong number of arguments for 'command|getkeys' command")]
+    #[case("COMMAND GETKEYS missing key", "ERR Invalid command specified")]
+    #[case("COMMAND GETKEYS GET", "ERR Invalid number of arguments specified for command")]
+    #[case("COMMAND GETKEYS ECHO message", "ERR The command has no key arguments")]
+    #[case("COMMAND GETKEYS BLMPOP 0 4 a b LEFT", "ERR Invalid arguments specified for command")]
+    #[case("COMMAND COUNT extra", "ERR wrong number of arguments for 'command|count' command")]
+    #[case("COMMAND MISSING", "ERR unknown subcommand 'MISSING'. Try COMMAND HELP.")]
+    #[tokio::test]
+    async fn should_reject_bad_command_requests(#[case] request: &str, #[case] expected_error: &str) {
+        let response = interpret_inline(request).await;
+
+        assert!(matches!(response, RESPFrame::Error(s) if s == expected_error));
+    }
+
+    #[tokio::test]
+    async fn should_reply_command_help() {
+        let response = interpret_inline("COMMAND HELP").await;
+
+        assert!(matches!(response, RESPFrame::Array(lines) if !lines.is_empty()));
+    }
+}
@@ -0,0 +1,985 @@
+use std::mem::discriminant;
+
+use bytes::Bytes;
+
+use crate::{error::RedisError, resp::{frame::RESPFrame, parser::MAX_BULK_SIZE}, store::RedisStore};
+
+use super::{
+    clamp_range, parse_float, parse_integer, CommandContext, CommandResult, ExpireCommandFlags,
+    SetCommandFlags, SetCommandExistFlag, SetCommandTTLFlag
+};
+
+/**
+ * GET key
+ */
+pub fn get(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    Ok(ctx.store.get(key)?.map_or(RESPFrame::Null, RESPFrame::Bulk))
+}
+
+/**
+ * SET key value [NX | XX] [GET] [EX seconds | PX milliseconds | EXAT unix-time-seconds | PXAT unix-time-milliseconds | KEEPTTL]
+ */
+pub fn set(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, value, options @ ..] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let set_flags = calculate_set_flags(ctx, options)?;
+    let store = &mut ctx.store;
+
+    let prev_value = if set_flags.get_flag {
+        store.get(key)?
+    } else { None };
+
+    let update_success = store.set(key, value, &set_flags);
+
+    if set_flags.get_flag {
+        Ok(prev_value.map_or(RESPFrame::Null, RESPFrame::Bulk))
+    } else if update_success {
+        Ok(RESPFrame::Simple("OK".to_owned()))
+    } else {
+        Ok(RESPFrame::Null)
+    }
+}
+
+/**
+ * MGET key [key ...]
+ */
+pub fn mget(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    Ok(RESPFrame::Array(
+        args.iter()
+            .map(|key| ctx.store.get(key).ok().flatten().map_or(RESPFrame::Null, RESPFrame::Bulk))
+            .collect()
+    ))
+}
+
+/**
+ * MSET key value [key value ...]
+ */
+pub fn mset(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    if !args.len().is_multiple_of(2) {
+        return Err(ctx.wrong_arity())
+    }
+
+    for pair in args.chunks_exact(2) {
+        ctx.store.set(&pair[0], &pair[1], &SetCommandFlags::default());
+    }
+
+    Ok(RESPFrame::Simple("OK".to_owned()))
+}
+
+/**
+ * MSETNX key value [key value ...]
+ * Sets none of the keys if any of them exist
+ */
+pub fn msetnx(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    if !args.len().is_multiple_of(2) {
+        return Err(ctx.wrong_arity())
+    }
+
+    if args.iter().step_by(2).any(|key| ctx.store.exists(key)) {
+        return Ok(RESPFrame::Integer(0))
+    }
+
+    for pair in args.chunks_exact(2) {
+        ctx.store.set(&pair[0], &pair[1], &SetCommandFlags::default());
+    }
+
+    Ok(RESPFrame::Integer(1))
+}
+
+/**
+ * GETEX key [EX seconds | PX milliseconds | EXAT unix-time-seconds | PXAT unix-time-milliseconds | PERSIST]
+ */
+pub fn getex(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, options @ ..] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let mut ttl_flag = None;
+    let mut persist = false;
+    match options {
+        [] => {},
+        [option] if option.eq_ignore_ascii_case(b"PERSIST") => persist = true,
+        [ttl_type, ttl] => ttl_flag = Some(parse_ttl_flag(ctx, ttl_type, ttl)?),
+        _ => return Err(RedisError::Syntax)
+    }
+
+    let Some(value) = ctx.store.get(key)? else {
+        return Ok(RESPFrame::Null)
+    };
+
+    if let Some(deadline) = ttl_flag.and_then(|ttl_flag| ttl_flag.deadline(RedisStore::get_unix_time())) {
+        ctx.store.expire(key, deadline, &ExpireCommandFlags::default());
+    } else if persist {
+        ctx.store.persist(key);
+    }
+
+    Ok(RESPFrame::Bulk(value))
+}
+
+/**
+ * GETDEL key
+ */
+pub fn getdel(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let value = ctx.store.get(key)?;
+    ctx.store.del(key);
+
+    Ok(value.map_or(RESPFrame::Null, RESPFrame::Bulk))
+}
+
+/**
+ * GETSET key value
+ * Replaces the value and clears its TTL, replying with the old value
+ */
+pub fn getset(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, value] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let prev_value = ctx.store.get(key)?;
+    ctx.store.set(key, value, &SetCommandFlags::default());
+
+    Ok(prev_value.map_or(RESPFrame::Null, RESPFrame::Bulk))
+}
+
+/**
+ * SETNX key value
+ */
+pub fn setnx(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, value] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let flags = SetCommandFlags { exist_flag: Some(SetCommandExistFlag::NX), ..Default::default() };
+    Ok(RESPFrame::Integer(ctx.store.set(key, value, &flags) as i64))
+}
+
+/**
+ * SETEX key seconds value
+ */
+pub fn setex(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    set_with_ttl(ctx, args, b"EX")
+}
+
+/**
+ * PSETEX key milliseconds value
+ */
+pub fn psetex(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    set_with_ttl(ctx, args, b"PX")
+}
+
+/**
+ * INCR key
+ */
+pub fn incr(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    Ok(RESPFrame::Integer(ctx.store.incr_by(key, 1)?))
+}
+
+/**
+ * DECR key
+ */
+pub fn decr(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    Ok(RESPFrame::Integer(ctx.store.incr_by(key, -1)?))
+}
+
+/**
+ * INCRBY key increment
+ */
+pub fn incrby(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, increment] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    Ok(RESPFrame::Integer(ctx.store.incr_by(key, parse_integer(increment)?)?))
+}
+
+/**
+ * DECRBY key decrement
+ */
+pub fn decrby(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, decrement] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let increment = parse_integer(decrement)?.checked_neg()
+        .ok_or_else(|| RedisError::Custom("decrement would overflow".to_owned()))?;
+
+    Ok(RESPFrame::Integer(ctx.store.incr_by(key, increment)?))
+}
+
+/**
+ * INCRBYFLOAT key increment
+ */
+pub fn incrbyfloat(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, increment] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    Ok(RESPFrame::Bulk(ctx.store.incr_by_float(key, parse_float(increment)?)?))
+}
+
+/**
+ * APPEND key value
+ */
+pub fn append(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, value] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    check_string_length(ctx.store.strlen(key)? + value.len())?;
+
+    Ok(RESPFrame::Integer(ctx.store.append(key, value)? as i64))
+}
+
+/**
+ * STRLEN key
+ */
+pub fn strlen(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    Ok(RESPFrame::Integer(ctx.store.strlen(key)? as i64))
+}
+
+/**
+ * GETRANGE key start end
+ * Negative indices count back from the end, and the range is clamped to the string
+ */
+pub fn getrange(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, start, end] = args else {
+        return Err(ctx.wrong_arity())
+    };
+    let (start, end) = (parse_integer(start)?, parse_integer(end)?);
+
+    let value = ctx.store.get_raw(key)?.unwrap_or_default();
+
+    Ok(RESPFrame::Bulk(
+        clamp_range(start, end, value.len()).map_or_else(Bytes::new, |range| Bytes::copy_from_slice(&value[range]))
+    ))
+}
+
+/**
+ * SETRANGE key offset value
+ * Zero pads the string if offset is past its end
+ */
+pub fn setrange(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key, offset, value] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let offset = parse_integer(offset)?;
+    if offset < 0 {
+        return Err(RedisError::Custom("offset is out of range".to_owned()))
+    }
+    if value.is_empty() {
+        return Ok(RESPFrame::Integer(ctx.store.strlen(key)? as i64))
+    }
+
+    let offset = offset as usize;
+    check_string_length(offset.saturating_add(value.len()))?;
+
+    Ok(RESPFrame::Integer(ctx.store.setrange(key, offset, value)? as i64))
+}
+
+/**
+ * LCS key1 key2 [LEN] [IDX] [MINMATCHLEN min-match-len] [WITHMATCHLEN]
+ * Missing keys are treated as empty strings
+ */
+pub fn lcs(ctx: &mut CommandContext, args: &[Bytes]) -> CommandResult {
+    let [key1, key2, options @ ..] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let (mut len_flag, mut idx_flag, mut with_match_len, mut min_match_len) = (false, false, false, 0);
+    let mut options = options;
+    while let [option, rest @ ..] = options {
+        options = rest;
+
+        match option.to_ascii_uppercase().as_slice() {
+            b"LEN" => len_flag = true,
+            b"IDX" => idx_flag = true,
+            b"WITHMATCHLEN" => with_match_len = true,
+            b"MINMATCHLEN" => {
+                let [min, rest @ ..] = options else {
+                    return Err(RedisError::Syntax)
+                };
+                min_match_len = parse_integer(min)?.max(0) as usize;
+                options = rest
+            },
+            _ => return Err(RedisError::Syntax)
+        }
+    }
+
+    if len_flag && idx_flag {
+        return Err(RedisError::Custom("If you want both the length and indexes, please just use IDX.".to_owned()))
+    }
+
+    let not_strings = |_| RedisError::Custom("The specified keys must contain string values".to_owned());
+    let a = ctx.store.get(key1).map_err(not_strings)?.unwrap_or_default();
+    let b = ctx.store.get(key2).map_err(not_strings)?.unwrap_or_default();
+    let table_size = (a.len() + 1).checked_mul(b.len() + 1)
+        .and_then(|cells| cells.checked_mul(std::mem::size_of::<u32>()));
+    if table_size.is_none_or(|size| size > MAX_BULK_SIZE as usize) {
+        return Err(RedisError::Custom("Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len".to_owned()))
+    }
+
+    let (subsequence, matches) = longest_common_subsequence(&a, &b);
+
+    if len_flag {
+        return Ok(RESPFrame::Integer(subsequence.len() as i64))
+    }
+    if !idx_flag {
+        return Ok(RESPFrame::Bulk(Bytes::from(subsequence)))
+    }
+
+    let range = |(start, end): (usize, usize)| RESPFrame::Array(vec![
+        RESPFrame::Integer(start as i64),
+        RESPFrame::Integer(end as i64),
+    ]);
+    let matches = matches.into_iter()
+        .filter(|(a_range, _)| a_range.1 - a_range.0 + 1 >= min_match_len)
+        .map(|(a_range, b_range)| {
+            let mut frames = vec![range(a_range), range(b_range)];
+            if with_match_len {
+                frames.push(RESPFrame::Integer((a_range.1 - a_range.0 + 1) as i64))
+            }
+            RESPFrame::Array(frames)
+        })
+        .collect();
+
+    Ok(RESPFrame::Map(vec![
+        (RESPFrame::Bulk(Bytes::from("matches")), RESPFrame::Array(matches)),
+        (RESPFrame::Bulk(Bytes::from("len")), RESPFrame::Integer(subsequence.len() as i64)),
+    ]))
+}
+
+type LcsRange = (usize, usize);
+
+/**
+ * Finds the LCS and its contiguous matching ranges (inclusive), from the last match to the first
+ */
+fn longest_common_subsequence(a: &[u8], b: &[u8]) -> (Vec<u8>, Vec<(LcsRange, LcsRange)>) {
+    let width = b.len() + 1;
+    let mut table = vec![0u32; (a.len() + 1) * width];
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            table[i * width + j] = if a[i - 1] == b[j - 1] {
+                table[(i - 1) * width + j - 1] + 1
+            } else {
+                table[(i - 1) * width + j].max(table[i * width + j - 1])
+            };
+        }
+    }
+
+    let mut subsequence = vec![0u8; table[a.len() * width + b.len()] as usize];
+    let mut matches = vec![];
+    let mut current: Option<(LcsRange, LcsRange)> = None;
+    let (mut i, mut j, mut index) = (a.len(), b.len(), subsequence.len());
+
+    // Walk back through the table, extending each match backwards while it stays contiguous
+    while i > 0 && j > 0 {
+        if a[i - 1] == b[j - 1] {
+            index -= 1;
+            subsequence[index] = a[i - 1];
+            i -= 1;
+            j -= 1;
+
+            let (a_range, b_range) = current.get_or_insert(((i, i), (j, j)));
+            a_range.0 = i;
+            b_range.0 = j;
+        } else {
+            if table[(i - 1) * width + j] > table[i * width + j - 1] {
+                i -= 1
+            } else {
+                j -= 1
+            }
+            matches.extend(current.take());
+        }
+    }
+    matches.extend(current.take());
+
+    (subsequence, matches)
+}
+
+/**
+ * Strings are limited to Redis' default proto-max-bulk-len
+ */
+fn check_string_length(length: usize) -> Result<(), RedisError> {
+    if length > MAX_BULK_SIZE as usize {
+        return Err(RedisError::Custom("string exceeds maximum allowed size (proto-max-bulk-len)".to_owned()))
+    }
+    Ok(())
+}
+
+fn set_with_ttl(ctx: &mut CommandContext, args: &[Bytes], ttl_type: &[u8]) -> CommandResult {
+    let [key, ttl, value] = args else {
+        return Err(ctx.wrong_arity())
+    };
+
+    let flags = SetCommandFlags { ttl_flag: Some(parse_ttl_flag(ctx, ttl_type, ttl)?), ..Default::default() };
+    ctx.store.set(key, value, &flags);
+
+    Ok(RESPFrame::Simple("OK".to_owned()))
+}
+
+/**
+ * Parses a positive TTL whose deadline fits in epoch milliseconds
+ */
+fn parse_ttl_flag(ctx: &CommandContext, ttl_type: &[u8], ttl: &[u8]) -> Result<SetCommandTTLFlag, RedisError> {
+    let invalid_expire_time = || RedisError::InvalidExpireTime(ctx.command.name.to_owned());
+
+    let ttl = parse_integer(ttl)?;
+    if ttl <= 0 {
+        return Err(invalid_expire_time())
+    }
+
+    let ttl_flag = match ttl_type.to_ascii_uppercase().as_slice() {
+        b"EX" => SetCommandTTLFlag::EX(ttl as u64),
+        b"PX" => SetCommandTTLFlag::PX(ttl as u64),
+        b"EXAT" => SetCommandTTLFlag::EXAT(ttl as u64),
+        b"PXAT" => SetCommandTTLFlag::PXAT(ttl as u64),
+        _ => return Err(RedisError::Syntax)
+    };
+
+    match ttl_flag.deadline(RedisStore::get_unix_time()) {
+        Some(deadline) if deadline <= i64::MAX as u64 => Ok(ttl_flag),
+        _ => Err(invalid_expire_time())
+    }
+}
+
+/**
+ * Parses SET options in any order
+ * Repeating an option is allowed, but combining conflicting options is a syntax error
+ */
+fn calculate_set_flags(ctx: &CommandContext, options: &[Bytes]) -> Result<SetCommandFlags, RedisError> {
+    let mut set_flags = SetCommandFlags::default();
+    // TTL type and value, only validated once the options are syntactically valid
+    let mut ttl_option: Option<(Vec<u8>, Option<&Bytes>)> = None;
+    let mut options = options;
+
+    while let [option, rest @ ..] = options {
+        let option = option.to_ascii_uppercase();
+        options = rest;
+
+        match option.as_slice() {
+            b"NX" | b"XX" => {
+                let exist_flag = if option == b"NX" { SetCommandExistFlag::NX } else { SetCommandExistFlag::XX };
+                if set_flags.exist_flag.as_ref().is_some_and(|existing| discriminant(existing) != discriminant(&exist_flag)) {
+                    return Err(RedisError::Syntax)
+                }
+                set_flags.exist_flag = Some(exist_flag)
+            },
+            b"GET" => set_flags.get_flag = true,
+            b"KEEPTTL" | b"EX" | b"PX" | b"EXAT" | b"PXAT" => {
+                if matches!(&ttl_option, Some((ttl_type, _)) if *ttl_type != option) {
+                    return Err(RedisError::Syntax)
+                }
+
+                let ttl = if option == b"KEEPTTL" {
+                    None
+                } else {
+                    let [ttl, rest @ ..] = options else {
+                        return Err(RedisError::Syntax)
+                    };
+                    options = rest;
+                    Some(ttl)
+                };
+                ttl_option = Some((option, ttl))
+            },
+            _ => return Err(RedisError::Syntax)
+        }
+    }
+
+    set_flags.ttl_flag = match ttl_option {
+        None => None,
+        Some((_, None)) => Some(SetCommandTTLFlag::KEEPTTL),
+        Some((ttl_type, Some(ttl))) => Some(parse_ttl_flag(ctx, &ttl_type, ttl)?),
+    };
+
+    Ok(set_flags)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::super::test_utils::{insert_value, interpret_inline, matches_bulk, matches_integer, matches_ok};
+    use super::*;
+    use crate::{clock::{Clock, MockClockSession}, store::RedisValue};
+    use rstest::rstest;
+
+    #[tokio::test]
+    async fn should_get_and_update_ttl() {
+        let _session = MockClockSession::new();
+        Clock::mock_set_time(1_000_000);
+
+        interpret_inline("SET test_getex_key value").await;
+
+        assert!(matches_bulk(interpret_inline("GETEX test_getex_key").await, "value"));
+        assert!(matches_integer(interpret_inline("TTL test_getex_key").await, -1));
+
+        assert!(matches_bulk(interpret_inline("GETEX test_getex_key EX 10").await, "value"));
+        assert!(matches_integer(interpret_inline("PTTL test_getex_key").await, 10_000));
+
+        assert!(matches_bulk(interpret_inline("GETEX test_getex_key px 500").await, "value"));
+        assert!(matches_integer(interpret_inline("PTTL test_getex_key").await, 500));
+
+        assert!(matches_bulk(interpret_inline("GETEX test_getex_key PXAT 1000200").await, "value"));
+        assert!(matches_integer(interpret_inline("PTTL test_getex_key").await, 200));
+
+        assert!(matches_bulk(interpret_inline("GETEX test_getex_key PERSIST").await, "value"));
+        assert!(matches_integer(interpret_inline("TTL test_getex_key").await, -1));
+
+        assert!(matches_bulk(interpret_inline("GETEX test_getex_key EXAT 999").await, "value"));
+        assert!(matches!(interpret_inline("GET test_getex_key").await, RESPFrame::Null));
+        assert!(matches!(interpret_inline("GETEX test_getex_key EX 10").await, RESPFrame::Null));
+    }
+
+    #[tokio::test]
+    async fn should_get_and_delete() {
+        interpret_inline("SET test_getdel_key value").await;
+
+        assert!(matches_bulk(interpret_inline("GETDEL test_getdel_key").await, "value"));
+        assert!(matches!(interpret_inline("GETDEL test_getdel_key").await, RESPFrame::Null));
+        assert!(matches_integer(interpret_inline("EXISTS test_getdel_key").await, 0));
+    }
+
+    #[tokio::test]
+    async fn should_get_and_set() {
+        let _session = MockClockSession::new();
+        Clock::mock_freeze();
+
+        assert!(matches!(interpret_inline("GETSET test_getset_key first").await, RESPFrame::Null));
+        interpret_inline("EXPIRE test_getset_key 10").await;
+
+        assert!(matches_bulk(interpret_inline("GETSET test_getset_key second").await, "first"));
+        assert!(matches_bulk(interpret_inline("GET test_getset_key").await, "second"));
+        assert!(matches_integer(interpret_inline("TTL test_getset_key").await, -1));
+    }
+
+    #[tokio::test]
+    async fn should_set_if_not_exists() {
+        assert!(matches_integer(interpret_inline("SETNX test_setnx_key first").await, 1));
+        assert!(matches_integer(interpret_inline("SETNX test_setnx_key second").await, 0));
+        assert!(matches_bulk(interpret_inline("GET test_setnx_key").await, "first"));
+    }
+
+    #[rstest]
+    #[case("SETEX test_setex_key 10 value", 10_000)]
+    #[case("PSETEX test_setex_key 1500 value", 1_500)]
+    #[tokio::test]
+    async fn should_set_with_ttl(#[case] request: &str, #[case] expected_pttl: i64) {
+        let _session = MockClockSession::new();
+        Clock::mock_freeze();
+
+        assert!(matches_ok(interpret_inline(request).await));
+        assert!(matches_bulk(interpret_inline("GET test_setex_key").await, "value"));
+        assert!(matches_integer(interpret_inline("PTTL test_setex_key").await, expected_pttl));
+
+        Clock::mock_advance(Duration::from_millis(expected_pttl as u64));
+        assert!(matches!(interpret_inline("GET test_setex_key").await, RESPFrame::Null));
+    }
+
+    #[rstest]
+    #[case("SETEX test_string_bad 0 value", "ERR invalid expire time in 'setex' command")]
+    #[case("PSETEX test_string_bad -5 value", "ERR invalid expire time in 'psetex' command")]
+    #[case("SETEX test_string_bad 9223372036854775 value", "ERR invalid expire time in 'setex' command")]
+    #[case("SETEX test_string_bad ten value", "ERR value is not an integer or out of range")]
+    #[case("GETEX test_string_bad EX 0", "ERR invalid expire time in 'getex' command")]
+    #[case("GETEX test_string_bad EX", "ERR syntax error")]
+    #[case("GETEX test_string_bad KEEPTTL 10", "ERR syntax error")]
+    #[case("GETEX test_string_bad PERSIST EX 10", "ERR syntax error")]
+    #[case("GETSET test_string_bad", "ERR wrong number of arguments for 'getset' command")]
+    #[tokio::test]
+    async fn should_reject_bad_string_requests(#[case] request: &str, #[case] expected_error: &str) {
+        let response = interpret_inline(request).await;
+
+        assert!(matches!(response, RESPFrame::Error(s) if s == expected_error));
+    }
+
+    #[rstest]
+    #[case("GET EX 10", Some("old"), Some(10_000))]
+    #[case("EX 10 GET", Some("old"), Some(10_000))]
+    #[case("px 100 xx", None, Some(100))]
+    #[case("XX GET PX 100", Some("old"), Some(100))]
+    #[case("EX 10 EX 20", None, Some(20_000))]
+    #[case("XX XX KEEPTTL", None, Some(5_000))]
+    #[case("KEEPTTL", None, Some(5_000))]
+    #[case("PXAT 1000300 GET", Some("old"), Some(300))]
+    #[case("EXAT 1010", None, Some(10_000))]
+    #[case("GET", Some("old"), None)]
+    #[case("", None, None)]
+    #[tokio::test]
+    async fn should_set_with_options_in_any_order(
+        #[case] options: &str,
+        #[case] expected_get: Option<&str>,
+        #[case] expected_pttl: Option<i64>
+    ) {
+        let _session = MockClockSession::new();
+        Clock::mock_set_time(1_000_000);
+        interpret_inline("SET test_set_options old PX 5000").await;
+
+        let response = interpret_inline(&format!("SET test_set_options new {}", options)).await;
+
+        match expected_get {
+            Some(expected_get) => assert!(matches_bulk(response, expected_get)),
+            None => assert!(matches_ok(response)),
+        }
+        assert!(matches_bulk(interpret_inline("GET test_set_options").await, "new"));
+        assert!(matches_integer(interpret_inline("PTTL test_set_options").await, expected_pttl.unwrap_or(-1)));
+    }
+
+    #[rstest]
+    #[case("NX XX", "ERR syntax error")]
+    #[case("XX GET NX", "ERR syntax error")]
+    #[case("EX 10 PX 100", "ERR syntax error")]
+    #[case("EX 10 KEEPTTL", "ERR syntax error")]
+    #[case("KEEPTTL PXAT 100", "ERR syntax error")]
+    #[case("EX", "ERR syntax error")]
+    #[case("GET EX", "ERR syntax error")]
+    #[case("EX 10 UNKNOWN", "ERR syntax error")]
+    #[case("KEEPTTL 10", "ERR syntax error")]
+    #[case("EX ten NX XX", "ERR syntax error")]
+    #[case("EX ten", "ERR value is not an integer or out of range")]
+    #[case("PX \"\\xff\"", "ERR value is not an integer or out of range")]
+    #[case("EX 0", "ERR invalid expire time in 'set' command")]
+    #[case("PX -100", "ERR invalid expire time in 'set' command")]
+    #[case("EXAT 0", "ERR invalid expire time in 'set' command")]
+    #[case("EX 9223372036854775807", "ERR invalid expire time in 'set' command")]
+    #[tokio::test]
+    async fn should_reject_bad_set_options(#[case] options: &str, #[case] expected_error: &str) {
+        let response = interpret_inline(&format!("SET test_set_bad_options value {}", options)).await;
+
+        assert!(matches!(response, RESPFrame::Error(s) if s == expected_error));
+        assert!(matches!(interpret_inline("GET test_set_bad_options").await, RESPFrame::Null));
+    }
+
+    #[tokio::test]
+    async fn should_check_every_pair_of_set_options() {
+        let _session = MockClockSession::new();
+        Clock::mock_set_time(1_000_000);
+        let options = ["NX", "XX", "GET", "KEEPTTL", "EX 10", "PX 100", "EXAT 2000", "PXAT 2000000"];
+        let option_name = |option: &str| option.split(' ').next().unwrap().to_owned();
+        let is_exist = |name: &str| name == "NX" || name == "XX";
+        let is_ttl = |name: &str| !is_exist(name) && name != "GET";
+
+        for first in options {
+            for second in options {
+                let (first_name, second_name) = (option_name(first), option_name(second));
+                let conflicts = first_name != second_name && (
+                    (is_exist(&first_name) && is_exist(&second_name))
+                        || (is_ttl(&first_name) && is_ttl(&second_name))
+                );
+
+                let request = format!("SET test_set_option_pairs value {} {}", first, second);
+                let response = interpret_inline(&request).await;
+
+                if conflicts {
+                    assert!(matches!(response, RESPFrame::Error(s) if s == "ERR syntax error"), "{}", request);
+                } else {
+                    assert!(!matches!(response, RESPFrame::Error(_)), "{}", request);
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn should_increment_and_decrement() {
+        assert!(matches_integer(interpret_inline("INCR test_incr_key").await, 1));
+        assert!(matches_integer(interpret_inline("INCRBY test_incr_key 41").await, 42));
+        assert!(matches_integer(interpret_inline("DECR test_incr_key").await, 41));
+        assert!(matches_integer(interpret_inline("DECRBY test_incr_key -9").await, 50));
+        assert!(matches_integer(interpret_inline("INCRBY test_incr_key -60").await, -10));
+        assert!(matches_bulk(interpret_inline("GET test_incr_key").await, "-10"));
+
+        interpret_inline("SET test_incr_key 99").await;
+        assert!(matches_integer(interpret_inline("INCR test_incr_key").await, 100));
+    }
+
+    #[tokio::test]
+    async fn should_keep_ttl_when_incrementing() {
+        let _session = MockClockSession::new();
+        Clock::mock_freeze();
+
+        interpret_inline("SET test_incr_ttl 10 EX 100").await;
+        assert!(matches_integer(interpret_inline("INCR test_incr_ttl").await, 11));
+        assert!(matches_bulk(interpret_inline("INCRBYFLOAT test_incr_ttl 0.5").await, "11.5"));
+        assert!(matches_integer(interpret_inline("TTL test_incr_ttl").await, 100));
+    }
+
+    #[rstest]
+    #[case("10", "0.1", "10.1")]
+    #[case("10.50", "0.5", "11")]
+    #[case("5.0e3", "2.0e2", "5200")]
+    #[case("3", "-1.5", "1.5")]
+    #[case("0", "1e-5", "0.00001")]
+    #[tokio::test]
+    async fn should_increment_by_float(#[case] initial: &str, #[case] increment: &str, #[case] expected: &str) {
+        interpret_inline(&format!("SET test_incrbyfloat_key {}", initial)).await;
+
+        assert!(matches_bulk(interpret_inline(&format!("INCRBYFLOAT test_incrbyfloat_key {}", increment)).await, expected));
+        assert!(matches_bulk(interpret_inline("GET test_incrbyfloat_key").await, expected));
+    }
+
+    #[rstest]
+    #[case("abc", "INCR", "ERR value is not an integer or out of range")]
+    #[case("1.5", "INCR", "ERR value is not an integer or out of range")]
+    #[case("007", "INCR", "ERR value is not an integer or out of range")]
+    #[case(" 7", "INCR", "ERR value is not an integer or out of range")]
+    #[case("9223372036854775807", "INCR", "ERR increment or decrement would overflow")]
+    #[case("-9223372036854775808", "DECR", "ERR increment or decrement would overflow")]
+    #[case("1", "INCRBY 1.5", "ERR value is not an integer or out of range")]
+    #[case("1", "INCRBY +1", "ERR value is not an integer or out of range")]
+    #[case("1", "DECRBY -9223372036854775808", "ERR decrement would overflow")]
+    #[case("abc", "INCRBYFLOAT 1", "ERR value is not a valid float")]
+    #[case("1", "INCRBYFLOAT one", "ERR value is not a valid float")]
+    #[case("1", "INCRBYFLOAT nan", "ERR value is not a valid float")]
+    #[case("1", "INCRBYFLOAT inf", "ERR increment would produce NaN or Infinity")]
+    #[case("1e308", "INCRBYFLOAT 1e308", "ERR increment would produce NaN or Infinity")]
+    #[tokio::test]
+    async fn should_reject_bad_increments(#[case] initial: &str, #[case] command: &str, #[case] expected_error: &str) {
+        interpret_inline(&format!("SET test_incr_bad \"{}\"", initial)).await;
+        let (name, increment) = command.split_once(' ').unwrap_or((command, ""));
+
+        let response = interpret_inline(&format!("{} test_incr_bad {}", name, increment)).await;
+
+        assert!(matches!(response, RESPFrame::Error(s) if s == expected_error));
+        assert!(matches_bulk(interpret_inline("GET test_incr_bad").await, initial));
+    }
+
+    #[tokio::test]
+    async fn should_append_and_measure_length() {
+        interpret_inline("SET test_append_ttl 12 EX 100").await;
+
+        assert!(matches_integer(interpret_inline("APPEND test_append_key Hello").await, 5));
+        assert!(matches_integer(interpret_inline("APPEND test_append_key \" World\"").await, 11));
+        assert!(matches_bulk(interpret_inline("GET test_append_key").await, "Hello World"));
+        assert!(matches_integer(interpret_inline("STRLEN test_append_key").await, 11));
+        assert!(matches_integer(interpret_inline("STRLEN test_append_missing").await, 0));
+
+        assert!(matches_integer(interpret_inline("APPEND test_append_ttl 34").await, 4));
+        assert!(matches_integer(interpret_inline("STRLEN test_append_ttl").await, 4));
+        assert!(matches_integer(interpret_inline("INCR test_append_ttl").await, 1235));
+        assert!(!matches_integer(interpret_inline("TTL test_append_ttl").await, -1));
+    }
+
+    #[rstest]
+    #[case("0 3", "This")]
+    #[case("-3 -1", "ing")]
+    #[case("0 -1", "This is a string")]
+    #[case("10 100", "string")]
+    #[case("-100 3", "This")]
+    #[case("5 3", "")]
+    #[case("-1 -5", "")]
+    #[case("16 20", "")]
+    #[tokio::test]
+    async fn should_get_range(#[case] range: &str, #[case] expected: &str) {
+        interpret_inline("SET test_getrange_key \"This is a string\"").await;
+
+        assert!(matches_bulk(interpret_inline(&format!("GETRANGE test_getrange_key {}", range)).await, expected));
+    }
+
+    #[tokio::test]
+    async fn should_get_range_of_missing_key() {
+        assert!(matches_bulk(interpret_inline("GETRANGE test_getrange_missing 0 -1").await, ""));
+    }
+
+    #[tokio::test]
+    async fn should_set_range() {
+        interpret_inline("SET test_setrange_key \"Hello World\"").await;
+
+        assert!(matches_integer(interpret_inline("SETRANGE test_setrange_key 6 Redis").await, 11));
+        assert!(matches_bulk(interpret_inline("GET test_setrange_key").await, "Hello Redis"));
+        assert!(matches_integer(interpret_inline("SETRANGE test_setrange_key 0 \"\"").await, 11));
+
+        assert!(matches_integer(interpret_inline("SETRANGE test_setrange_padded 3 abc").await, 6));
+        assert!(matches!(
+            interpret_inline("GET test_setrange_padded").await,
+            RESPFrame::Bulk(s) if s.as_ref() == b"\0\0\0abc"
+        ));
+        assert!(matches_integer(interpret_inline("SETRANGE test_setrange_empty 5 \"\"").await, 0));
+        assert!(matches_integer(interpret_inline("EXISTS test_setrange_empty").await, 0));
+    }
+
+    #[rstest]
+    #[case("SETRANGE test_setrange_bad -1 value", "ERR offset is out of range")]
+    #[case("SETRANGE test_setrange_bad 536870912 value", "ERR string exceeds maximum allowed size (proto-max-bulk-len)")]
+    #[case("SETRANGE test_setrange_bad one value", "ERR value is not an integer or out of range")]
+    #[tokio::test]
+    async fn should_limit_set_range(#[case] request: &str, #[case] expected_error: &str) {
+        let response = interpret_inline(request).await;
+
+        assert!(matches!(response, RESPFrame::Error(s) if s == expected_error));
+    }
+
+    #[rstest]
+    #[case(512 * 1024 * 1024, true)]
+    #[case(512 * 1024 * 1024 + 1, false)]
+    fn should_check_string_length_against_max_bulk_size(#[case] length: usize, #[case] allowed: bool) {
+        assert_eq!(allowed, check_string_length(length).is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_find_longest_common_subsequence() {
+        interpret_inline("SET test_lcs_key1 ohmytext").await;
+        interpret_inline("SET test_lcs_key2 mynewtext").await;
+
+        assert!(matches_bulk(interpret_inline("LCS test_lcs_key1 test_lcs_key2").await, "mytext"));
+        assert!(matches_integer(interpret_inline("LCS test_lcs_key1 test_lcs_key2 LEN").await, 6));
+        assert!(matches_bulk(interpret_inline("LCS test_lcs_key1 test_lcs_missing").await, ""));
+    }
+
+    fn lcs_match(a: (i64, i64), b: (i64, i64), length: Option<i64>) -> RESPFrame {
+        let range = |(start, end)| RESPFrame::Array(vec![RESPFrame::Integer(start), RESPFrame::Integer(end)]);
+        let mut frames = vec![range(a), range(b)];
+        frames.extend(length.map(RESPFrame::Integer));
+        RESPFrame::Array(frames)
+    }
+
+    #[rstest]
+    #[case("IDX", vec![lcs_match((4, 7), (5, 8), None), lcs_match((2, 3), (0, 1), None)])]
+    #[case("IDX MINMATCHLEN 4", vec![lcs_match((4, 7), (5, 8), None)])]
+    #[case("WITHMATCHLEN IDX minmatchlen 3", vec![lcs_match((4, 7), (5, 8), Some(4))])]
+    #[case("IDX WITHMATCHLEN", vec![lcs_match((4, 7), (5, 8), Some(4)), lcs_match((2, 3), (0, 1), Some(2))])]
+    #[case("IDX MINMATCHLEN -5", vec![lcs_match((4, 7), (5, 8), None), lcs_match((2, 3), (0, 1), None)])]
+    #[tokio::test]
+    async fn should_find_longest_common_subsequence_indexes(#[case] options: &str, #[case] expected_matches: Vec<RESPFrame>) {
+        interpret_inline("SET test_lcs_idx_key1 ohmytext").await;
+        interpret_inline("SET test_lcs_idx_key2 mynewtext").await;
+
+        let response = interpret_inline(&format!("LCS test_lcs_idx_key1 test_lcs_idx_key2 {}", options)).await;
+
+        assert_eq!(RESPFrame::Map(vec![
+            (RESPFrame::Bulk(Bytes::from("matches")), RESPFrame::Array(expected_matches)),
+            (RESPFrame::Bulk(Bytes::from("len")), RESPFrame::Integer(6)),
+        ]), response);
+    }
+
+    #[rstest]
+    #[case("LCS test_lcs_bad1 test_lcs_bad2 LEN IDX", "ERR If you want both the length and indexes, please just use IDX.")]
+    #[case("LCS test_lcs_bad1 test_lcs_bad2 MINMATCHLEN", "ERR syntax error")]
+    #[case("LCS test_lcs_bad1 test_lcs_bad2 MINMATCHLEN x", "ERR value is not an integer or out of range")]
+    #[case("LCS test_lcs_bad1 test_lcs_bad2 UNKNOWN", "ERR syntax error")]
+    #[tokio::test]
+    async fn should_reject_bad_lcs_options(#[case] request: &str, #[case] expected_error: &str) {
+        let response = interpret_inline(request).await;
+
+        assert!(matches!(response, RESPFrame::Error(s) if s == expected_error));
+    }
+
+    #[tokio::test]
+    async fn should_get_multiple_keys() {
+        interpret_inline("SET test_mget_a 1").await;
+        interpret_inline("SET test_mget_b two").await;
+
+        assert_eq!(
+            RESPFrame::Array(vec![
+                RESPFrame::Bulk(Bytes::from("1")),
+                RESPFrame::Null,
+                RESPFrame::Bulk(Bytes::from("two")),
+            ]),
+            interpret_inline("MGET test_mget_a test_mget_missing test_mget_b").await
+        );
+    }
+
+    #[tokio::test]
+    async fn should_set_multiple_keys() {
+        let _session = MockClockSession::new();
+        Clock::mock_freeze();
+        interpret_inline("SET test_mset_b old EX 10").await;
+
+        assert!(matches_ok(interpret_inline("MSET test_mset_a 1 test_mset_b 2 test_mset_a 3").await));
+        assert!(matches_bulk(interpret_inline("GET test_mset_a").await, "3"));
+        assert!(matches_bulk(interpret_inline("GET test_mset_b").await, "2"));
+        assert!(matches_integer(interpret_inline("TTL test_mset_b").await, -1));
+    }
+
+    #[tokio::test]
+    async fn should_set_multiple_keys_only_if_none_exist() {
+        interpret_inline("SET test_msetnx_existing old").await;
+
+        assert!(matches_integer(interpret_inline("MSETNX test_msetnx_a 1 test_msetnx_existing 2").await, 0));
+        assert!(matches_integer(interpret_inline("EXISTS test_msetnx_a").await, 0));
+        assert!(matches_bulk(interpret_inline("GET test_msetnx_existing").await, "old"));
+
+        assert!(matches_integer(interpret_inline("MSETNX test_msetnx_a 1 test_msetnx_b 2").await, 1));
+        assert!(matches_bulk(interpret_inline("GET test_msetnx_a").await, "1"));
+        assert!(matches_bulk(interpret_inline("GET test_msetnx_b").await, "2"));
+    }
+
+    #[rstest]
+    #[case("MSET test_mset_odd", "mset")]
+    #[case("MSET test_mset_odd 1 test_mset_odd_b", "mset")]
+    #[case("MSETNX test_mset_odd 1 test_mset_odd_b", "msetnx")]
+    #[case("MGET", "mget")]
+    #[tokio::test]
+    async fn should_reject_unpaired_keys(#[case] request: &str, #[case] command: &str) {
+        let response = interpret_inline(request).await;
+
+        assert!(matches!(
+            response,
+            RESPFrame::Error(s) if s == format!("ERR wrong number of arguments for '{}' command", command)
+        ));
+        assert!(matches_integer(interpret_inline("EXISTS test_mset_odd").await, 0));
+    }
+
+    #[rstest]
+    #[case("GET test_string_wrongtype")]
+    #[case("SET test_string_wrongtype value GET")]
+    #[case("GETEX test_string_wrongtype PERSIST")]
+    #[case("GETDEL test_string_wrongtype")]
+    #[case("GETSET test_string_wrongtype value")]
+    #[case("INCR test_string_wrongtype")]
+    #[case("DECRBY test_string_wrongtype 2")]
+    #[case("INCRBYFLOAT test_string_wrongtype 1.5")]
+    #[case("APPEND test_string_wrongtype value")]
+    #[case("STRLEN test_string_wrongtype")]
+    #[case("GETRANGE test_string_wrongtype 0 -1")]
+    #[case("SETRANGE test_string_wrongtype 0 value")]
+    #[tokio::test]
+    async fn should_reject_other_types(#[case] request: &str) {
+        insert_value("test_string_wrongtype", RedisValue::List(vec![Bytes::from("a")].into_iter().collect())).await;
+
+        let response = interpret_inline(request).await;
+
+        assert!(matches!(response, RESPFrame::Error(s) if s == "WRONGTYPE Operation against a key holding the wrong kind of value"));
+        assert!(matches!(interpret_inline("TYPE test_string_wrongtype").await, RESPFrame::Simple(s) if s == "list"));
+    }
+
+    #[tokio::test]
+    async fn should_handle_other_types_like_redis() {
+        insert_value("test_string_other_type", RedisValue::Hash(Default::default())).await;
+        interpret_inline("SET test_string_other_type_string value").await;
+
+        // MGET replies null for keys that aren't strings, and LCS has its own error
+        assert_eq!(
+            interpret_inline("MGET test_string_other_type test_string_other_type_string").await,
+            RESPFrame::Array(vec![RESPFrame::Null, RESPFrame::Bulk(Bytes::from("value"))])
+        );
+        assert!(matches!(
+            interpret_inline("LCS test_string_other_type test_string_other_type_string").await,
+            RESPFrame::Error(s) if s == "ERR The specified keys must contain string values"
+        ));
+
+        // SET replaces a value of any type
+        assert!(matches_ok(interpret_inline("SET test_string_other_type value").await));
+        assert!(matches_bulk(interpret_inline("GET test_string_other_type").await, "value"));
+    }
+}
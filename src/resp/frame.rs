@@ -1,74 +1,195 @@
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 
-use super::{parser::RESPMessage, token::RESPToken};
+use super::token::{RESPToken, format_double};
 
 #[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum RESPFrame {
     Simple(String),
     Error(String),
     Integer(i64),
     Bulk(Bytes),
     Null,
-    Array(Vec<RESPFrame>)
+    Array(Vec<RESPFrame>),
+    NullArray,
+    // RESP3 only, downgraded for RESP2 connections
+    Nil,
+    Double(f64),
+    Boolean(bool),
+    BigNumber(String),
+    Verbatim(String, Bytes),
+    Map(Vec<(RESPFrame, RESPFrame)>),
+    Set(Vec<RESPFrame>),
+    Attribute(Vec<(RESPFrame, RESPFrame)>, Box<RESPFrame>),
+    Push(Vec<RESPFrame>)
 }
 
-impl From<RESPMessage> for RESPFrame {
-    fn from(message: RESPMessage) -> RESPFrame {
-        assert!(!message.is_empty(), "Empty RESP message found");
+/**
+ * Protocol version negotiated per connection through HELLO
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RESPProtocol {
+    #[default]
+    RESP2,
+    RESP3
+}
 
-        let mut message_iter = message.into_iter();
-        let token = message_iter.next().unwrap();
-        let rest = message_iter;
+impl RESPFrame {
+    /**
+     * Decodes one frame from the token stream, recursing into nested arrays
+     */
+    pub fn from_tokens<I: Iterator<Item = RESPToken>>(tokens: &mut I) -> RESPFrame {
+        let token = tokens.next().expect("Incomplete RESP message found");
 
         match token {
-            // Nested arrays not supported
-            RESPToken::ArraySize(_) => RESPFrame::Array(
-                rest.map(|child| child.into()).collect()
+            RESPToken::ArraySize(size) => RESPFrame::Array(
+                (0..size).map(|_| RESPFrame::from_tokens(tokens)).collect()
+            ),
+            RESPToken::SetSize(size) => RESPFrame::Set(
+                (0..size).map(|_| RESPFrame::from_tokens(tokens)).collect()
+            ),
+            RESPToken::PushSize(size) => RESPFrame::Push(
+                (0..size).map(|_| RESPFrame::from_tokens(tokens)).collect()
+            ),
+            RESPToken::MapSize(size) => RESPFrame::Map(
+                RESPFrame::pairs_from_tokens(tokens, size)
             ),
+            RESPToken::AttributeSize(size) => {
+                let attributes = RESPFrame::pairs_from_tokens(tokens, size);
+                RESPFrame::Attribute(attributes, Box::new(RESPFrame::from_tokens(tokens)))
+            },
             _ => token.into()
         }
     }
-}
 
-impl From<RESPToken> for RESPFrame {
-    fn from(token: RESPToken) -> RESPFrame {
-        match token {
-            RESPToken::SimpleString(s) => RESPFrame::Simple(s),
-            RESPToken::Error(s) => RESPFrame::Error(s),
-            RESPToken::Integer(n) => RESPFrame::Integer(n),
-            RESPToken::BulkString(_, s) => RESPFrame::Bulk(s),
-            RESPToken::Null => RESPFrame::Null,
-            RESPToken::ArraySize(_) => unreachable!(),
+    fn pairs_from_tokens<I: Iterator<Item = RESPToken>>(
+        tokens: &mut I,
+        size: u32
+    ) -> Vec<(RESPFrame, RESPFrame)> {
+        (0..size)
+            .map(|_| (RESPFrame::from_tokens(tokens), RESPFrame::from_tokens(tokens)))
+            .collect()
+    }
+
+    /**
+     * Adapts a reply to the protocol the connection speaks.
+     * RESP2 has no equivalent for RESP3 types, so they fall back to the closest RESP2 type
+     * the same way Redis does (maps flatten into arrays, doubles become bulk strings).
+     */
+    pub fn into_protocol(self, protocol: RESPProtocol) -> RESPFrame {
+        let adapt_all = |frames: Vec<RESPFrame>| -> Vec<RESPFrame> {
+            frames.into_iter().map(|frame| frame.into_protocol(protocol)).collect()
+        };
+        let adapt_pairs = |pairs: Vec<(RESPFrame, RESPFrame)>| -> Vec<(RESPFrame, RESPFrame)> {
+            pairs.into_iter()
+                .map(|(key, value)| (key.into_protocol(protocol), value.into_protocol(protocol)))
+                .collect()
+        };
+
+        match (protocol, self) {
+            (_, RESPFrame::Array(data)) => RESPFrame::Array(adapt_all(data)),
+            (RESPProtocol::RESP3, RESPFrame::Null | RESPFrame::NullArray) => RESPFrame::Nil,
+            (RESPProtocol::RESP3, RESPFrame::Map(pairs)) => RESPFrame::Map(adapt_pairs(pairs)),
+            (RESPProtocol::RESP3, RESPFrame::Set(data)) => RESPFrame::Set(adapt_all(data)),
+            (RESPProtocol::RESP3, RESPFrame::Push(data)) => RESPFrame::Push(adapt_all(data)),
+            (RESPProtocol::RESP3, RESPFrame::Attribute(attributes, frame)) => RESPFrame::Attribute(
+                adapt_pairs(attributes),
+                Box::new(frame.into_protocol(protocol))
+            ),
+            (RESPProtocol::RESP2, RESPFrame::Nil) => RESPFrame::Null,
+            (RESPProtocol::RESP2, RESPFrame::Double(n)) => RESPFrame::Bulk(Bytes::from(format_double(n))),
+            (RESPProtocol::RESP2, RESPFrame::Boolean(b)) => RESPFrame::Integer(b as i64),
+            (RESPProtocol::RESP2, RESPFrame::BigNumber(n)) => RESPFrame::Bulk(Bytes::from(n)),
+            (RESPProtocol::RESP2, RESPFrame::Verbatim(_, s)) => RESPFrame::Bulk(s),
+            (RESPProtocol::RESP2, RESPFrame::Map(pairs)) => RESPFrame::Array(
+                adapt_pairs(pairs).into_iter()
+                    .flat_map(|(key, value)| [key, value])
+                    .collect()
+            ),
+            (RESPProtocol::RESP2, RESPFrame::Set(data) | RESPFrame::Push(data)) => RESPFrame::Array(adapt_all(data)),
+            // Attributes are invisible to RESP2 clients
+            (RESPProtocol::RESP2, RESPFrame::Attribute(_, frame)) => frame.into_protocol(protocol),
+            (_, frame) => frame
         }
     }
-}
 
-impl From<RESPFrame> for RESPMessage {
-    fn from(frame: RESPFrame) -> RESPMessage {
-        match frame {
+    /**
+     * Encodes the frame straight into a write buffer, flattening nested aggregates depth first
+     */
+    pub fn encode(self, buf: &mut BytesMut) {
+        match self {
+            RESPFrame::Simple(s) => RESPToken::SimpleString(s).encode(buf),
+            RESPFrame::Error(s) => RESPToken::Error(s).encode(buf),
+            RESPFrame::Integer(n) => RESPToken::Integer(n).encode(buf),
+            RESPFrame::Bulk(s) => RESPToken::BulkString(s.len() as u32, s).encode(buf),
+            RESPFrame::Null => RESPToken::Null.encode(buf),
             RESPFrame::Array(data) => {
-                let mut message = vec![RESPToken::ArraySize(data.len() as u32)];
+                RESPToken::ArraySize(data.len() as u32).encode(buf);
 
                 for child in data {
-                    message.push(child.into())
+                    child.encode(buf)
                 }
-                message
-            }
-            _ => vec![frame.into()],
+            },
+            RESPFrame::NullArray => RESPToken::NullArray.encode(buf),
+            RESPFrame::Nil => RESPToken::Nil.encode(buf),
+            RESPFrame::Double(n) => RESPToken::Double(n).encode(buf),
+            RESPFrame::Boolean(b) => RESPToken::Boolean(b).encode(buf),
+            RESPFrame::BigNumber(n) => RESPToken::BigNumber(n).encode(buf),
+            RESPFrame::Verbatim(format, s) => RESPToken::VerbatimString(format, s).encode(buf),
+            RESPFrame::Map(pairs) => {
+                RESPToken::MapSize(pairs.len() as u32).encode(buf);
+                RESPFrame::encode_pairs(pairs, buf)
+            },
+            RESPFrame::Set(data) => {
+                RESPToken::SetSize(data.len() as u32).encode(buf);
+
+                for child in data {
+                    child.encode(buf)
+                }
+            },
+            RESPFrame::Attribute(attributes, frame) => {
+                RESPToken::AttributeSize(attributes.len() as u32).encode(buf);
+                RESPFrame::encode_pairs(attributes, buf);
+                frame.encode(buf)
+            },
+            RESPFrame::Push(data) => {
+                RESPToken::PushSize(data.len() as u32).encode(buf);
+
+                for child in data {
+                    child.encode(buf)
+                }
+            },
+        }
+    }
+
+    fn encode_pairs(pairs: Vec<(RESPFrame, RESPFrame)>, buf: &mut BytesMut) {
+        for (key, value) in pairs {
+            key.encode(buf);
+            value.encode(buf)
         }
     }
 }
 
-impl From<RESPFrame> for RESPToken {
-    fn from(frame: RESPFrame) -> RESPToken {
-        match frame {
-            RESPFrame::Simple(s) => RESPToken::SimpleString(s),
-            RESPFrame::Error(s) => RESPToken::Error(s),
-            RESPFrame::Integer(n) => RESPToken::Integer(n),
-            RESPFrame::Bulk(s) => RESPToken::BulkString(s.len() as u32, s),
-            RESPFrame::Null => RESPToken::Null,
-            RESPFrame::Array(_) => unimplemented!("RESP Frame array doesn't map to a token"),
+impl From<RESPToken> for RESPFrame {
+    fn from(token: RESPToken) -> RESPFrame {
+        match token {
+            RESPToken::SimpleString(s) => RESPFrame::Simple(s),
+            RESPToken::Error(s) => RESPFrame::Error(s),
+            RESPToken::Integer(n) => RESPFrame::Integer(n),
+            RESPToken::BulkString(_, s) => RESPFrame::Bulk(s),
+            RESPToken::Null => RESPFrame::Null,
+            RESPToken::NullArray => RESPFrame::NullArray,
+            RESPToken::Nil => RESPFrame::Nil,
+            RESPToken::Double(n) => RESPFrame::Double(n),
+            RESPToken::Boolean(b) => RESPFrame::Boolean(b),
+            RESPToken::BigNumber(n) => RESPFrame::BigNumber(n),
+            RESPToken::VerbatimString(format, s) => RESPFrame::Verbatim(format, s),
+            // Aggregate sizes only make sense alongside their elements
+            RESPToken::ArraySize(_)
+                | RESPToken::MapSize(_)
+                | RESPToken::SetSize(_)
+                | RESPToken::AttributeSize(_)
+                | RESPToken::PushSize(_) => unreachable!(),
         }
     }
 }
@@ -76,45 +197,39 @@ impl From<RESPFrame> for RESPToken {
 
 #[cfg(test)]
 mod tests {
-    use bytes::Bytes;
+    use bytes::{Bytes, BytesMut};
 
-    use crate::resp::{token::RESPToken, parser::RESPMessage};
+    use crate::resp::token::RESPToken;
 
-    use super::RESPFrame;
+    use super::{RESPFrame, RESPProtocol};
     use rstest::rstest;
 
     #[test]
     fn should_convert_frame_to_and_from_simple_string(){
         let message = vec![RESPToken::SimpleString("PING".to_owned())];
 
-        let simple_frame = RESPFrame::from(message);
+        let expected_bytes = encode_tokens(&message);
+        let simple_frame = frame_from(message);
         assert!(matches!(
             &simple_frame,
             RESPFrame::Simple(s) if s == "PING"
         ));
 
-        let back_to_message = RESPMessage::from(simple_frame);
-        assert!(matches!(
-            back_to_message.as_slice(),
-            [RESPToken::SimpleString(s)] if s == "PING"
-        ));
+        assert_eq!(expected_bytes, encode_frame(simple_frame));
     }
 
     #[test]
     fn should_convert_frame_to_and_from_error_string(){
         let message = vec![RESPToken::Error("ERR".to_owned())];
         
-        let error_frame = RESPFrame::from(message);
+        let expected_bytes = encode_tokens(&message);
+        let error_frame = frame_from(message);
         assert!(matches!(
             &error_frame,
             RESPFrame::Error(s) if s == "ERR"
         ));
 
-        let back_to_message = RESPMessage::from(error_frame);
-        assert!(matches!(
-            back_to_message.as_slice(),
-            [RESPToken::Error(s)] if s == "ERR"
-        ));
+        assert_eq!(expected_bytes, encode_frame(error_frame));
     }
 
     #[rstest]
@@ -125,51 +240,42 @@ mod tests {
     fn should_convert_frame_to_and_from_integer(#[case] int: i64){
         let message = vec![RESPToken::Integer(int)];
         
-        let int_frame = RESPFrame::from(message);
+        let expected_bytes = encode_tokens(&message);
+        let int_frame = frame_from(message);
         assert!(matches!(
             &int_frame,
             RESPFrame::Integer(int_match) if *int_match == int
         ));
 
-        let back_to_message = RESPMessage::from(int_frame);
-        assert!(matches!(
-            back_to_message.as_slice(),
-            [RESPToken::Integer(int_match)] if *int_match == int
-        ));
+        assert_eq!(expected_bytes, encode_frame(int_frame));
     }
 
     #[test]
     fn should_convert_frame_to_and_from_null(){
         let message = vec![RESPToken::Null];
         
-        let null_frame = RESPFrame::from(message);
+        let expected_bytes = encode_tokens(&message);
+        let null_frame = frame_from(message);
         assert!(matches!(
             null_frame,
             RESPFrame::Null
         ));
 
-        let back_to_message = RESPMessage::from(null_frame);
-        assert!(matches!(
-            back_to_message.as_slice(),
-            [RESPToken::Null]
-        ));
+        assert_eq!(expected_bytes, encode_frame(null_frame));
     }
 
     #[test]
     fn should_convert_frame_to_and_from_bulk_string(){
         let message = vec![RESPToken::BulkString(4, Bytes::from("BULK"))];
         
-        let bulk_frame = RESPFrame::from(message);
+        let expected_bytes = encode_tokens(&message);
+        let bulk_frame = frame_from(message);
         assert!(matches!(
             &bulk_frame,
             RESPFrame::Bulk(s) if s == "BULK"
         ));
 
-        let back_to_message = RESPMessage::from(bulk_frame);
-        assert!(matches!(
-            back_to_message.as_slice(),
-            [RESPToken::BulkString(4, s)] if s == "BULK"
-        ));
+        assert_eq!(expected_bytes, encode_frame(bulk_frame));
     }
 
     #[test]
@@ -183,7 +289,8 @@ mod tests {
             RESPToken::Null
         ];
         
-        let bulk_frame = RESPFrame::from(message);
+        let expected_bytes = encode_tokens(&message);
+        let bulk_frame = frame_from(message);
         assert!(matches!(
             &bulk_frame,
             RESPFrame::Array(array) if matches!(
@@ -198,26 +305,205 @@ mod tests {
             )
         ));
 
-        let back_to_message = RESPMessage::from(bulk_frame);
+        assert_eq!(expected_bytes, encode_frame(bulk_frame));
+    }
+
+    #[test]
+    fn should_convert_frame_to_and_from_nested_array(){
+        let message = vec![
+            RESPToken::ArraySize(3),
+            RESPToken::ArraySize(2),
+            RESPToken::Integer(1),
+            RESPToken::ArraySize(1),
+            RESPToken::BulkString(4, Bytes::from("DEEP")),
+            RESPToken::ArraySize(0),
+            RESPToken::NullArray
+        ];
+
+        let expected_bytes = encode_tokens(&message);
+        let nested_frame = frame_from(message);
+        assert!(matches!(
+            &nested_frame,
+            RESPFrame::Array(array) if matches!(
+                array.as_slice(),
+                [
+                    RESPFrame::Array(first),
+                    RESPFrame::Array(empty),
+                    RESPFrame::NullArray,
+                ] if empty.is_empty() && matches!(
+                    first.as_slice(),
+                    [
+                        RESPFrame::Integer(1),
+                        RESPFrame::Array(deep)
+                    ] if matches!(deep.as_slice(), [RESPFrame::Bulk(s)] if s == "DEEP")
+                )
+            )
+        ));
+
+        assert_eq!(expected_bytes, encode_frame(nested_frame));
+    }
+
+    #[test]
+    fn should_convert_frame_to_and_from_null_array(){
+        let message = vec![RESPToken::NullArray];
+
+        let expected_bytes = encode_tokens(&message);
+        let null_array_frame = frame_from(message);
+        assert!(matches!(
+            null_array_frame,
+            RESPFrame::NullArray
+        ));
+
+        assert_eq!(expected_bytes, encode_frame(null_array_frame));
+    }
+
+    #[test]
+    fn should_convert_frame_to_and_from_resp3_scalars(){
+        let message = vec![
+            RESPToken::ArraySize(5),
+            RESPToken::Nil,
+            RESPToken::Double(2.5),
+            RESPToken::Boolean(true),
+            RESPToken::BigNumber("123456789012345678901234567890".to_owned()),
+            RESPToken::VerbatimString("txt".to_owned(), Bytes::from("hi")),
+        ];
+
+        let expected_bytes = encode_tokens(&message);
+        let scalar_frame = frame_from(message);
+        assert!(matches!(
+            &scalar_frame,
+            RESPFrame::Array(array) if matches!(
+                array.as_slice(),
+                [
+                    RESPFrame::Nil,
+                    RESPFrame::Double(double),
+                    RESPFrame::Boolean(true),
+                    RESPFrame::BigNumber(_),
+                    RESPFrame::Verbatim(format, s),
+                ] if *double == 2.5 && format == "txt" && s == "hi"
+            )
+        ));
+
+        assert_eq!(expected_bytes, encode_frame(scalar_frame));
+    }
+
+    #[test]
+    fn should_convert_frame_to_and_from_resp3_aggregates(){
+        let message = vec![
+            RESPToken::AttributeSize(1),
+            RESPToken::SimpleString("ttl".to_owned()),
+            RESPToken::Integer(3),
+            RESPToken::MapSize(2),
+            RESPToken::SimpleString("set".to_owned()),
+            RESPToken::SetSize(1),
+            RESPToken::Integer(1),
+            RESPToken::SimpleString("push".to_owned()),
+            RESPToken::PushSize(0),
+        ];
+
+        let expected_bytes = encode_tokens(&message);
+        let attribute_frame = frame_from(message);
         assert!(matches!(
-            back_to_message.as_slice(),
-            [
-                RESPToken::ArraySize(5),
-                RESPToken::SimpleString(simple),
-                RESPToken::Error(error),
-                RESPToken::Integer(42),
-                RESPToken::BulkString(4, bulk),
-                RESPToken::Null
-            ] if simple == "PING"
-                && error == "ERR bad"
-                && bulk == "BULK"
+            &attribute_frame,
+            RESPFrame::Attribute(attributes, map) if attributes.len() == 1 && matches!(
+                map.as_ref(),
+                RESPFrame::Map(pairs) if matches!(
+                    pairs.as_slice(),
+                    [
+                        (RESPFrame::Simple(_), RESPFrame::Set(set)),
+                        (RESPFrame::Simple(_), RESPFrame::Push(push)),
+                    ] if set.len() == 1 && push.is_empty()
+                )
+            )
         ));
+
+        assert_eq!(expected_bytes, encode_frame(attribute_frame));
+    }
+
+    #[test]
+    fn should_downgrade_resp3_frames_for_resp2() {
+        let frame = RESPFrame::Attribute(
+            vec![(RESPFrame::Simple("hint".to_owned()), RESPFrame::Integer(1))],
+            Box::new(RESPFrame::Array(vec![
+                RESPFrame::Map(vec![(RESPFrame::Simple("key".to_owned()), RESPFrame::Double(1.5))]),
+                RESPFrame::Set(vec![RESPFrame::Boolean(true), RESPFrame::Boolean(false)]),
+                RESPFrame::Nil,
+                RESPFrame::BigNumber("99".to_owned()),
+                RESPFrame::Verbatim("txt".to_owned(), Bytes::from("text")),
+            ]))
+        );
+
+        assert!(matches!(
+            frame.into_protocol(RESPProtocol::RESP2),
+            RESPFrame::Array(array) if matches!(
+                array.as_slice(),
+                [
+                    RESPFrame::Array(map),
+                    RESPFrame::Array(set),
+                    RESPFrame::Null,
+                    RESPFrame::Bulk(big),
+                    RESPFrame::Bulk(verbatim),
+                ] if big == "99" && verbatim == "text" && matches!(
+                    map.as_slice(),
+                    [RESPFrame::Simple(key), RESPFrame::Bulk(double)] if key == "key" && double == "1.5"
+                ) && matches!(
+                    set.as_slice(),
+                    [RESPFrame::Integer(1), RESPFrame::Integer(0)]
+                )
+            )
+        ));
+    }
+
+    #[test]
+    fn should_upgrade_nulls_for_resp3() {
+        let frame = RESPFrame::Array(vec![
+            RESPFrame::Null,
+            RESPFrame::NullArray,
+            RESPFrame::Map(vec![(RESPFrame::Simple("key".to_owned()), RESPFrame::Null)]),
+        ]);
+
+        assert!(matches!(
+            frame.into_protocol(RESPProtocol::RESP3),
+            RESPFrame::Array(array) if matches!(
+                array.as_slice(),
+                [
+                    RESPFrame::Nil,
+                    RESPFrame::Nil,
+                    RESPFrame::Map(pairs),
+                ] if matches!(pairs.as_slice(), [(RESPFrame::Simple(_), RESPFrame::Nil)])
+            )
+        ));
+    }
+
+    #[test]
+    #[should_panic]
+    fn bad_incomplete_nested_message() {
+        let _invalid_frame = frame_from(vec![
+            RESPToken::ArraySize(2),
+            RESPToken::ArraySize(2),
+            RESPToken::Integer(1),
+        ]);
     }
 
     #[test]
     #[should_panic]
     fn bad_empty_message() {
-        let _invalid_frame = RESPFrame::from(Vec::<RESPToken>::from([]));
+        let _invalid_frame = frame_from(vec![]);
+    }
+
+    fn frame_from(message: Vec<RESPToken>) -> RESPFrame {
+        RESPFrame::from_tokens(&mut message.into_iter())
     }
 
+    fn encode_tokens(message: &[RESPToken]) -> BytesMut {
+        let mut buf = BytesMut::new();
+        message.iter().for_each(|token| token.encode(&mut buf));
+        buf
+    }
+
+    fn encode_frame(frame: RESPFrame) -> BytesMut {
+        let mut buf = BytesMut::new();
+        frame.encode(&mut buf);
+        buf
+    }
 }
@@ -1,140 +1,161 @@
-use std::str::from_utf8;
+use std::time::Duration;
 
-use bytes::{Bytes, Buf};
+use bytes::Bytes;
 
 use super::{
-    frame::RESPFrame, 
-    command::{RedisCommand, SetCommandFlags, SetCommandExistFlag, SetCommandTTLFlag},
-    super::store::RedisStore
+    frame::{RESPFrame, RESPProtocol},
+    command::{RedisCommand, CommandContext, CommandFlag, CommandResult, BlockingRequest},
+    super::{store::{RedisStore, BlockedClient}, error::RedisError}
 };
 
+pub const REDIS_VERSION: &str = "7.0.0";
+
+// How often blocked clients check their deadline against the clock
+const BLOCKED_TIMEOUT_RESOLUTION: Duration = Duration::from_millis(10);
+
+/**
+ * State kept for the lifetime of a client connection
+ */
+#[derive(Debug, Default)]
+pub struct ConnectionState {
+    pub id: u64,
+    pub name: Option<Bytes>,
+    pub protocol: RESPProtocol,
+}
+
 /**
  * Interprets RESP frames and talks to redis store interface
  */
 pub struct RESPInterpreter;
 
 impl RESPInterpreter {
-    pub async fn interpret(frame: &RESPFrame) -> RESPFrame {
-        // Take PING return PONG (also hardcoded for any unimplemented requests)
-        let pong_response = RESPFrame::Simple("PONG".to_owned());
-        
-        match frame {
-            RESPFrame::Array(elements) => {
-                match elements.as_slice() {
-                    [RESPFrame::Bulk(command), args @ ..] => {
-                        match command.into() {
-                            RedisCommand::PING => pong_response,
-                            RedisCommand::ECHO => {
-                                if let [RESPFrame::Bulk(message)] = args {
-                                    RESPFrame::Bulk(message.to_owned())
-                                } else {
-                                    pong_response
-                                }
-                            },
-                            RedisCommand::GET => {
-                                if let [RESPFrame::Bulk(key)] = args {
-                                    let shared_store = RedisStore::get_shared_store();
-                                    let mut store = shared_store.lock().await;
-
-                                    if let Some(store_value) = store.get(&bytes_to_string(key)) {
-                                        RESPFrame::Bulk(Bytes::from(store_value))
-                                    } else {
-                                        RESPFrame::Null
-                                    }
-                                } else {
-                                    RESPFrame::Null
-                                }
-                            },
-                            RedisCommand::SET => {
-                                if let [RESPFrame::Bulk(key), RESPFrame::Bulk(value), options @ ..] = args {
-                                    let set_flags = RESPInterpreter::calculate_set_flags(options);
-
-                                    let shared_store = RedisStore::get_shared_store();
-                                    let mut store = shared_store.lock().await;
-                                    
-                                    let prev_value = if set_flags.get_flag {
-                                        store.get(&bytes_to_string(key))
-                                    } else { None };
-
-                                    let update_success = store.set(&bytes_to_string(key), &bytes_to_string(value), &set_flags);
-
-                                    if set_flags.get_flag {
-                                        match prev_value {
-                                            Some(value) => RESPFrame::Bulk(Bytes::from(value)),
-                                            None => RESPFrame::Null,
-                                        }
-                                    } else {
-                                        if update_success { 
-                                            RESPFrame::Simple("OK".to_owned())
-                                        } else {
-                                            RESPFrame::Null
-                                        }
-                                    }
-                                } else {
-                                    RESPFrame::Null
-                                }
-                            },
-                            _ => pong_response
-                        }
-                    },
-                    _ => pong_response
-                }
-            }
-            _ => pong_response
-        }
+    /**
+     * Command errors are replied to the client as RESP errors
+     */
+    pub async fn interpret(connection: &mut ConnectionState, frame: &RESPFrame) -> RESPFrame {
+        RESPInterpreter::execute(connection, frame).await
+            .unwrap_or_else(|err| RESPFrame::Error(err.to_string()))
     }
 
-    fn calculate_set_flags(options: &[RESPFrame]) -> SetCommandFlags {
-        let mut set_flags = SetCommandFlags::default();
-        let mut options_2: &[RESPFrame] = &[];
-        let mut options_3: &[RESPFrame] = &[];
+    async fn execute(connection: &mut ConnectionState, frame: &RESPFrame) -> CommandResult {
+        let request = RESPInterpreter::request_args(frame)?;
+        let (name, args) = request.split_first()
+            .ok_or_else(|| RedisError::Protocol("empty command".to_owned()))?;
+
+        let command = RedisCommand::lookup(name)
+            .ok_or_else(|| RedisError::unknown_command(name, args))?;
+
+        // Container commands run their subcommand, if one is given
+        let (command, args) = match args {
+            [subcommand, subcommand_args @ ..] if !command.subcommands.is_empty() => (
+                command.lookup_subcommand(subcommand)
+                    .ok_or_else(|| command.unknown_subcommand(subcommand))?,
+                subcommand_args
+            ),
+            _ => (command, args)
+        };
+
+        if !command.accepts_arity(request.len()) {
+            return Err(command.wrong_arity())
+        }
 
-        if let [RESPFrame::Bulk(exist_option), other_options @ ..] = options {
-            options_2 = other_options;
+        let shared_store = RedisStore::get_shared_store();
+        let mut store = shared_store.lock().await;
 
-            match exist_option.to_ascii_uppercase().as_slice() {
-                b"NX" => set_flags.exist_flag = Some(SetCommandExistFlag::NX),
-                b"XX" => set_flags.exist_flag = Some(SetCommandExistFlag::XX),
-                _ => options_2 = options,
-            }
-        }
+        let mut context = CommandContext { command, connection, store: &mut store, blocking: None };
+        let result = (command.handler)(&mut context, args);
+        let blocking = context.blocking.take();
 
-        if let [RESPFrame::Bulk(get_option), other_options @ ..] = options_2 {
-            options_3 = other_options;
+        // Clients blocked on keys this command pushed to are served before anyone else
+        store.serve_blocked();
 
-            match get_option.to_ascii_uppercase().as_slice() {
-                b"GET" => set_flags.get_flag = true,
-                _ => options_3 = options_2,
-            }
+        match blocking {
+            Some(request) => {
+                let client = store.block(request.keys.clone(), request.attempt.clone());
+                drop(store);
+
+                RESPInterpreter::wait_blocked(client, request).await
+            },
+            None => result
         }
+    }
 
-        if let [RESPFrame::Bulk(ttl_type), RESPFrame::Bulk(ttl_bytes)] = options_3 {
-            if let Some(ttl)= from_utf8(ttl_bytes.chunk()).unwrap()
-                .to_owned()
-                .parse::<u64>()
-                .ok() {
-                    match ttl_type.to_ascii_uppercase().as_slice() {
-                        b"EX" => set_flags.ttl_flag = Some(SetCommandTTLFlag::EX(ttl)),
-                        b"PX" => set_flags.ttl_flag = Some(SetCommandTTLFlag::PX(ttl)),
-                        b"EXAT" => set_flags.ttl_flag = Some(SetCommandTTLFlag::EXAT(ttl)),
-                        b"PXAT" => set_flags.ttl_flag = Some(SetCommandTTLFlag::PXAT(ttl)),
-                        _ => {}
+    /**
+     * Waits for a blocked client to be served, without holding the store lock
+     * The client is unblocked if the wait is abandoned, such as when it disconnects
+     */
+    async fn wait_blocked(mut client: BlockedClient, request: BlockingRequest) -> CommandResult {
+        let guard = UnblockOnDrop(Some(client.id));
+
+        loop {
+            tokio::select! {
+                result = &mut client.reply => {
+                    guard.disarm();
+                    return result.unwrap_or(Ok(request.timeout_reply))
+                },
+                _ = tokio::time::sleep(BLOCKED_TIMEOUT_RESOLUTION) => {
+                    if request.deadline.is_none_or(|deadline| RedisStore::get_unix_time() < deadline) {
+                        continue
                     }
+
+                    guard.disarm();
+                    if RedisStore::get_shared_store().lock().await.unblock(client.id) {
+                        return Ok(request.timeout_reply)
+                    }
+
+                    // Served just as it timed out
+                    return client.reply.await.unwrap_or(Ok(request.timeout_reply))
                 }
+            }
         }
+    }
 
-        if let [RESPFrame::Bulk(keepttl_option)] = options_3 {
-            if keepttl_option.eq_ignore_ascii_case(b"KEEPTTL") {
-                set_flags.ttl_flag = Some(SetCommandTTLFlag::KEEPTTL)
-            }
+    /**
+     * Whether the request runs a command that may block the client
+     */
+    pub fn may_block(frame: &RESPFrame) -> bool {
+        let Ok(request) = RESPInterpreter::request_args(frame) else { return false };
+
+        request.first()
+            .and_then(|name| RedisCommand::lookup(name))
+            .is_some_and(|command| command.flags.contains(&CommandFlag::Blocking))
+    }
+
+    /**
+     * Requests are arrays of strings, where the first string is the command name
+     */
+    fn request_args(frame: &RESPFrame) -> Result<Vec<Bytes>, RedisError> {
+        let expected_array = || RedisError::Protocol("expected an array of bulk strings".to_owned());
+
+        match frame {
+            RESPFrame::Array(elements) => elements.iter()
+                .map(|element| match element {
+                    RESPFrame::Bulk(arg) => Ok(arg.clone()),
+                    RESPFrame::Simple(arg) => Ok(Bytes::from(arg.to_owned())),
+                    _ => Err(expected_array())
+                })
+                .collect(),
+            _ => Err(expected_array())
         }
+    }
+}
 
-        set_flags
+struct UnblockOnDrop(Option<u64>);
+
+impl UnblockOnDrop {
+    fn disarm(mut self) {
+        self.0 = None;
     }
 }
 
-fn bytes_to_string(bytes: &Bytes) -> String {
-    from_utf8(bytes).unwrap().to_owned()
+impl Drop for UnblockOnDrop {
+    fn drop(&mut self) {
+        if let Some(id) = self.0 {
+            tokio::spawn(async move {
+                RedisStore::get_shared_store().lock().await.unblock(id);
+            });
+        }
+    }
 }
 
 
@@ -142,30 +163,73 @@ fn bytes_to_string(bytes: &Bytes) -> String {
 mod tests {
     use std::time::Duration;
 
-    use crate::clock::{MockClockSession, Clock};
+    use crate::{clock::{MockClockSession, Clock}, resp::command::test_utils::{matches_bulk, matches_error, matches_ok}};
 
     use super::*;
     use rstest::rstest;
 
+    #[rstest]
+    #[case(RESPFrame::Simple("Hi".to_owned()))]
+    #[case(RESPFrame::Error("Err".to_owned()))]
+    #[case(RESPFrame::Integer(-23))]
+    #[case(RESPFrame::Bulk(Bytes::from("Hello world!")))]
+    #[case(RESPFrame::Null)]
+    #[case(RESPFrame::Array(vec![RESPFrame::Bulk(Bytes::from("GET")), RESPFrame::Integer(1)]))]
     #[tokio::test]
-    async fn should_interpret_non_array_frames() {
-        assert!(matches_pong(RESPInterpreter::interpret(&RESPFrame::Simple("Hi".to_owned())).await));
-        assert!(matches_pong(RESPInterpreter::interpret(&RESPFrame::Error("Err".to_owned())).await));
-        assert!(matches_pong(RESPInterpreter::interpret(&RESPFrame::Integer(-23)).await));
-        assert!(matches_pong(RESPInterpreter::interpret(
-            &RESPFrame::Bulk(Bytes::from("Hello world!"))
-        ).await));
-        assert!(matches_pong(RESPInterpreter::interpret(&RESPFrame::Null).await));
+    async fn should_interpret_non_array_frames(#[case] frame: RESPFrame) {
+        let response = RESPInterpreter::interpret(&mut ConnectionState::default(), &frame).await;
+
+        assert!(matches_error(response, "ERR Protocol error: expected an array of bulk strings"));
     }
 
     #[tokio::test]
     async fn should_interpret_empty_array() {
-        assert!(matches_pong(RESPInterpreter::interpret(&RESPFrame::Array(vec![])).await));
+        let response = RESPInterpreter::interpret(&mut ConnectionState::default(), &RESPFrame::Array(vec![])).await;
+
+        assert!(matches_error(response, "ERR Protocol error: empty command"));
+    }
+
+    #[rstest]
+    #[case("FOO", "ERR unknown command 'FOO', with args beginning with: ")]
+    #[case("foo bar baz", "ERR unknown command 'foo', with args beginning with: 'bar' 'baz' ")]
+    #[tokio::test]
+    async fn should_interpret_unknown_command(#[case] request: &str, #[case] expected_error: &str) {
+        assert!(matches_error(interpret_request(request).await, expected_error));
+    }
+
+    #[rstest]
+    #[case("PING a b", "ping")]
+    #[case("ECHO", "echo")]
+    #[case("echo a b", "echo")]
+    #[case("GET", "get")]
+    #[case("GET a b", "get")]
+    #[case("SET", "set")]
+    #[case("set key", "set")]
+    #[tokio::test]
+    async fn should_interpret_wrong_arity(#[case] request: &str, #[case] command: &str) {
+        let expected_error = format!("ERR wrong number of arguments for '{}' command", command);
+
+        assert!(matches_error(interpret_request(request).await, &expected_error));
+    }
+
+    #[rstest]
+    #[case("SET test_bad_ttl_key value EX ten", "ERR value is not an integer or out of range")]
+    #[case("SET test_bad_ttl_key value PX -5", "ERR invalid expire time in 'set' command")]
+    #[case("SET test_bad_ttl_key value TTL 10", "ERR syntax error")]
+    #[tokio::test]
+    async fn should_interpret_bad_set_options(#[case] request: &str, #[case] expected_error: &str) {
+        assert!(matches_error(interpret_request(request).await, expected_error));
+        assert!(matches_null(interpret_get("test_bad_ttl_key").await));
+    }
+
+    #[tokio::test]
+    async fn should_interpret_ping_with_message() {
+        assert!(matches_bulk(interpret_request("PING hello").await, "hello"));
     }
 
     #[tokio::test]
     async fn should_interpret_ping_command() {
-        assert!(matches_pong(RESPInterpreter::interpret(&RESPFrame::Array(vec![
+        assert!(matches_pong(RESPInterpreter::interpret(&mut ConnectionState::default(), &RESPFrame::Array(vec![
             RESPFrame::Simple("PING".to_owned())
         ])).await));
     }
@@ -176,19 +240,19 @@ mod tests {
     #[case("two words")]    
     #[tokio::test]
     async fn should_interpret_echo_command(#[case] message: &str) {
-        let response = RESPInterpreter::interpret(&RESPFrame::Array(vec![
+        let response = RESPInterpreter::interpret(&mut ConnectionState::default(), &RESPFrame::Array(vec![
             RESPFrame::Bulk(Bytes::from("ECHO")),
             RESPFrame::Bulk(Bytes::from(message.to_owned()))
         ])).await;
 
         assert!(matches!(response, RESPFrame::Bulk(s) if s == message));
 
-        let lower_case_echo_response = RESPInterpreter::interpret(&RESPFrame::Array(vec![
+        let lower_case_echo_response = RESPInterpreter::interpret(&mut ConnectionState::default(), &RESPFrame::Array(vec![
             RESPFrame::Bulk(Bytes::from("echo")),
             RESPFrame::Bulk(Bytes::from(message.to_owned()))
         ])).await;
 
-        assert!(matches_bulk(lower_case_echo_response, &message));
+        assert!(matches_bulk(lower_case_echo_response, message));
     }
 
     #[tokio::test]
@@ -204,10 +268,32 @@ mod tests {
         assert!(matches_bulk(interpret_get("test_setting_key").await, "hi"));
     }
 
+    #[rstest]
+    #[case(b"line\r\nbreak")]
+    #[case(b"  padded  ")]
+    #[case(b"\x89PNG\r\n\x1a\n\x00\xff")]
+    #[tokio::test]
+    async fn should_interpret_set_get_binary(#[case] value: &'static [u8]) {
+        let key = Bytes::from_static(b"test_binary_\xffkey");
+
+        let set_response = RESPInterpreter::interpret(&mut ConnectionState::default(), &RESPFrame::Array(vec![
+            RESPFrame::Bulk(Bytes::from("SET")),
+            RESPFrame::Bulk(key.clone()),
+            RESPFrame::Bulk(Bytes::from_static(value))
+        ])).await;
+        assert!(matches_ok(set_response));
+
+        let get_response = RESPInterpreter::interpret(&mut ConnectionState::default(), &RESPFrame::Array(vec![
+            RESPFrame::Bulk(Bytes::from("GET")),
+            RESPFrame::Bulk(key)
+        ])).await;
+        assert!(matches!(get_response, RESPFrame::Bulk(s) if s == value));
+    }
+
     // TODO: Simulate clock time instead of sleep
     #[tokio::test]
     async fn should_interpret_set_expiry() {
-        MockClockSession::new();
+        let _session = MockClockSession::new();
         Clock::mock_freeze();
 
         assert!(matches_null(interpret_get("test_expiry_key").await));
@@ -225,16 +311,69 @@ mod tests {
         assert!(matches_null(interpret_get("test_expiry_key").await));        
     }
 
-    fn matches_pong(response: RESPFrame) -> bool {
-        matches!(response, RESPFrame::Simple(s) if s == "PONG")
+    #[tokio::test]
+    async fn should_interpret_hello_without_version() {
+        let mut connection = ConnectionState::default();
+        let response = interpret_hello(&mut connection, "").await;
+
+        assert_eq!(RESPProtocol::RESP2, connection.protocol);
+        assert!(matches!(
+            response,
+            RESPFrame::Map(pairs) if pairs.iter().any(|pair| matches!(
+                pair,
+                (RESPFrame::Bulk(key), RESPFrame::Integer(2)) if key == "proto"
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_interpret_hello_switching_protocol() {
+        let mut connection = ConnectionState::default();
+
+        interpret_hello(&mut connection, "3").await;
+        assert_eq!(RESPProtocol::RESP3, connection.protocol);
+
+        // Protocol is kept when HELLO is sent without a version
+        let response = interpret_hello(&mut connection, "").await;
+        assert_eq!(RESPProtocol::RESP3, connection.protocol);
+        assert!(matches!(
+            response,
+            RESPFrame::Map(pairs) if pairs.iter().any(|pair| matches!(
+                pair,
+                (RESPFrame::Bulk(key), RESPFrame::Integer(3)) if key == "proto"
+            ))
+        ));
+
+        interpret_hello(&mut connection, "2").await;
+        assert_eq!(RESPProtocol::RESP2, connection.protocol);
+    }
+
+    #[tokio::test]
+    async fn should_interpret_hello_options() {
+        let mut connection = ConnectionState::default();
+
+        interpret_hello(&mut connection, "3 AUTH default secret SETNAME worker").await;
+        assert_eq!(RESPProtocol::RESP3, connection.protocol);
+        assert!(matches!(&connection.name, Some(name) if name == "worker"));
     }
 
-    fn matches_ok(response: RESPFrame) -> bool {
-        matches!(response, RESPFrame::Simple(s) if s == "OK")
+    #[rstest]
+    #[case("4", "NOPROTO")]
+    #[case("three", "NOPROTO")]
+    #[case("3 AUTH someone secret", "WRONGPASS")]
+    #[case("3 SETNAME", "ERR")]
+    #[case("3 UNKNOWN", "ERR")]
+    #[tokio::test]
+    async fn should_interpret_bad_hello(#[case] options: &str, #[case] error_prefix: &str) {
+        let mut connection = ConnectionState::default();
+        let response = interpret_hello(&mut connection, options).await;
+
+        assert!(matches!(response, RESPFrame::Error(s) if s.starts_with(error_prefix)));
+        assert_eq!(RESPProtocol::RESP2, connection.protocol);
     }
 
-    fn matches_bulk(response: RESPFrame, message: &str) -> bool {
-        matches!(response, RESPFrame::Bulk(s) if s == message)
+    fn matches_pong(response: RESPFrame) -> bool {
+        matches!(response, RESPFrame::Simple(s) if s == "PONG")
     }
 
     fn matches_null(response: RESPFrame) -> bool {
@@ -242,12 +381,28 @@ mod tests {
     }
 
     async fn interpret_get(key: &str) -> RESPFrame {
-        RESPInterpreter::interpret(&RESPFrame::Array(vec![
+        RESPInterpreter::interpret(&mut ConnectionState::default(), &RESPFrame::Array(vec![
             RESPFrame::Bulk(Bytes::from("GET")),
             RESPFrame::Bulk(Bytes::from(key.to_owned()))
         ])).await
     }
 
+    async fn interpret_request(request: &str) -> RESPFrame {
+        let request_array = request.split_whitespace()
+            .map(|arg| RESPFrame::Bulk(Bytes::from(arg.to_owned())))
+            .collect::<Vec<RESPFrame>>();
+
+        RESPInterpreter::interpret(&mut ConnectionState::default(), &RESPFrame::Array(request_array)).await
+    }
+
+    async fn interpret_hello(connection: &mut ConnectionState, options: &str) -> RESPFrame {
+        let mut hello_array = vec![RESPFrame::Bulk(Bytes::from("HELLO"))];
+        hello_array.extend(options.split_whitespace()
+            .map(|option| RESPFrame::Bulk(Bytes::from(option.to_owned()))));
+
+        RESPInterpreter::interpret(connection, &RESPFrame::Array(hello_array)).await
+    }
+
     async fn interpret_set(options: &str) -> RESPFrame {
         //let options = options.to_owned();
         let mut set_array = vec![RESPFrame::Bulk(Bytes::from("SET"))];
@@ -256,6 +411,6 @@ mod tests {
             .collect::<Vec<RESPFrame>>();
         set_array.append(&mut options_array);
 
-        RESPInterpreter::interpret(&RESPFrame::Array(set_array)).await
+        RESPInterpreter::interpret(&mut ConnectionState::default(), &RESPFrame::Array(set_array)).await
     }
 }
@@ -1,4 +1,4 @@
-/**
+/*!
  * RESP - Redis Serialisation Protocol
  * https://redis.io/docs/reference/protocol-spec/
  */
@@ -1,259 +1,783 @@
-use std::{io, num};
-use tokio::io::AsyncBufReadExt;
-use super::token::RESPToken;
-use bytes::Bytes;
+use std::{num, ops::Range, str::from_utf8};
+use thiserror::Error;
+use super::{frame::RESPFrame, token::RESPToken};
+use bytes::{Bytes, BytesMut};
 
-pub type RESPMessage = Vec<RESPToken>;
+// Same limit as Redis' default proto-max-bulk-len
+pub const MAX_BULK_SIZE: u32 = 512 * 1024 * 1024;
+// Same limit as Redis' PROTO_INLINE_MAX_SIZE
+const MAX_INLINE_SIZE: usize = 64 * 1024;
+// Frames are decoded and encoded recursively, so their depth is bounded
+const MAX_NESTING_DEPTH: usize = 128;
 
-#[derive(Debug)]
+const RESP_PREFIXES: &[u8] = b"+-:$*_,#(=%|~>";
+
+#[derive(Debug, Error)]
 pub enum RESPParserError {
-    BadIntParse(num::ParseIntError),
-    BadRead(io::Error),
+    #[error("bad integer: {0}")]
+    BadIntParse(#[from] num::ParseIntError),
+    #[error("bad double: {0}")]
+    BadFloatParse(#[from] num::ParseFloatError),
+    #[error("invalid token: {0:?}")]
     InvalidToken(String),
-    InvalidArray(usize)
+    // Buffer ends part way through a frame, more data needs to be read
+    #[error("incomplete frame")]
+    Incomplete,
 }
 
-impl From<num::ParseIntError> for RESPParserError {
-    fn from(err: num::ParseIntError) -> RESPParserError {
-        RESPParserError::BadIntParse(err)
-    }
-}
-
-impl From<io::Error> for RESPParserError {
-    fn from(err: io::Error) -> RESPParserError {
-        RESPParserError::BadRead(err)
+impl RESPParserError {
+    /**
+     * Reason given to the client in a protocol error reply, e.g. "unbalanced quotes in request"
+     */
+    pub fn reason(&self) -> String {
+        match self {
+            RESPParserError::InvalidToken(reason) if !reason.is_empty() => reason.clone(),
+            err => err.to_string(),
+        }
     }
 }
 
 /**
- * Reads tokens from buffer until a RESP frame is read
+ * Decodes RESP frames from a read buffer.
+ * Frames are only consumed from the buffer once they are complete,
+ * and bulk strings are sliced out of the buffer without copying.
+ * Progress through an incomplete frame is kept between calls, so each read only scans new bytes;
+ * the buffer must only be appended to until the frame is returned.
  */
-pub struct RESPParser;
+#[derive(Debug, Default)]
+pub struct RESPParser {
+    // Bytes of the incomplete frame already checked
+    checked: usize,
+    // Tokens left to read at each level of nesting, innermost last
+    remaining_tokens: Vec<usize>,
+}
 
 impl RESPParser {
-    // Parses one token (or array of tokens) at time
-    pub async fn parse<R: AsyncBufReadExt + Unpin>(
-        reader: &mut R
-    ) -> Result<RESPMessage, RESPParserError> {
-        let mut parsed_message = vec![];
-        let mut remaining_tokens = 1;
-        let mut token_buf: String = String::new();
-    
-        while remaining_tokens > 0 {
-            RESPParser::read_token(reader, &mut token_buf).await?;
-            let prefix = token_buf.get(..1)
-                .ok_or(RESPParserError::InvalidToken(token_buf.to_owned()))?;
-
-            match prefix {
-                "+" => parsed_message.push(
-                    RESPToken::SimpleString(RESPParser::trim_token(&token_buf).to_owned())
-                ),
-                "-" => parsed_message.push(
-                    RESPToken::Error(RESPParser::trim_token(&token_buf).to_owned())
-                ),
-                ":" => parsed_message.push(
-                    RESPToken::Integer(RESPParser::trim_token(&token_buf).parse::<i64>()?)
-                ),
-                "$" => {
-                    let string_size = RESPParser::trim_token(&token_buf);
-                    if string_size == "-1" {
-                        parsed_message.push(RESPToken::Null)
-                    } else {
-                        let string_size = string_size.parse::<u32>()?;
-
-                        RESPParser::read_token(reader, &mut token_buf).await?;
-                        let bulk_string = Bytes::from(token_buf.trim().to_owned());
-                        
-                        parsed_message.push(
-                            RESPToken::BulkString(string_size, bulk_string)
-                        )
-                    }
+    /**
+     * Returns the next complete frame, or None if the buffer doesn't hold one yet
+     */
+    pub fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<RESPFrame>, RESPParserError> {
+        match buf.first() {
+            None => return Ok(None),
+            Some(prefix) if !RESP_PREFIXES.contains(prefix) => return self.decode_inline(buf),
+            _ => {}
+        }
+
+        // First pass only checks the frame is complete, without taking from the buffer
+        let frame_size = match self.check_frame(buf) {
+            Ok(frame_size) => frame_size,
+            Err(RESPParserError::Incomplete) => return Ok(None),
+            Err(err) => {
+                *self = RESPParser::default();
+                return Err(err)
+            },
+        };
+
+        // Second pass takes the frame and shares its memory with the parsed bulk strings
+        let frame_bytes = buf.split_to(frame_size).freeze();
+        let slice_bulk = |range| frame_bytes.slice(range);
+        let mut pos = 0;
+        let mut tokens = std::iter::from_fn(||
+            RESPParser::read_token(&frame_bytes, &mut pos, &slice_bulk).ok()
+        );
+
+        Ok(Some(RESPFrame::from_tokens(&mut tokens)))
+    }
+
+    /**
+     * Inline commands are plain text lines, e.g. "SET key value\r\n" typed from telnet.
+     * Arguments are split on whitespace and decoded as an array of bulk strings.
+     */
+    fn decode_inline(&mut self, buf: &mut BytesMut) -> Result<Option<RESPFrame>, RESPParserError> {
+        loop {
+            let line_end = match buf[self.checked..].iter().position(|byte| *byte == b'\n') {
+                Some(line_end) => self.checked + line_end,
+                None if buf.len() > MAX_INLINE_SIZE => {
+                    self.checked = 0;
+                    return Err(RESPParserError::InvalidToken("too big inline request".to_owned()))
                 },
-                "*" => {
-                    // Only accept array at start of message
-                    if !parsed_message.is_empty() {
-                        return Err(RESPParserError::InvalidArray(parsed_message.len()))
+                None => {
+                    self.checked = buf.len();
+                    return Ok(None)
+                },
+            };
+            self.checked = 0;
+
+            let line = buf.split_to(line_end + 1);
+            let line = line.strip_suffix(b"\n").unwrap();
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+            let args = RESPParser::split_inline_args(line)?;
+
+            // Blank lines are skipped, like Redis does
+            if !args.is_empty() {
+                return Ok(Some(RESPFrame::Array(args.into_iter().map(RESPFrame::Bulk).collect())))
+            }
+            if buf.is_empty() {
+                return Ok(None)
+            }
+        }
+    }
+
+    /**
+     * Splits arguments the same way as Redis' sdssplitargs.
+     * Double quotes support escape sequences ("\n", "\x41"), single quotes only escape "\'".
+     */
+    fn split_inline_args(line: &[u8]) -> Result<Vec<Bytes>, RESPParserError> {
+        let unbalanced_quotes = || RESPParserError::InvalidToken("unbalanced quotes in request".to_owned());
+        let mut args = vec![];
+        let mut pos = 0;
+
+        loop {
+            while pos < line.len() && line[pos].is_ascii_whitespace() {
+                pos += 1
+            }
+            if pos == line.len() {
+                return Ok(args)
+            }
+
+            let mut arg = vec![];
+            let quote = match line[pos] {
+                b'"' | b'\'' => { pos += 1; Some(line[pos - 1]) },
+                _ => None
+            };
+
+            loop {
+                match (quote, line.get(pos)) {
+                    (None, None) => break,
+                    (None, Some(byte)) if byte.is_ascii_whitespace() => break,
+                    (Some(_), None) => return Err(unbalanced_quotes()),
+                    (Some(quote), Some(byte)) if *byte == quote => {
+                        // Closing quote must be followed by a space or nothing
+                        pos += 1;
+                        match line.get(pos) {
+                            Some(next) if !next.is_ascii_whitespace() => return Err(unbalanced_quotes()),
+                            _ => break
+                        }
+                    },
+                    (Some(b'"'), Some(b'\\')) if pos + 1 < line.len() => {
+                        let hex = line.get(pos + 2..pos + 4)
+                            .and_then(|hex| from_utf8(hex).ok())
+                            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+                        match (line[pos + 1], hex) {
+                            (b'x', Some(byte)) => { arg.push(byte); pos += 4; continue },
+                            (b'n', _) => arg.push(b'\n'),
+                            (b'r', _) => arg.push(b'\r'),
+                            (b't', _) => arg.push(b'\t'),
+                            (b'b', _) => arg.push(0x08),
+                            (b'a', _) => arg.push(0x07),
+                            (escaped, _) => arg.push(escaped),
+                        }
+                        pos += 2
+                    },
+                    (Some(b'\''), Some(b'\\')) if line.get(pos + 1) == Some(&b'\'') => {
+                        arg.push(b'\'');
+                        pos += 2
+                    },
+                    (_, Some(byte)) => {
+                        arg.push(*byte);
+                        pos += 1
                     }
+                }
+            }
 
-                    let array_size = RESPParser::trim_token(&token_buf)
-                        .parse::<u32>()?;
-                    parsed_message.push(RESPToken::ArraySize(array_size));
-                    remaining_tokens += array_size;
-                },
-                _ => {
-                    return Err(RESPParserError::InvalidToken(token_buf));
+            args.push(Bytes::from(arg));
+        }
+    }
+
+    /**
+     * Reads one token (or aggregate of tokens) at a time until a whole frame is read,
+     * resuming after the tokens checked by previous calls.
+     * Returns the size of the frame in bytes.
+     */
+    fn check_frame(&mut self, src: &[u8]) -> Result<usize, RESPParserError> {
+        if self.remaining_tokens.is_empty() {
+            self.remaining_tokens.push(1);
+        }
+
+        while let Some(remaining) = self.remaining_tokens.last_mut() {
+            if *remaining == 0 {
+                self.remaining_tokens.pop();
+                continue
+            }
+
+            // Progress is only kept once a token is complete
+            let mut pos = self.checked;
+            let child_count = RESPParser::skip_token(src, &mut pos)?;
+            *remaining -= 1;
+            self.checked = pos;
+
+            // Aggregates open a new level of tokens to read
+            if child_count > 0 {
+                if self.remaining_tokens.len() > MAX_NESTING_DEPTH {
+                    return Err(RESPParserError::InvalidToken("too deeply nested aggregates".to_owned()))
                 }
+                self.remaining_tokens.push(child_count);
             }
-            remaining_tokens -= 1
         }
-    
-        Ok(parsed_message)
-    }
-    
-    async fn read_token<R: AsyncBufReadExt + Unpin>(
-        reader: &mut R,
-        token_buf: &mut String
-    ) -> io::Result<()> {
-        token_buf.clear();
-        reader.read_line(token_buf).await?;
-
-        println!("Read token: {:?}", token_buf);
+
+        Ok(std::mem::take(&mut self.checked))
+    }
+
+    /**
+     * Validates one token the same way as read_token, without building it.
+     * Returns how many tokens nest under it.
+     */
+    fn skip_token(src: &[u8], pos: &mut usize) -> Result<usize, RESPParserError> {
+        let line = RESPParser::read_line(src, pos)?;
+        let (prefix, content) = line.split_first()
+            .ok_or_else(|| RESPParserError::InvalidToken(String::new()))?;
+
+        let child_count = match prefix {
+            b'+' | b'-' | b'(' => { RESPParser::to_str(content)?; 0 },
+            b':' => { RESPParser::to_str(content)?.parse::<i64>()?; 0 },
+            b'$' | b'*' if content == b"-1" => 0,
+            b'$' => {
+                let string_size = RESPParser::read_size(content)?;
+                RESPParser::read_bulk(src, pos, string_size)?;
+                0
+            },
+            b'_' => 0,
+            b',' => { RESPParser::to_str(content)?.parse::<f64>()?; 0 },
+            b'#' if content == b"t" || content == b"f" => 0,
+            b'=' => {
+                let string_size = RESPParser::read_size(content)?;
+                let verbatim_range = RESPParser::read_bulk(src, pos, string_size)?;
+                RESPParser::check_verbatim(&src[verbatim_range])?;
+                0
+            },
+            b'*' | b'%' | b'|' | b'~' | b'>' => {
+                let size = RESPParser::to_str(content)?.parse::<u32>()?;
+                let aggregate = match prefix {
+                    b'*' => RESPToken::ArraySize(size),
+                    b'%' => RESPToken::MapSize(size),
+                    b'|' => RESPToken::AttributeSize(size),
+                    b'~' => RESPToken::SetSize(size),
+                    _ => RESPToken::PushSize(size),
+                };
+                aggregate.child_count()
+            },
+            _ => return Err(RESPParserError::InvalidToken(
+                String::from_utf8_lossy(line).into_owned()
+            )),
+        };
+
+        Ok(child_count)
+    }
+
+    fn read_token<S: Fn(Range<usize>) -> Bytes>(
+        src: &[u8],
+        pos: &mut usize,
+        slice_bulk: &S
+    ) -> Result<RESPToken, RESPParserError> {
+        let line = RESPParser::read_line(src, pos)?;
+        let (prefix, content) = line.split_first()
+            .ok_or_else(|| RESPParserError::InvalidToken(String::new()))?;
+
+        let token = match prefix {
+            b'+' => RESPToken::SimpleString(RESPParser::to_str(content)?.to_owned()),
+            b'-' => RESPToken::Error(RESPParser::to_str(content)?.to_owned()),
+            b':' => RESPToken::Integer(RESPParser::to_str(content)?.parse::<i64>()?),
+            b'$' => {
+                if content == b"-1" {
+                    RESPToken::Null
+                } else {
+                    let string_size = RESPParser::read_size(content)?;
+                    let bulk_range = RESPParser::read_bulk(src, pos, string_size)?;
+
+                    RESPToken::BulkString(string_size, slice_bulk(bulk_range))
+                }
+            },
+            b'*' => {
+                if content == b"-1" {
+                    RESPToken::NullArray
+                } else {
+                    RESPToken::ArraySize(RESPParser::to_str(content)?.parse::<u32>()?)
+                }
+            },
+            b'_' => RESPToken::Nil,
+            b',' => RESPToken::Double(RESPParser::to_str(content)?.parse::<f64>()?),
+            b'#' => match content {
+                b"t" => RESPToken::Boolean(true),
+                b"f" => RESPToken::Boolean(false),
+                _ => return Err(RESPParserError::InvalidToken(
+                    String::from_utf8_lossy(line).into_owned()
+                )),
+            },
+            b'(' => RESPToken::BigNumber(RESPParser::to_str(content)?.to_owned()),
+            b'=' => {
+                let string_size = RESPParser::read_size(content)?;
+                let verbatim_range = RESPParser::read_bulk(src, pos, string_size)?;
+
+                let verbatim = &src[verbatim_range.clone()];
+                RESPParser::check_verbatim(verbatim)?;
+                let format = String::from_utf8_lossy(&verbatim[..3]).into_owned();
+
+                RESPToken::VerbatimString(format, slice_bulk(verbatim_range.start + 4..verbatim_range.end))
+            },
+            b'%' => RESPToken::MapSize(RESPParser::to_str(content)?.parse::<u32>()?),
+            b'|' => RESPToken::AttributeSize(RESPParser::to_str(content)?.parse::<u32>()?),
+            b'~' => RESPToken::SetSize(RESPParser::to_str(content)?.parse::<u32>()?),
+            b'>' => RESPToken::PushSize(RESPParser::to_str(content)?.parse::<u32>()?),
+            _ => return Err(RESPParserError::InvalidToken(
+                String::from_utf8_lossy(line).into_owned()
+            )),
+        };
+
+        Ok(token)
+    }
+
+    /**
+     * Reads up to the next CRLF, returning the line without it
+     */
+    fn read_line<'a>(src: &'a [u8], pos: &mut usize) -> Result<&'a [u8], RESPParserError> {
+        let rest = src.get(*pos..).unwrap_or_default();
+        let line_end = rest.windows(2)
+            .position(|window| window == b"\r\n")
+            .ok_or(RESPParserError::Incomplete)?;
+
+        *pos += line_end + 2;
+        Ok(&rest[..line_end])
+    }
+
+    /**
+     * Skips exactly the declared bulk length, then expects the terminating CRLF.
+     * Payload bytes are never trimmed or decoded.
+     */
+    fn read_bulk(src: &[u8], pos: &mut usize, size: u32) -> Result<Range<usize>, RESPParserError> {
+        let bulk_range = *pos..*pos + size as usize;
+
+        match src.get(bulk_range.end..bulk_range.end + 2) {
+            Some(b"\r\n") => {},
+            Some(_) => return Err(RESPParserError::InvalidToken(
+                String::from_utf8_lossy(&src[bulk_range]).into_owned()
+            )),
+            None => return Err(RESPParserError::Incomplete),
+        }
+
+        *pos = bulk_range.end + 2;
+        Ok(bulk_range)
+    }
+
+    /**
+     * Verbatim payloads are prefixed with a three character format, e.g. "txt:"
+     */
+    fn check_verbatim(verbatim: &[u8]) -> Result<(), RESPParserError> {
+        if verbatim.len() < 4 || verbatim[3] != b':' {
+            return Err(RESPParserError::InvalidToken(
+                String::from_utf8_lossy(verbatim).into_owned()
+            ))
+        }
         Ok(())
     }
 
-    fn trim_token(token: &str) -> &str {
-        token.get(1..).unwrap().trim()
+    fn read_size(content: &[u8]) -> Result<u32, RESPParserError> {
+        let size = RESPParser::to_str(content)?.parse::<u32>()?;
+
+        if size > MAX_BULK_SIZE {
+            return Err(RESPParserError::InvalidToken(format!("invalid bulk length {}", size)))
+        }
+        Ok(size)
     }
-}
 
-pub fn to_string(message: RESPMessage) -> String {
-    message.into_iter()
-        .map(|token| token.to_string())
-        .collect::<Vec<String>>()
-        .join("")
+    fn to_str(content: &[u8]) -> Result<&str, RESPParserError> {
+        from_utf8(content)
+            .map_err(|_| RESPParserError::InvalidToken(String::from_utf8_lossy(content).into_owned()))
+    }
 }
 
 
 #[cfg(test)]
 mod tests {
-    use std::io::Cursor;
     use super::*;
     use rstest::rstest;
-    use tokio::io::BufReader;
 
-    #[tokio::test]
-    async fn should_parse_simple() {
-        let mut reader =
-            BufReader::new(Cursor::new("+PING\r\n"));
+    fn decode_str(input: &str) -> Result<Option<RESPFrame>, RESPParserError> {
+        RESPParser::default().decode(&mut BytesMut::from(input))
+    }
 
-        let simple_string_message: RESPMessage =
-            RESPParser::parse(&mut reader).await.unwrap();
+    #[test]
+    fn should_parse_simple() {
+        let simple_string_frame = decode_str("+PING\r\n").unwrap().unwrap();
 
         assert!(matches!(
-            simple_string_message.as_slice(),
-            [RESPToken::SimpleString(s)] if s == "PING"
+            simple_string_frame,
+            RESPFrame::Simple(s) if s == "PING"
         ));
     }
 
-    #[tokio::test]
-    async fn should_parse_all_types() {
-        let mut reader = BufReader::new(Cursor::new(
+    #[test]
+    fn should_parse_all_types() {
+        let array_frame = decode_str(
             "*5\r\n+string\r\n-error\r\n:10\r\n$-1\r\n$4\r\nbulk\r\n"
+        ).unwrap().unwrap();
+
+        assert!(matches!(
+            &array_frame,
+            RESPFrame::Array(array) if matches!(
+                array.as_slice(),
+                [
+                    RESPFrame::Simple(simple),
+                    RESPFrame::Error(error),
+                    RESPFrame::Integer(10),
+                    RESPFrame::Null,
+                    RESPFrame::Bulk(bulk),
+                ] if simple == "string"
+                    && error == "error"
+                    && bulk == "bulk"
+            )
         ));
+    }
 
-        let array_message: RESPMessage =
-            RESPParser::parse(&mut reader).await.unwrap();
+    #[test]
+    fn should_parse_all_resp3_types() {
+        let array_frame = decode_str(
+            "*6\r\n_\r\n,1.25\r\n#t\r\n(12345678901234567890\r\n=8\r\nmkd:text\r\n>1\r\n,-inf\r\n"
+        ).unwrap().unwrap();
 
         assert!(matches!(
-            array_message.as_slice(),
-            [
-                RESPToken::ArraySize(5),
-                RESPToken::SimpleString(simple),
-                RESPToken::Error(error),
-                RESPToken::Integer(10),
-                RESPToken::Null,
-                RESPToken::BulkString(4, bulk),
-                
-            ] if simple == "string" 
-                && error == "error" 
-                && bulk == "bulk"
+            &array_frame,
+            RESPFrame::Array(array) if matches!(
+                array.as_slice(),
+                [
+                    RESPFrame::Nil,
+                    RESPFrame::Double(double),
+                    RESPFrame::Boolean(true),
+                    RESPFrame::BigNumber(big),
+                    RESPFrame::Verbatim(format, verbatim),
+                    RESPFrame::Push(push),
+                ] if *double == 1.25
+                    && big == "12345678901234567890"
+                    && format == "mkd"
+                    && verbatim == "text"
+                    && matches!(
+                        push.as_slice(),
+                        [RESPFrame::Double(infinity)] if *infinity == f64::NEG_INFINITY
+                    )
+            )
         ));
     }
 
-    #[tokio::test]
-    async fn should_only_parse_up_to_array_length() {
-        let mut reader = BufReader::new(Cursor::new(
+    #[test]
+    fn should_parse_resp3_aggregates() {
+        let mut buf = BytesMut::from(
+            "|1\r\n+ttl\r\n:10\r\n%2\r\n+a\r\n:1\r\n+b\r\n~2\r\n#f\r\n_\r\n+extra\r\n"
+        );
+
+        let attribute_frame = RESPParser::default().decode(&mut buf).unwrap().unwrap();
+
+        assert!(matches!(
+            &attribute_frame,
+            RESPFrame::Attribute(attributes, map) if attributes.len() == 1 && matches!(
+                map.as_ref(),
+                RESPFrame::Map(pairs) if matches!(
+                    pairs.as_slice(),
+                    [
+                        (RESPFrame::Simple(_), RESPFrame::Integer(1)),
+                        (RESPFrame::Simple(_), RESPFrame::Set(set)),
+                    ] if set.len() == 2
+                )
+            )
+        ));
+        assert_eq!("+extra\r\n", buf);
+    }
+
+    #[rstest]
+    #[case("#x\r\n")]
+    #[case("=3\r\ntxt\r\n")]
+    fn should_not_parse_bad_resp3_tokens(#[case] input: &str) {
+        assert!(matches!(
+            decode_str(input).err().unwrap(),
+            RESPParserError::InvalidToken(_)
+        ));
+    }
+
+    #[test]
+    fn should_not_parse_bad_double() {
+        assert!(matches!(
+            decode_str(",one\r\n").err().unwrap(),
+            RESPParserError::BadFloatParse(_)
+        ));
+    }
+
+    #[test]
+    fn should_only_parse_up_to_array_length() {
+        let mut buf = BytesMut::from(
             "*2\r\n+many\r\n+things\r\n+to\r\n+say\r\n"
+        );
+
+        let array_frame = RESPParser::default().decode(&mut buf).unwrap().unwrap();
+
+        assert!(matches!(
+            &array_frame,
+            RESPFrame::Array(array) if matches!(
+                array.as_slice(),
+                [
+                    RESPFrame::Simple(first),
+                    RESPFrame::Simple(second)
+                ] if first == "many" && second == "things"
+            )
         ));
+        assert_eq!("+to\r\n+say\r\n", buf);
+    }
+
+    #[test]
+    fn should_decode_consecutive_frames() {
+        let mut buf = BytesMut::from("+first\r\n:2\r\n$5\r\nthird\r\n");
+
+        let mut parser = RESPParser::default();
+        assert!(matches!(parser.decode(&mut buf).unwrap(), Some(RESPFrame::Simple(s)) if s == "first"));
+        assert!(matches!(parser.decode(&mut buf).unwrap(), Some(RESPFrame::Integer(2))));
+        assert!(matches!(parser.decode(&mut buf).unwrap(), Some(RESPFrame::Bulk(s)) if s == "third"));
+        assert!(parser.decode(&mut buf).unwrap().is_none());
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn should_not_parse_empty_buffer() {
+        assert!(decode_str("").unwrap().is_none());
+    }
 
-        let array_message: RESPMessage =
-            RESPParser::parse(&mut reader).await.unwrap();
+    #[rstest]
+    #[case("*2\r\n+hi\r\n")]
+    #[case("*2\r\n+hi\r\n*2\r\n+nested\r\n")]
+    #[case("+PI")]
+    #[case("$5\r\nhel")]
+    #[case("$5\r\nhello\r")]
+    #[case("%1\r\n+key\r\n")]
+    #[case("|1\r\n+key\r\n+value\r\n")]
+    fn should_wait_for_incomplete_frames(#[case] input: &str) {
+        let mut buf = BytesMut::from(input);
+
+        assert!(RESPParser::default().decode(&mut buf).unwrap().is_none());
+        // Nothing is consumed until the frame is complete
+        assert_eq!(input, buf);
+    }
 
-        assert_eq!(3, array_message.len());
+    #[test]
+    fn should_parse_frame_once_completed() {
+        let mut buf = BytesMut::from("*2\r\n$4\r\nECHO\r\n$5\r\nhel");
+        let mut parser = RESPParser::default();
+        assert!(parser.decode(&mut buf).unwrap().is_none());
+
+        buf.extend_from_slice(b"lo\r\n");
         assert!(matches!(
-            array_message.as_slice(),
-            [
-                RESPToken::ArraySize(2),
-                RESPToken::SimpleString(first),
-                RESPToken::SimpleString(second)
-                
-            ] if first == "many" && second == "things" 
+            parser.decode(&mut buf).unwrap(),
+            Some(RESPFrame::Array(array)) if matches!(
+                array.as_slice(),
+                [RESPFrame::Bulk(command), RESPFrame::Bulk(message)]
+                    if command == "ECHO" && message == "hello"
+            )
         ));
+        assert!(buf.is_empty());
     }
 
-    #[tokio::test]
-    async fn should_not_parse_empty_buffer() {
-        let mut reader =
-            BufReader::new(Cursor::new(""));
+    #[rstest]
+    #[case("*3\r\n+hi\r\n*2\r\n*1\r\n:1\r\n$5\r\nhello\r\n|1\r\n+key\r\n=8\r\ntxt:done\r\n#t\r\n")]
+    #[case("SET key \"hello world\"\r\n")]
+    fn should_resume_frames_read_byte_by_byte(#[case] input: &str) {
+        let mut parser = RESPParser::default();
+        let mut buf = BytesMut::new();
+
+        for (i, byte) in input.bytes().enumerate() {
+            buf.extend_from_slice(&[byte]);
+            let frame = parser.decode(&mut buf).unwrap();
+
+            if i + 1 < input.len() {
+                assert!(frame.is_none());
+                // Only the unchecked tail is scanned on the next read
+                assert!(parser.checked <= buf.len());
+            } else {
+                assert!(frame.is_some());
+            }
+        }
+        assert!(buf.is_empty());
+        assert_eq!(0, parser.checked);
+        assert!(parser.remaining_tokens.is_empty());
+    }
+
+    #[test]
+    fn should_parse_nested_arrays() {
+        let nested_frame = decode_str(
+            "*3\r\n+hi\r\n*2\r\n*1\r\n:1\r\n*0\r\n*-1\r\n+after\r\n"
+        ).unwrap().unwrap();
 
         assert!(matches!(
-            RESPParser::parse(&mut reader).await.err().unwrap(),
-            RESPParserError::InvalidToken(_)
+            &nested_frame,
+            RESPFrame::Array(array) if matches!(
+                array.as_slice(),
+                [
+                    RESPFrame::Simple(hi),
+                    RESPFrame::Array(nested),
+                    RESPFrame::NullArray,
+                ] if hi == "hi" && matches!(
+                    nested.as_slice(),
+                    [RESPFrame::Array(one), RESPFrame::Array(empty)]
+                        if one.len() == 1 && empty.is_empty()
+                )
+            )
         ));
     }
 
-    #[tokio::test]
-    async fn should_not_accept_array_token_after_start() {
-        let mut reader =
-            BufReader::new(Cursor::new("*2\r\n+hi\r\n*1\r\n+nested\r\n"));
+    #[test]
+    fn should_parse_null_array() {
+        assert!(matches!(
+            decode_str("*-1\r\n").unwrap().unwrap(),
+            RESPFrame::NullArray
+        ));
+    }
+
+    #[rstest]
+    #[case("PING\r\n", &["PING"])]
+    #[case("PING\n", &["PING"])]
+    #[case("  set  key   value \r\n", &["set", "key", "value"])]
+    #[case("?what\r\n", &["?what"])]
+    #[case("SET key \"hello world\"\r\n", &["SET", "key", "hello world"])]
+    #[case("SET key \"line\\r\\nbreak\\x41\\\"\"\r\n", &["SET", "key", "line\r\nbreakA\""])]
+    #[case("SET key 'it\\'s \\n raw'\r\n", &["SET", "key", "it's \\n raw"])]
+    #[case("ECHO \"\"\r\n", &["ECHO", ""])]
+    fn should_parse_inline_command(#[case] input: &str, #[case] expected: &[&str]) {
+        let inline_frame = decode_str(input).unwrap().unwrap();
 
         assert!(matches!(
-            RESPParser::parse(&mut reader).await.err().unwrap(),
-            RESPParserError::InvalidArray(_)
+            inline_frame,
+            RESPFrame::Array(array) if array.len() == expected.len()
+                && array.iter().zip(expected).all(|(arg, expected_arg)| matches!(
+                    arg,
+                    RESPFrame::Bulk(bulk) if bulk == expected_arg
+                ))
         ));
     }
 
-    #[tokio::test]
-    async fn should_not_parse_incomplete_array() {
-        let mut reader =
-            BufReader::new(Cursor::new("*2\r\n+hi\r\n"));
+    #[test]
+    fn should_decode_inline_and_resp_commands_together() {
+        let mut buf = BytesMut::from("\r\n\r\nPING\r\n*1\r\n$4\r\nPING\r\nECHO hi\r\nGET");
+
+        let mut parser = RESPParser::default();
+        assert!(matches!(parser.decode(&mut buf).unwrap(), Some(RESPFrame::Array(array)) if array.len() == 1));
+        assert!(matches!(parser.decode(&mut buf).unwrap(), Some(RESPFrame::Array(array)) if array.len() == 1));
+        assert!(matches!(parser.decode(&mut buf).unwrap(), Some(RESPFrame::Array(array)) if array.len() == 2));
+        // Inline commands wait for the end of line too
+        assert!(parser.decode(&mut buf).unwrap().is_none());
+        assert_eq!("GET", buf);
+    }
 
+    #[test]
+    fn should_skip_blank_inline_lines() {
+        let mut buf = BytesMut::from("  \r\n\r\n");
+
+        assert!(RESPParser::default().decode(&mut buf).unwrap().is_none());
+        assert!(buf.is_empty());
+    }
+
+    #[rstest]
+    #[case("SET key \"unclosed\r\n")]
+    #[case("SET key 'unclosed\r\n")]
+    #[case("SET key \"closed\"trailing\r\n")]
+    fn should_not_parse_unbalanced_inline_quotes(#[case] input: &str) {
         assert!(matches!(
-            RESPParser::parse(&mut reader).await.err().unwrap(),
+            decode_str(input).err().unwrap(),
             RESPParserError::InvalidToken(_)
         ));
     }
 
-    #[tokio::test]
-    async fn should_not_parse_bad_integer() {
-        let mut reader =
-            BufReader::new(Cursor::new("*1\r\n:one\r\n"));
+    #[test]
+    fn should_not_parse_oversized_inline_command() {
+        let oversized = "A".repeat(MAX_INLINE_SIZE + 1);
+
+        assert!(matches!(
+            decode_str(&oversized).err().unwrap(),
+            RESPParserError::InvalidToken(_)
+        ));
+    }
 
+    #[test]
+    fn should_not_parse_bad_integer() {
         assert!(matches!(
-            RESPParser::parse(&mut reader).await.err().unwrap(),
+            decode_str("*1\r\n:one\r\n").err().unwrap(),
             RESPParserError::BadIntParse(_)
         ));
     }
 
-    #[tokio::test]
-    async fn should_not_parse_bad_bulk_size() {
-        let mut reader =
-            BufReader::new(Cursor::new("*1\r\n$-8\r\nnegative\r\n"));
+    #[rstest]
+    #[case(b"$12\r\nline\r\nbreaks\r\n", b"line\r\nbreaks")]
+    #[case(b"$10\r\n  padded  \r\n", b"  padded  ")]
+    #[case(b"$4\r\n\xff\x00\r\n\r\n", b"\xff\x00\r\n")]
+    #[case(b"$0\r\n\r\n", b"")]
+    fn should_parse_binary_bulk_string(
+        #[case] input: &'static [u8],
+        #[case] expected: &'static [u8]
+    ) {
+        let bulk_frame = RESPParser::default().decode(&mut BytesMut::from(input)).unwrap().unwrap();
+
+        assert!(matches!(
+            bulk_frame,
+            RESPFrame::Bulk(bulk) if bulk == expected
+        ));
+    }
+
+    #[test]
+    fn should_slice_bulk_strings_from_read_buffer() {
+        let mut buf = BytesMut::from("*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n");
+        let buffer_range = buf.as_ptr_range();
+
+        let frame = RESPParser::default().decode(&mut buf).unwrap().unwrap();
 
         assert!(matches!(
-            RESPParser::parse(&mut reader).await.err().unwrap(),
+            frame,
+            RESPFrame::Array(array) if array.iter().all(|element| matches!(
+                element,
+                RESPFrame::Bulk(bulk) if buffer_range.contains(&bulk.as_ptr())
+            ))
+        ));
+    }
+
+    #[test]
+    fn should_not_parse_bulk_string_longer_than_size() {
+        assert!(matches!(
+            decode_str("$2\r\nlonger\r\n").err().unwrap(),
+            RESPParserError::InvalidToken(_)
+        ));
+    }
+
+    #[test]
+    fn should_not_parse_bad_bulk_size() {
+        assert!(matches!(
+            decode_str("*1\r\n$-8\r\nnegative\r\n").err().unwrap(),
             RESPParserError::BadIntParse(_)
         ));
     }
 
+    #[test]
+    fn should_parse_frame_nested_up_to_max_depth() {
+        let nested = format!("{}+deepest\r\n", "*1\r\n".repeat(MAX_NESTING_DEPTH));
+
+        let mut frame = decode_str(&nested).unwrap().unwrap();
+        for _ in 0..MAX_NESTING_DEPTH {
+            let RESPFrame::Array(mut elements) = frame else { panic!("expected an array") };
+            frame = elements.pop().unwrap();
+        }
+        assert!(matches!(frame, RESPFrame::Simple(s) if s == "deepest"));
+    }
+
     #[rstest]
-    #[case("$4\r\n","4")]
-    #[case("*1\r\n","1")]
-    #[case("+\r\n","")]
-    #[case("+PING\r\n","PING")]
-    fn correctly_trims_token(#[case] token: &str, #[case] trimmed: &str) {
-        assert_eq!(trimmed, RESPParser::trim_token(&token))
+    #[case(MAX_NESTING_DEPTH + 1)]
+    #[case(4 * 1024 * 1024)]
+    fn should_not_parse_frame_nested_too_deep(#[case] depth: usize) {
+        let nested = format!("{}+deepest\r\n", "*1\r\n".repeat(depth));
+
+        assert!(matches!(
+            decode_str(&nested).err().unwrap(),
+            RESPParserError::InvalidToken(_)
+        ));
     }
 
     #[test]
-    fn resp_message_to_string() {
-        assert_eq!(
-            "*2\r\n$7\r\nCOMMAND\r\n$4\r\nDOCS\r\n",
-            to_string(vec![
-                RESPToken::ArraySize(2),
-                RESPToken::BulkString(7, Bytes::from("COMMAND")),
-                RESPToken::BulkString(4, Bytes::from("DOCS")),
-            ])
-        );
+    fn should_not_parse_oversized_bulk() {
+        assert!(matches!(
+            decode_str("$536870913\r\n").err().unwrap(),
+            RESPParserError::InvalidToken(_)
+        ));
     }
 }
@@ -1,6 +1,4 @@
-use std::str::from_utf8;
-
-use bytes::{Bytes, Buf};
+use bytes::{Bytes, BytesMut, BufMut};
 
 #[derive(Debug)]
 pub enum RESPToken {
@@ -9,22 +7,96 @@ pub enum RESPToken {
     Integer(i64),               // ":<INT>\r\n"
     BulkString(u32, Bytes),     // "$<SIZE>\r\n<STRING>\r\n"
     Null,                       // "$-1\r\n"
-    ArraySize(u32)              // "*<SIZE>\r\n"
+    ArraySize(u32),             // "*<SIZE>\r\n"
+    NullArray,                  // "*-1\r\n"
+    // RESP3 only
+    Nil,                        // "_\r\n"
+    Double(f64),                // ",<FLOAT>\r\n"
+    Boolean(bool),              // "#<t|f>\r\n"
+    BigNumber(String),          // "(<BIG_INT>\r\n"
+    VerbatimString(String, Bytes), // "=<SIZE>\r\n<FORMAT>:<STRING>\r\n"
+    MapSize(u32),               // "%<SIZE>\r\n"
+    SetSize(u32),               // "~<SIZE>\r\n"
+    AttributeSize(u32),         // "|<SIZE>\r\n"
+    PushSize(u32)               // "><SIZE>\r\n"
 }
 
 impl RESPToken {
-    pub fn to_string(self) -> String {
+    /**
+     * Writes the serialised token into a byte buffer.
+     * Bulk strings are copied as raw bytes so binary payloads survive intact.
+     */
+    pub fn encode(&self, buf: &mut BytesMut) {
         match self {
-            RESPToken::SimpleString(s) => format!("+{}\r\n", s),
-            RESPToken::Error(s) => format!("-{}\r\n", s),
-            RESPToken::Integer(n) => format!(":{}\r\n", n),
+            RESPToken::SimpleString(s) => write_line(buf, b'+', s.as_bytes()),
+            RESPToken::Error(s) => write_line(buf, b'-', s.as_bytes()),
+            RESPToken::Integer(n) => write_line(buf, b':', n.to_string().as_bytes()),
             RESPToken::BulkString(size, s) => {
-                format!("${}\r\n{}\r\n", size, from_utf8(s.chunk()).unwrap())
-            },     
-            RESPToken::Null => format!("$-1\r\n"),
-            RESPToken::ArraySize(size) => format!("*{}\r\n", size),
+                write_line(buf, b'$', size.to_string().as_bytes());
+                buf.put_slice(s);
+                buf.put_slice(b"\r\n");
+            },
+            RESPToken::Null => buf.put_slice(b"$-1\r\n"),
+            RESPToken::ArraySize(size) => write_line(buf, b'*', size.to_string().as_bytes()),
+            RESPToken::NullArray => buf.put_slice(b"*-1\r\n"),
+            RESPToken::Nil => buf.put_slice(b"_\r\n"),
+            RESPToken::Double(n) => write_line(buf, b',', format_double(*n).as_bytes()),
+            RESPToken::Boolean(b) => write_line(buf, b'#', if *b { b"t" } else { b"f" }),
+            RESPToken::BigNumber(n) => write_line(buf, b'(', n.as_bytes()),
+            RESPToken::VerbatimString(format, s) => {
+                write_line(buf, b'=', (format.len() + 1 + s.len()).to_string().as_bytes());
+                buf.put_slice(format.as_bytes());
+                buf.put_u8(b':');
+                buf.put_slice(s);
+                buf.put_slice(b"\r\n");
+            },
+            RESPToken::MapSize(size) => write_line(buf, b'%', size.to_string().as_bytes()),
+            RESPToken::SetSize(size) => write_line(buf, b'~', size.to_string().as_bytes()),
+            RESPToken::AttributeSize(size) => write_line(buf, b'|', size.to_string().as_bytes()),
+            RESPToken::PushSize(size) => write_line(buf, b'>', size.to_string().as_bytes()),
+        }
+    }
+
+    /**
+     * Number of values that follow an aggregate token
+     */
+    pub fn child_count(&self) -> usize {
+        match self {
+            RESPToken::ArraySize(size)
+                | RESPToken::SetSize(size)
+                | RESPToken::PushSize(size) => *size as usize,
+            RESPToken::MapSize(size) => *size as usize * 2,
+            // Attributes also annotate the value that follows them
+            RESPToken::AttributeSize(size) => *size as usize * 2 + 1,
+            _ => 0
         }
     }
+
+    #[cfg(test)]
+    pub fn to_bytes(&self) -> Bytes {
+        let mut buf = BytesMut::new();
+        self.encode(&mut buf);
+        buf.freeze()
+    }
+}
+
+/**
+ * RESP3 spells out infinities and NaN instead of using Rust's formatting
+ */
+pub fn format_double(n: f64) -> String {
+    if n.is_nan() {
+        "nan".to_owned()
+    } else if n.is_infinite() {
+        if n.is_sign_positive() { "inf".to_owned() } else { "-inf".to_owned() }
+    } else {
+        n.to_string()
+    }
+}
+
+fn write_line(buf: &mut BytesMut, prefix: u8, line: &[u8]) {
+    buf.put_u8(prefix);
+    buf.put_slice(line);
+    buf.put_slice(b"\r\n");
 }
 
 #[cfg(test)]
@@ -38,7 +110,7 @@ mod tests {
     #[case(" ", "+ \r\n")]
     #[case("Hello world", "+Hello world\r\n")]
     fn should_serialise_simple_string(#[case] simple: &str, #[case] expected_str: &str) {
-        assert_eq!(expected_str, RESPToken::SimpleString(simple.to_owned()).to_string())
+        assert_eq!(expected_str, RESPToken::SimpleString(simple.to_owned()).to_bytes())
     }
 
     #[rstest]
@@ -46,7 +118,7 @@ mod tests {
     #[case("ERR bad message", "-ERR bad message\r\n")]
     #[case("", "-\r\n")]
     fn should_serialise_error(#[case] error: &str, #[case] expected_str: &str) {
-        assert_eq!(expected_str, RESPToken::Error(error.to_owned()).to_string())
+        assert_eq!(expected_str, RESPToken::Error(error.to_owned()).to_bytes())
     }
 
     #[rstest]
@@ -54,7 +126,7 @@ mod tests {
     #[case(-10, ":-10\r\n")]
     #[case(23, ":23\r\n")]
     fn should_serialise_int(#[case] int: i64, #[case] expected_str: &str) {
-        assert_eq!(expected_str, RESPToken::Integer(int).to_string())
+        assert_eq!(expected_str, RESPToken::Integer(int).to_bytes())
     }
 
     #[rstest]
@@ -69,13 +141,27 @@ mod tests {
     ) {
         assert_eq!(
             expected_str,
-            RESPToken::BulkString(size, Bytes::from(bulk.to_owned())).to_string()
+            RESPToken::BulkString(size, Bytes::from(bulk.to_owned())).to_bytes()
+        )
+    }
+
+    #[rstest]
+    #[case(b"line\r\nbreak", b"$11\r\nline\r\nbreak\r\n")]
+    #[case(b"  padded  ", b"$10\r\n  padded  \r\n")]
+    #[case(b"\xff\x00\xfe", b"$3\r\n\xff\x00\xfe\r\n")]
+    fn should_serialise_binary_bulk_string(
+        #[case] bulk: &'static [u8],
+        #[case] expected_bytes: &'static [u8]
+    ) {
+        assert_eq!(
+            expected_bytes,
+            RESPToken::BulkString(bulk.len() as u32, Bytes::from_static(bulk)).to_bytes()
         )
     }
 
     #[test]
     fn should_serialise_null() {
-        assert_eq!("$-1\r\n", RESPToken::Null.to_string())
+        assert_eq!("$-1\r\n", RESPToken::Null.to_bytes())
     }
 
     #[rstest]
@@ -83,6 +169,71 @@ mod tests {
     #[case(1,"*1\r\n")]
     #[case(5,"*5\r\n")]
     fn should_serialise_array(#[case] size: u32, #[case] expected_str: &str) {
-        assert_eq!(expected_str, RESPToken::ArraySize(size).to_string())
+        assert_eq!(expected_str, RESPToken::ArraySize(size).to_bytes())
+    }
+
+    #[test]
+    fn should_serialise_null_array() {
+        assert_eq!("*-1\r\n", RESPToken::NullArray.to_bytes())
+    }
+
+    #[rstest]
+    #[case(RESPToken::ArraySize(3), 3)]
+    #[case(RESPToken::SetSize(2), 2)]
+    #[case(RESPToken::PushSize(1), 1)]
+    #[case(RESPToken::MapSize(2), 4)]
+    #[case(RESPToken::AttributeSize(1), 3)]
+    #[case(RESPToken::NullArray, 0)]
+    #[case(RESPToken::Integer(5), 0)]
+    fn should_count_children(#[case] token: RESPToken, #[case] expected_count: usize) {
+        assert_eq!(expected_count, token.child_count())
+    }
+
+    #[test]
+    fn should_serialise_nil() {
+        assert_eq!("_\r\n", RESPToken::Nil.to_bytes())
+    }
+
+    #[rstest]
+    #[case(1.5, ",1.5\r\n")]
+    #[case(-3.0, ",-3\r\n")]
+    #[case(0.0, ",0\r\n")]
+    #[case(f64::INFINITY, ",inf\r\n")]
+    #[case(f64::NEG_INFINITY, ",-inf\r\n")]
+    #[case(f64::NAN, ",nan\r\n")]
+    fn should_serialise_double(#[case] double: f64, #[case] expected_str: &str) {
+        assert_eq!(expected_str, RESPToken::Double(double).to_bytes())
+    }
+
+    #[rstest]
+    #[case(true, "#t\r\n")]
+    #[case(false, "#f\r\n")]
+    fn should_serialise_boolean(#[case] boolean: bool, #[case] expected_str: &str) {
+        assert_eq!(expected_str, RESPToken::Boolean(boolean).to_bytes())
+    }
+
+    #[test]
+    fn should_serialise_big_number() {
+        assert_eq!(
+            "(3492890328409238509324850943850943825024385\r\n",
+            RESPToken::BigNumber("3492890328409238509324850943850943825024385".to_owned()).to_bytes()
+        )
+    }
+
+    #[test]
+    fn should_serialise_verbatim_string() {
+        assert_eq!(
+            "=15\r\ntxt:Some string\r\n",
+            RESPToken::VerbatimString("txt".to_owned(), Bytes::from("Some string")).to_bytes()
+        )
+    }
+
+    #[rstest]
+    #[case(RESPToken::MapSize(2), "%2\r\n")]
+    #[case(RESPToken::SetSize(3), "~3\r\n")]
+    #[case(RESPToken::AttributeSize(1), "|1\r\n")]
+    #[case(RESPToken::PushSize(0), ">0\r\n")]
+    fn should_serialise_aggregate_sizes(#[case] token: RESPToken, #[case] expected_str: &str) {
+        assert_eq!(expected_str, token.to_bytes())
     }
 }
@@ -1,18 +1,28 @@
-use std::str::from_utf8;
-use tokio::io::AsyncWriteExt;
-use tokio::io::BufReader;
+use bytes::BytesMut;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::io::AsyncWrite;
 use std::io::Result;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use crate::resp::{self, interpreter::RESPInterpreter, parser::{RESPParser, RESPMessage}};
+use crate::error::RedisError;
+use crate::resp::{frame::RESPFrame, interpreter::{RESPInterpreter, ConnectionState}, parser::{RESPParser, RESPParserError}};
 use crate::store::RedisStore;
 
+const BUFFER_SIZE: usize = 4096;
+// Same limit as Redis' default client-query-buffer-limit, past which a blocked client is disconnected
+#[cfg(not(test))]
+const MAX_QUERY_BUFFER_SIZE: usize = 1024 * 1024 * 1024;
+#[cfg(test)]
+const MAX_QUERY_BUFFER_SIZE: usize = 1024 * 1024;
+
 
 pub async fn init() {
     tokio::spawn(async {
         RedisStore::init();
-        println!("Server initialised")
+        println!("Server initialised");
+
+        RedisStore::active_expire_loop().await
     });
 }
 
@@ -37,37 +47,262 @@ pub async fn listen() {
     }
 }
 
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+
 async fn handle_connection(mut stream: TcpStream) -> Result<()> {
-    let (reader, mut writer) = stream.split();
-    let mut reader = BufReader::new(reader);
+    let (mut reader, mut writer) = stream.split();
+    let mut read_buf = BytesMut::with_capacity(BUFFER_SIZE);
+    let mut write_buf = BytesMut::with_capacity(BUFFER_SIZE);
+    let mut parser = RESPParser::default();
+    let mut connection = ConnectionState {
+        id: NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed),
+        ..ConnectionState::default()
+    };
+
     loop {
-        // Receive in RESP, Respond in RESP
-        let request = RESPParser::parse(&mut reader).await
-            .unwrap_or_else(|err| {
-                // Catch parser error and default to empty message
-                println!("Parsing error: {:?}",err);
-                vec![]
-            });
-        
-        if request.is_empty() {
-            println!("Closing connection, empty request received.");
+        // Execute every complete request already buffered, queueing their replies in order
+        // Input is still read meanwhile, so a client that disconnects while blocked is unblocked
+        let mut pending_buf = BytesMut::new();
+        let parse_result = {
+            let requests = handle_requests(&mut connection, &mut parser, &mut read_buf, &mut write_buf, &mut writer);
+            tokio::pin!(requests);
+
+            loop {
+                tokio::select! {
+                    biased;
+                    result = &mut requests => break result?,
+                    read = reader.read_buf(&mut pending_buf) => if read? == 0 {
+                        println!("Closing connection, client disconnected.");
+                        return Ok(())
+                    } else if pending_buf.len() > MAX_QUERY_BUFFER_SIZE {
+                        println!("Closing connection, query buffer limit reached while blocked.");
+                        return Ok(())
+                    }
+                }
+            }
+        };
+
+        // A malformed request is replied to before the connection is closed
+        if let Err(err) = &parse_result {
+            println!("Parsing error: {}", err);
+            RESPFrame::Error(RedisError::Protocol(err.reason()).to_string()).encode(&mut write_buf);
+        }
+
+        // Flush all queued replies with one write
+        if !write_buf.is_empty() {
+            reply(&mut writer, &write_buf).await?;
+            write_buf.clear();
+        }
+
+        if parse_result.is_err() {
             break
         }
-        println!("Request: {:?}", request);
 
-        let response_message: RESPMessage = RESPInterpreter::interpret(&(request.into())).await.into();
-        println!("Response: {:?}", response_message);
+        // Input received while executing is handled before reading more
+        if !pending_buf.is_empty() {
+            read_buf.extend_from_slice(&pending_buf);
+            continue
+        }
 
-        let response_string: String = resp::parser::to_string(response_message);
-        reply(&mut writer, response_string.as_bytes()).await?;
+        if reader.read_buf(&mut read_buf).await? == 0 {
+            println!("Closing connection, client disconnected.");
+            break
+        }
     }
 
     Ok(())
 }
 
+/**
+ * Receive in RESP, Respond in RESP
+ * Stops at the first incomplete request, leaving it in the read buffer, or at the first malformed one
+ * Replies are queued, and only flushed early before a command that may block the client
+ */
+async fn handle_requests<W: AsyncWrite + Unpin>(
+    connection: &mut ConnectionState,
+    parser: &mut RESPParser,
+    read_buf: &mut BytesMut,
+    write_buf: &mut BytesMut,
+    writer: &mut W
+) -> Result<std::result::Result<(), RESPParserError>> {
+    loop {
+        let request = match parser.decode(read_buf) {
+            Ok(Some(request)) => request,
+            Ok(None) => return Ok(Ok(())),
+            Err(err) => return Ok(Err(err)),
+        };
+        println!("Request: {:?}", request);
+
+        // Earlier replies aren't held back while the client waits
+        if !write_buf.is_empty() && RESPInterpreter::may_block(&request) {
+            reply(writer, write_buf).await?;
+            write_buf.clear();
+        }
+
+        let response = RESPInterpreter::interpret(connection, &request).await
+            .into_protocol(connection.protocol);
+        println!("Response: {:?}", response);
+
+        response.encode(write_buf);
+    }
+}
+
 async fn reply<W: AsyncWrite + Unpin>(writer: &mut W, buf: &[u8]) -> Result<()> {
-    println!("Sending: {:?}", from_utf8(buf).unwrap());
+    println!("Sending: {:?}", String::from_utf8_lossy(buf));
     writer.write_all(buf).await?;
 
     Ok(())
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resp::command::test_utils::wait_blocked_on;
+    use tokio::io::AsyncReadExt;
+
+    async fn connect() -> TcpStream {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            handle_connection(socket).await.unwrap();
+        });
+
+        TcpStream::connect(addr).await.unwrap()
+    }
+
+    async fn read_exactly(client: &mut TcpStream, size: usize) -> Vec<u8> {
+        let mut response = vec![0; size];
+        client.read_exact(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn should_reply_to_pipelined_requests_in_order() {
+        let mut client = connect().await;
+        let request_count = 500;
+
+        let mut requests = BytesMut::new();
+        for i in 0..request_count {
+            let key = format!("test_pipeline_key_{}", i);
+            requests.extend_from_slice(
+                format!("*3\r\n$3\r\nSET\r\n${}\r\n{}\r\n$1\r\n{}\r\n", key.len(), key, i % 10).as_bytes()
+            );
+        }
+        requests.extend_from_slice(b"*2\r\n$3\r\nGET\r\n$21\r\ntest_pipeline_missing\r\n");
+        requests.extend_from_slice(b"*2\r\n$3\r\nGET\r\n$21\r\ntest_pipeline_key_499\r\n");
+        client.write_all(&requests).await.unwrap();
+
+        let expected = "+OK\r\n".repeat(request_count) + "$-1\r\n$1\r\n9\r\n";
+        let response = read_exactly(&mut client, expected.len()).await;
+        assert_eq!(expected.as_bytes(), response.as_slice());
+    }
+
+    #[tokio::test]
+    async fn should_wait_for_request_split_across_writes() {
+        let mut client = connect().await;
+
+        client.write_all(b"*1\r\n$4\r\nPING\r\n*2\r\n$4\r\nECHO\r\n$5\r\nhel").await.unwrap();
+        assert_eq!(b"+PONG\r\n", read_exactly(&mut client, 7).await.as_slice());
+
+        client.write_all(b"lo\r\n").await.unwrap();
+        assert_eq!(b"$5\r\nhello\r\n", read_exactly(&mut client, 11).await.as_slice());
+    }
+
+    #[tokio::test]
+    async fn should_reply_with_protocol_error_before_closing_on_bad_request() {
+        let mut client = connect().await;
+
+        client.write_all(b"*1\r\n$4\r\nPING\r\nECHO \"bad\r\n").await.unwrap();
+
+        let mut response = vec![];
+        client.read_to_end(&mut response).await.unwrap();
+        assert_eq!(
+            "+PONG\r\n-ERR Protocol error: unbalanced quotes in request\r\n",
+            String::from_utf8_lossy(&response)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reply_with_protocol_error_to_oversized_inline_request() {
+        let mut client = connect().await;
+
+        // Just past the inline limit, so the server reads everything sent before closing
+        client.write_all(&vec![b'a'; 64 * 1024 + 1]).await.unwrap();
+
+        let mut response = vec![];
+        client.read_to_end(&mut response).await.unwrap();
+        assert_eq!(
+            "-ERR Protocol error: too big inline request\r\n",
+            String::from_utf8_lossy(&response)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reply_once_unblocked_then_handle_pipelined_requests() {
+        let mut client = connect().await;
+
+        client.write_all(b"*3\r\n$5\r\nBLPOP\r\n$22\r\ntest_server_block_list\r\n$1\r\n0\r\n").await.unwrap();
+        wait_blocked_on("test_server_block_list", 1).await;
+        client.write_all(b"*1\r\n$4\r\nPING\r\n").await.unwrap();
+
+        let mut pusher = connect().await;
+        pusher.write_all(b"*3\r\n$5\r\nRPUSH\r\n$22\r\ntest_server_block_list\r\n$1\r\na\r\n").await.unwrap();
+        assert_eq!(b":1\r\n", read_exactly(&mut pusher, 4).await.as_slice());
+
+        let expected = b"*2\r\n$22\r\ntest_server_block_list\r\n$1\r\na\r\n+PONG\r\n";
+        assert_eq!(expected, read_exactly(&mut client, expected.len()).await.as_slice());
+    }
+
+    #[tokio::test]
+    async fn should_reply_before_blocking() {
+        let mut client = connect().await;
+
+        client.write_all(b"*1\r\n$4\r\nPING\r\n*3\r\n$5\r\nBLPOP\r\n$24\r\ntest_server_pending_list\r\n$1\r\n0\r\n").await.unwrap();
+
+        let pong = tokio::time::timeout(std::time::Duration::from_secs(5), read_exactly(&mut client, 7)).await
+            .expect("earlier replies should not wait for the blocked request");
+        assert_eq!(b"+PONG\r\n", pong.as_slice());
+
+        let mut pusher = connect().await;
+        pusher.write_all(b"*3\r\n$5\r\nRPUSH\r\n$24\r\ntest_server_pending_list\r\n$1\r\na\r\n").await.unwrap();
+
+        let expected = b"*2\r\n$24\r\ntest_server_pending_list\r\n$1\r\na\r\n";
+        assert_eq!(expected, read_exactly(&mut client, expected.len()).await.as_slice());
+    }
+
+    #[tokio::test]
+    async fn should_unblock_client_that_disconnects() {
+        let mut client = connect().await;
+
+        client.write_all(b"*3\r\n$5\r\nBLPOP\r\n$24\r\ntest_server_abandon_list\r\n$1\r\n0\r\n").await.unwrap();
+        wait_blocked_on("test_server_abandon_list", 1).await;
+        drop(client);
+        wait_blocked_on("test_server_abandon_list", 0).await;
+
+        // The pushed element is left for other clients
+        let mut pusher = connect().await;
+        pusher.write_all(b"*3\r\n$5\r\nRPUSH\r\n$24\r\ntest_server_abandon_list\r\n$1\r\na\r\n").await.unwrap();
+        assert_eq!(b":1\r\n", read_exactly(&mut pusher, 4).await.as_slice());
+        pusher.write_all(b"*2\r\n$4\r\nLLEN\r\n$24\r\ntest_server_abandon_list\r\n").await.unwrap();
+        assert_eq!(b":1\r\n", read_exactly(&mut pusher, 4).await.as_slice());
+    }
+
+    #[tokio::test]
+    async fn should_disconnect_blocked_client_past_query_buffer_limit() {
+        let mut client = connect().await;
+
+        client.write_all(b"*3\r\n$5\r\nBLPOP\r\n$22\r\ntest_server_flood_list\r\n$1\r\n0\r\n").await.unwrap();
+        wait_blocked_on("test_server_flood_list", 1).await;
+
+        // The server may close before reading everything, failing the write
+        let _ = client.write_all(&vec![b'a'; MAX_QUERY_BUFFER_SIZE + 1]).await;
+        wait_blocked_on("test_server_flood_list", 0).await;
+
+        let mut response = vec![];
+        let _ = client.read_to_end(&mut response).await;
+        assert!(response.is_empty());
+    }
+}
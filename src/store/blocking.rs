@@ -0,0 +1,258 @@
+use std::{collections::{HashMap, VecDeque}, sync::Arc};
+
+use bytes::Bytes;
+use tokio::sync::oneshot;
+
+use crate::resp::command::CommandResult;
+
+use super::RedisStore;
+
+/**
+ * Retries a blocked command against the store
+ * Returns None while there is still nothing to serve the client with
+ */
+pub type BlockedAttempt = Arc<dyn Fn(&mut RedisStore) -> Option<Served> + Send + Sync>;
+
+/**
+ * Reply to a client served by an attempt, and how to undo what it did if the client can't receive it
+ */
+pub struct Served {
+    pub reply: CommandResult,
+    undo: Box<dyn FnOnce(&mut RedisStore) + Send>,
+}
+
+impl Served {
+    pub fn new(reply: CommandResult, undo: impl FnOnce(&mut RedisStore) + Send + 'static) -> Self {
+        Served { reply, undo: Box::new(undo) }
+    }
+}
+
+impl From<CommandResult> for Served {
+    /**
+     * Reply that changed nothing, so has nothing to undo
+     */
+    fn from(reply: CommandResult) -> Self {
+        Served::new(reply, |_| {})
+    }
+}
+
+/**
+ * Handle to a client blocked on keys, receiving the reply once served
+ */
+pub struct BlockedClient {
+    pub id: u64,
+    pub reply: oneshot::Receiver<CommandResult>,
+}
+
+struct Waiter {
+    keys: Vec<Bytes>,
+    attempt: BlockedAttempt,
+    reply: oneshot::Sender<CommandResult>,
+}
+
+/**
+ * Clients blocked on keys, served in the order they blocked
+ */
+#[derive(Default)]
+pub struct BlockedClients {
+    next_id: u64,
+    waiters: HashMap<u64, Waiter>,
+    // Waiter ids per key, oldest first
+    queues: HashMap<Bytes, VecDeque<u64>>,
+    // Keys that received values since clients were last served
+    ready: VecDeque<Bytes>,
+}
+
+impl BlockedClients {
+    fn block(&mut self, keys: Vec<Bytes>, attempt: BlockedAttempt) -> BlockedClient {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        for key in &keys {
+            self.queues.entry(key.clone()).or_default().push_back(id);
+        }
+
+        let (sender, reply) = oneshot::channel();
+        self.waiters.insert(id, Waiter { keys, attempt, reply: sender });
+        BlockedClient { id, reply }
+    }
+
+    fn unblock(&mut self, id: u64) -> Option<Waiter> {
+        let waiter = self.waiters.remove(&id)?;
+
+        for key in &waiter.keys {
+            if let Some(queue) = self.queues.get_mut(key) {
+                queue.retain(|&waiting| waiting != id);
+                if queue.is_empty() {
+                    self.queues.remove(key);
+                }
+            }
+        }
+        Some(waiter)
+    }
+
+    fn signal_ready(&mut self, key: &Bytes) {
+        if self.queues.contains_key(key) && !self.ready.contains(key) {
+            self.ready.push_back(key.clone());
+        }
+    }
+
+    fn first_waiting(&self, key: &Bytes) -> Option<u64> {
+        self.queues.get(key)?.front().copied()
+    }
+}
+
+impl RedisStore {
+    /**
+     * Parks a client on keys until a value pushed to one of them lets the attempt succeed
+     */
+    pub fn block(&mut self, keys: Vec<Bytes>, attempt: BlockedAttempt) -> BlockedClient {
+        self.blocked.block(keys, attempt)
+    }
+
+    /**
+     * Returns false if the client was already served, or never blocked
+     */
+    pub fn unblock(&mut self, id: u64) -> bool {
+        self.blocked.unblock(id).is_some()
+    }
+
+    /**
+     * Serves clients blocked on keys that received values, oldest first, for as long as each key can
+     * Serving a client may push to another key, which is served in turn
+     */
+    pub fn serve_blocked(&mut self) {
+        while let Some(key) = self.blocked.ready.pop_front() {
+            while let Some(id) = self.blocked.first_waiting(&key) {
+                // A client that went away before being unblocked would lose what it's served
+                if self.blocked.waiters[&id].reply.is_closed() {
+                    self.blocked.unblock(id);
+                    continue
+                }
+
+                let attempt = Arc::clone(&self.blocked.waiters[&id].attempt);
+                let Some(served) = attempt(self) else { break };
+
+                // It may still go away before the reply is sent, so what it was served goes to the next client
+                let waiter = self.blocked.unblock(id).unwrap();
+                if waiter.reply.send(served.reply).is_err() {
+                    (served.undo)(self);
+                }
+            }
+        }
+    }
+
+    /**
+     * Number of clients blocked on key
+     */
+    #[cfg(test)]
+    pub fn blocked_on(&self, key: &[u8]) -> usize {
+        self.blocked.queues.get(key).map_or(0, |queue| queue.len())
+    }
+
+    /**
+     * Marks key as having received values, if any client is blocked on it
+     */
+    pub(super) fn signal_ready(&mut self, key: &Bytes) {
+        self.blocked.signal_ready(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::resp::frame::RESPFrame;
+
+    fn pop_front(key: &'static str) -> BlockedAttempt {
+        Arc::new(move |store: &mut RedisStore| {
+            let key = Bytes::from(key);
+            let list = store.get_list_mut(&key).ok()??;
+            let element = list.pop_front()?;
+            if list.is_empty() {
+                store.del(&key);
+            }
+
+            let popped = element.clone();
+            Some(Served::new(Ok(RESPFrame::Bulk(element)), move |store| {
+                store.get_list_or_insert(&key).unwrap().push_front(popped);
+            }))
+        })
+    }
+
+    fn push(store: &mut RedisStore, key: &'static str, element: &'static str) {
+        store.get_list_or_insert(&Bytes::from(key)).unwrap().push_back(Bytes::from(element));
+    }
+
+    #[test]
+    fn should_serve_clients_in_order_they_blocked() {
+        let mut store = RedisStore::default();
+        let mut first = store.block(vec![Bytes::from("list")], pop_front("list"));
+        let mut second = store.block(vec![Bytes::from("list")], pop_front("list"));
+
+        push(&mut store, "list", "a");
+        store.serve_blocked();
+        assert_eq!(Ok(Ok(RESPFrame::Bulk(Bytes::from("a")))), first.reply.try_recv());
+        assert!(second.reply.try_recv().is_err());
+
+        push(&mut store, "list", "b");
+        store.serve_blocked();
+        assert_eq!(Ok(Ok(RESPFrame::Bulk(Bytes::from("b")))), second.reply.try_recv());
+        assert!(!store.unblock(first.id));
+        assert!(!store.unblock(second.id));
+    }
+
+    #[test]
+    fn should_skip_clients_that_went_away() {
+        let mut store = RedisStore::default();
+        let gone = store.block(vec![Bytes::from("list")], pop_front("list"));
+        let mut waiting = store.block(vec![Bytes::from("list")], pop_front("list"));
+        let gone_id = gone.id;
+        drop(gone);
+
+        push(&mut store, "list", "a");
+        store.serve_blocked();
+        assert_eq!(Ok(Ok(RESPFrame::Bulk(Bytes::from("a")))), waiting.reply.try_recv());
+        assert!(!store.unblock(gone_id));
+
+        // With nobody left waiting, a push is kept
+        let gone = store.block(vec![Bytes::from("other")], pop_front("other"));
+        drop(gone);
+        push(&mut store, "other", "b");
+        store.serve_blocked();
+        assert_eq!(1, store.get_list(&Bytes::from("other")).unwrap().unwrap().len());
+    }
+
+    /**
+     * Pops like pop_front, but the client goes away first, after serve_blocked checked it was still there
+     */
+    fn pop_front_going_away(key: &'static str, client: &Arc<Mutex<Option<BlockedClient>>>) -> BlockedAttempt {
+        let (client, pop) = (Arc::clone(client), pop_front(key));
+        Arc::new(move |store: &mut RedisStore| {
+            client.lock().unwrap().take();
+            pop(store)
+        })
+    }
+
+    #[test]
+    fn should_serve_next_client_if_reply_cannot_be_sent() {
+        let mut store = RedisStore::default();
+        let going: Arc<Mutex<Option<BlockedClient>>> = Arc::default();
+        let client = store.block(vec![Bytes::from("list")], pop_front_going_away("list", &going));
+        *going.lock().unwrap() = Some(client);
+        let mut waiting = store.block(vec![Bytes::from("list")], pop_front("list"));
+
+        push(&mut store, "list", "a");
+        store.serve_blocked();
+        assert_eq!(Ok(Ok(RESPFrame::Bulk(Bytes::from("a")))), waiting.reply.try_recv());
+        assert!(!store.exists(&Bytes::from("list")));
+
+        // With nobody left waiting, the element is put back
+        let client = store.block(vec![Bytes::from("list")], pop_front_going_away("list", &going));
+        *going.lock().unwrap() = Some(client);
+        push(&mut store, "list", "b");
+        store.serve_blocked();
+        assert_eq!(Some(&Bytes::from("b")), store.get_list(&Bytes::from("list")).unwrap().unwrap().get(0));
+    }
+}
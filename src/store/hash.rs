@@ -0,0 +1,350 @@
+use std::{collections::{hash_map::DefaultHasher, BTreeSet}, hash::{Hash, Hasher}, iter::FromIterator};
+
+use bytes::Bytes;
+use indexmap::IndexMap;
+use rand::{seq::index, Rng};
+
+use super::value::{LISTPACK_MAX_ENTRIES, LISTPACK_MAX_VALUE_LENGTH};
+
+/**
+ * Hash of fields to values
+ * Small hashes are kept as pairs in insertion order, and converted to a table once they outgrow it
+ */
+#[derive(Debug, Clone)]
+pub struct HashValue {
+    entries: HashEntries,
+}
+
+#[derive(Debug, Clone)]
+enum HashEntries {
+    Listpack(Vec<(Bytes, Bytes)>),
+    Table(HashTable),
+}
+
+#[derive(Debug, Clone, Default)]
+struct HashTable {
+    // Addressable by position too, so random fields are picked without walking the table
+    fields: IndexMap<Bytes, Bytes>,
+    // Fields ordered by scan hash, so a scan picks up from its cursor without sorting the table
+    scan_order: BTreeSet<(u64, Bytes)>,
+}
+
+impl Default for HashValue {
+    fn default() -> Self {
+        Self { entries: HashEntries::Listpack(Vec::new()) }
+    }
+}
+
+impl HashValue {
+    pub fn len(&self) -> usize {
+        match &self.entries {
+            HashEntries::Listpack(pairs) => pairs.len(),
+            HashEntries::Table(table) => table.fields.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /**
+     * Internal representation reported by OBJECT ENCODING
+     */
+    pub fn encoding(&self) -> &'static str {
+        match &self.entries {
+            HashEntries::Listpack(_) => "listpack",
+            HashEntries::Table(_) => "hashtable",
+        }
+    }
+
+    pub fn get(&self, field: &[u8]) -> Option<&Bytes> {
+        match &self.entries {
+            HashEntries::Listpack(pairs) => pairs.iter()
+                .find(|(existing, _)| existing == field)
+                .map(|(_, value)| value),
+            HashEntries::Table(table) => table.fields.get(field),
+        }
+    }
+
+    /**
+     * Sets field to value, returning whether the field is new
+     * Converts to a table past the listpack limits, and never back
+     */
+    pub fn insert(&mut self, field: Bytes, value: Bytes) -> bool {
+        let oversized = field.len() > LISTPACK_MAX_VALUE_LENGTH || value.len() > LISTPACK_MAX_VALUE_LENGTH;
+
+        let added = match &mut self.entries {
+            HashEntries::Listpack(pairs) => match pairs.iter_mut().find(|(existing, _)| *existing == field) {
+                Some((_, existing)) => {
+                    *existing = value;
+                    false
+                },
+                None => {
+                    pairs.push((field, value));
+                    true
+                }
+            },
+            HashEntries::Table(table) => table.insert(field, value),
+        };
+
+        if let HashEntries::Listpack(pairs) = &mut self.entries {
+            if oversized || pairs.len() > LISTPACK_MAX_ENTRIES {
+                self.entries = HashEntries::Table(pairs.drain(..).collect());
+            }
+        }
+        added
+    }
+
+    /**
+     * Returns whether the field existed
+     */
+    pub fn remove(&mut self, field: &[u8]) -> bool {
+        match &mut self.entries {
+            HashEntries::Listpack(pairs) => match pairs.iter().position(|(existing, _)| existing == field) {
+                Some(index) => {
+                    pairs.remove(index);
+                    true
+                },
+                None => false,
+            },
+            HashEntries::Table(table) => table.remove(field),
+        }
+    }
+
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (&Bytes, &Bytes)> + '_> {
+        match &self.entries {
+            HashEntries::Listpack(pairs) => Box::new(pairs.iter().map(|(field, value)| (field, value))),
+            HashEntries::Table(table) => Box::new(table.fields.iter()),
+        }
+    }
+
+    /**
+     * Up to count entries from cursor, along with the cursor to continue from, which is 0 once done
+     * A listpack is returned whole. A table is walked in order of a fixed hash of each field,
+     * so fields present for the whole scan are returned exactly once, whatever else changes
+     */
+    pub fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<(&Bytes, &Bytes)>) {
+        let HashEntries::Table(table) = &self.entries else {
+            return (0, self.iter().collect())
+        };
+
+        let mut entries = Vec::with_capacity(count.min(table.fields.len()));
+        let mut last_hash = None;
+        for (hash, field) in table.scan_order.range((cursor, Bytes::new())..) {
+            // Fields sharing a hash are returned together, as the cursor can't split them
+            if entries.len() >= count && last_hash != Some(*hash) {
+                return (*hash, entries)
+            }
+
+            entries.push((field, &table.fields[field]));
+            last_hash = Some(*hash);
+        }
+        (0, entries)
+    }
+
+    /**
+     * Random entries, either count distinct ones or count picks that may repeat
+     */
+    pub fn random_entries(&self, count: usize, distinct: bool) -> Vec<(&Bytes, &Bytes)> {
+        let len = self.len();
+        if len == 0 {
+            return vec![]
+        }
+
+        let mut rng = rand::thread_rng();
+        if distinct {
+            index::sample(&mut rng, len, count.min(len)).into_iter().map(|i| self.entry_at(i)).collect()
+        } else {
+            (0..count).map(|_| self.entry_at(rng.gen_range(0..len))).collect()
+        }
+    }
+
+    fn entry_at(&self, index: usize) -> (&Bytes, &Bytes) {
+        match &self.entries {
+            HashEntries::Listpack(pairs) => {
+                let (field, value) = &pairs[index];
+                (field, value)
+            },
+            HashEntries::Table(table) => table.fields.get_index(index).unwrap(),
+        }
+    }
+}
+
+impl PartialEq for HashValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(field, value)| other.get(field) == Some(value))
+    }
+}
+
+impl FromIterator<(Bytes, Bytes)> for HashValue {
+    fn from_iter<I: IntoIterator<Item = (Bytes, Bytes)>>(iter: I) -> Self {
+        let mut hash = HashValue::default();
+        for (field, value) in iter {
+            hash.insert(field, value);
+        }
+        hash
+    }
+}
+
+impl HashTable {
+    fn insert(&mut self, field: Bytes, value: Bytes) -> bool {
+        let added = self.fields.insert(field.clone(), value).is_none();
+        if added {
+            self.scan_order.insert((scan_hash(&field), field));
+        }
+        added
+    }
+
+    fn remove(&mut self, field: &[u8]) -> bool {
+        let Some((field, _)) = self.fields.swap_remove_entry(field) else {
+            return false
+        };
+        self.scan_order.remove(&(scan_hash(&field), field));
+        true
+    }
+}
+
+impl FromIterator<(Bytes, Bytes)> for HashTable {
+    fn from_iter<I: IntoIterator<Item = (Bytes, Bytes)>>(iter: I) -> Self {
+        let mut table = HashTable::default();
+        for (field, value) in iter {
+            table.insert(field, value);
+        }
+        table
+    }
+}
+
+/**
+ * Hash that is the same for a field across calls, unlike the table's own
+ */
+fn scan_hash(field: &Bytes) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    field.hash(&mut hasher);
+    hasher.finish()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn pair(field: &str, value: &str) -> (Bytes, Bytes) {
+        (Bytes::from(field.to_owned()), Bytes::from(value.to_owned()))
+    }
+
+    fn large_hash(entries: usize) -> HashValue {
+        (0..entries).map(|i| pair(&format!("field{}", i), &i.to_string())).collect()
+    }
+
+    #[test]
+    fn should_insert_get_and_remove() {
+        let mut hash = HashValue::default();
+
+        assert!(hash.insert(Bytes::from("a"), Bytes::from("1")));
+        assert!(hash.insert(Bytes::from("b"), Bytes::from("2")));
+        assert!(!hash.insert(Bytes::from("a"), Bytes::from("3")));
+
+        assert_eq!(2, hash.len());
+        assert_eq!(Some(&Bytes::from("3")), hash.get(b"a"));
+        assert!(hash.remove(b"a"));
+        assert!(!hash.remove(b"a"));
+        assert_eq!(None, hash.get(b"a"));
+        assert_eq!(vec![(&Bytes::from("b"), &Bytes::from("2"))], hash.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn should_convert_to_table_past_listpack_limits() {
+        let mut hash = large_hash(128);
+        assert_eq!("listpack", hash.encoding());
+
+        hash.insert(Bytes::from("one more"), Bytes::from("value"));
+        assert_eq!("hashtable", hash.encoding());
+        assert_eq!(129, hash.len());
+
+        // Never converted back
+        hash.remove(b"one more");
+        assert_eq!("hashtable", hash.encoding());
+
+        let mut hash: HashValue = vec![pair("field", "value")].into_iter().collect();
+        hash.insert(Bytes::from("long"), Bytes::from("a".repeat(65)));
+        assert_eq!("hashtable", hash.encoding());
+        assert_eq!(Some(&Bytes::from("value")), hash.get(b"field"));
+    }
+
+    #[test]
+    fn should_compare_regardless_of_encoding_and_order() {
+        let listpack: HashValue = vec![pair("a", "1"), pair("b", "2")].into_iter().collect();
+        let mut table = large_hash(200);
+        (0..200).for_each(|i| { table.remove(format!("field{}", i).as_bytes()); });
+        table.insert(Bytes::from("b"), Bytes::from("2"));
+        table.insert(Bytes::from("a"), Bytes::from("1"));
+
+        assert_eq!(listpack, table);
+        table.insert(Bytes::from("a"), Bytes::from("other"));
+        assert_ne!(listpack, table);
+    }
+
+    #[test]
+    fn scan_should_return_every_field_once() {
+        let hash = large_hash(1000);
+
+        let mut seen = HashSet::new();
+        let mut cursor = 0;
+        loop {
+            let (next, entries) = hash.scan(cursor, 10);
+            assert!(entries.len() >= 10 || next == 0);
+            for (field, _) in entries {
+                assert!(seen.insert(field.clone()));
+            }
+
+            if next == 0 { break }
+            cursor = next;
+        }
+        assert_eq!(1000, seen.len());
+    }
+
+    #[test]
+    fn scan_should_skip_fields_removed_meanwhile() {
+        let mut hash = large_hash(1000);
+
+        let mut seen = HashSet::new();
+        let (mut cursor, entries) = hash.scan(0, 100);
+        seen.extend(entries.into_iter().map(|(field, _)| field.clone()));
+        // Every other field is removed part way, and as many added back under other names
+        for i in (0..1000).step_by(2) {
+            hash.remove(format!("field{}", i).as_bytes());
+            hash.insert(Bytes::from(format!("added{}", i)), Bytes::from("value"));
+        }
+
+        while cursor != 0 {
+            let (next, entries) = hash.scan(cursor, 100);
+            for (field, value) in entries {
+                assert_eq!(hash.get(field), Some(value));
+                assert!(seen.insert(field.clone()));
+            }
+            cursor = next;
+        }
+        assert!((1..1000).step_by(2).all(|i| seen.contains(format!("field{}", i).as_bytes())));
+    }
+
+    #[test]
+    fn scan_should_return_listpack_whole() {
+        let hash = large_hash(5);
+
+        let (cursor, entries) = hash.scan(0, 1);
+        assert_eq!(0, cursor);
+        assert_eq!(5, entries.len());
+    }
+
+    #[test]
+    fn should_pick_random_entries() {
+        let hash = large_hash(10);
+
+        let distinct = hash.random_entries(20, true);
+        assert_eq!(10, distinct.iter().map(|(field, _)| *field).collect::<HashSet<_>>().len());
+        assert_eq!(3, hash.random_entries(3, true).len());
+        assert_eq!(20, hash.random_entries(20, false).len());
+        assert!(HashValue::default().random_entries(5, false).is_empty());
+    }
+}
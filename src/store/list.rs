@@ -0,0 +1,435 @@
+use std::{collections::VecDeque, iter::FromIterator};
+
+use bytes::Bytes;
+
+// Nodes are kept to the size of a listpack, unless they hold a single larger element
+const NODE_MAX_BYTES: usize = 8192;
+
+/**
+ * Run of consecutive list elements, with their total size
+ */
+#[derive(Debug, Clone, Default)]
+struct ListNode {
+    entries: VecDeque<Bytes>,
+    bytes: usize,
+}
+
+impl ListNode {
+    fn fits(&self, value: &Bytes) -> bool {
+        self.entries.is_empty() || self.bytes + value.len() <= NODE_MAX_BYTES
+    }
+}
+
+/**
+ * List as a deque of small nodes, like the Redis quicklist
+ * Both ends are cheap to push and pop, and indexing skips whole nodes
+ */
+#[derive(Debug, Clone, Default)]
+pub struct QuickList {
+    nodes: VecDeque<ListNode>,
+    len: usize,
+}
+
+impl QuickList {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /**
+     * A list small enough for a single node is reported as a listpack
+     */
+    pub fn encoding(&self) -> &'static str {
+        match self.nodes.front() {
+            Some(node) if self.nodes.len() > 1 || node.bytes > NODE_MAX_BYTES => "quicklist",
+            _ => "listpack",
+        }
+    }
+
+    pub fn push_front(&mut self, value: Bytes) {
+        if !self.nodes.front().is_some_and(|node| node.fits(&value)) {
+            self.nodes.push_front(ListNode::default());
+        }
+
+        let node = self.nodes.front_mut().unwrap();
+        node.bytes += value.len();
+        node.entries.push_front(value);
+        self.len += 1;
+    }
+
+    pub fn push_back(&mut self, value: Bytes) {
+        if !self.nodes.back().is_some_and(|node| node.fits(&value)) {
+            self.nodes.push_back(ListNode::default());
+        }
+
+        let node = self.nodes.back_mut().unwrap();
+        node.bytes += value.len();
+        node.entries.push_back(value);
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<Bytes> {
+        let node = self.nodes.front_mut()?;
+        let value = node.entries.pop_front()?;
+        node.bytes -= value.len();
+        self.len -= 1;
+
+        if node.entries.is_empty() {
+            self.nodes.pop_front();
+        } else {
+            self.merge_nodes(0);
+        }
+        Some(value)
+    }
+
+    pub fn pop_back(&mut self) -> Option<Bytes> {
+        let node = self.nodes.back_mut()?;
+        let value = node.entries.pop_back()?;
+        node.bytes -= value.len();
+        self.len -= 1;
+
+        if node.entries.is_empty() {
+            self.nodes.pop_back();
+        } else {
+            self.merge_nodes(self.nodes.len().saturating_sub(2));
+        }
+        Some(value)
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Bytes> {
+        let (node, offset) = self.locate(index)?;
+        self.nodes[node].entries.get(offset)
+    }
+
+    /**
+     * Replaces the element at index, returning false if it's out of range
+     */
+    pub fn set(&mut self, index: usize, value: Bytes) -> bool {
+        let Some((node, offset)) = self.locate(index) else {
+            return false
+        };
+
+        let node = &mut self.nodes[node];
+        node.bytes = node.bytes - node.entries[offset].len() + value.len();
+        node.entries[offset] = value;
+        true
+    }
+
+    /**
+     * Inserts before the element at index, or at the back if index is the length
+     * A node that grows too large is split in half
+     */
+    pub fn insert(&mut self, index: usize, value: Bytes) {
+        if index == self.len {
+            return self.push_back(value)
+        }
+        let Some((node_index, offset)) = self.locate(index) else {
+            return
+        };
+
+        let node = &mut self.nodes[node_index];
+        node.bytes += value.len();
+        node.entries.insert(offset, value);
+        self.len += 1;
+
+        if node.bytes > NODE_MAX_BYTES && node.entries.len() > 1 {
+            let entries = node.entries.split_off(node.entries.len() / 2);
+            let bytes = entries.iter().map(Bytes::len).sum();
+            node.bytes -= bytes;
+            self.nodes.insert(node_index + 1, ListNode { entries, bytes });
+        }
+    }
+
+    /**
+     * Removes up to limit elements equal to value, scanning from the back if reverse
+     * Returns the number removed
+     */
+    pub fn remove_matching(&mut self, value: &[u8], limit: usize, reverse: bool) -> usize {
+        let mut removed = 0;
+        let node_indexes: Vec<usize> = if reverse {
+            (0..self.nodes.len()).rev().collect()
+        } else {
+            (0..self.nodes.len()).collect()
+        };
+
+        for node_index in node_indexes {
+            if removed == limit {
+                break
+            }
+
+            let node = &mut self.nodes[node_index];
+            let mut matches: Vec<usize> = (0..node.entries.len())
+                .filter(|&offset| node.entries[offset] == value)
+                .collect();
+            if reverse {
+                matches.reverse();
+            }
+            matches.truncate(limit - removed);
+            matches.sort_unstable_by(|a, b| b.cmp(a));
+
+            for offset in &matches {
+                let entry = node.entries.remove(*offset).unwrap();
+                node.bytes -= entry.len();
+            }
+            removed += matches.len();
+        }
+
+        self.len -= removed;
+        self.nodes.retain(|node| !node.entries.is_empty());
+        self.merge_all_nodes();
+        removed
+    }
+
+    /**
+     * Keeps only the elements in the inclusive range, or none if it's None
+     */
+    pub fn trim(&mut self, range: Option<(usize, usize)>) {
+        let (start, end) = range.unwrap_or((self.len, self.len));
+
+        for _ in 0..self.len.saturating_sub(end + 1) {
+            self.pop_back();
+        }
+        for _ in 0..start.min(self.len) {
+            self.pop_front();
+        }
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Bytes> {
+        self.nodes.iter().flat_map(|node| node.entries.iter())
+    }
+
+    /**
+     * Elements from index onwards, skipping whole nodes to reach it
+     */
+    pub fn iter_from(&self, index: usize) -> impl Iterator<Item = &Bytes> {
+        let (node, offset) = self.locate(index).unwrap_or((self.nodes.len(), 0));
+
+        self.nodes.iter().skip(node)
+            .flat_map(|node| node.entries.iter())
+            .skip(offset)
+    }
+
+    /**
+     * Node and offset within it of the element at index, walking from the nearest end
+     */
+    fn locate(&self, index: usize) -> Option<(usize, usize)> {
+        if index >= self.len {
+            return None
+        }
+
+        if index < self.len / 2 {
+            let mut remaining = index;
+            for (node_index, node) in self.nodes.iter().enumerate() {
+                if remaining < node.entries.len() {
+                    return Some((node_index, remaining))
+                }
+                remaining -= node.entries.len();
+            }
+        } else {
+            let mut remaining = self.len - 1 - index;
+            for (node_index, node) in self.nodes.iter().enumerate().rev() {
+                if remaining < node.entries.len() {
+                    return Some((node_index, node.entries.len() - 1 - remaining))
+                }
+                remaining -= node.entries.len();
+            }
+        }
+        None
+    }
+
+    /**
+     * Merges the node at index with the next one, if they fit in a single node
+     */
+    fn merge_nodes(&mut self, index: usize) {
+        let (Some(node), Some(next)) = (self.nodes.get(index), self.nodes.get(index + 1)) else {
+            return
+        };
+        if node.bytes + next.bytes > NODE_MAX_BYTES {
+            return
+        }
+
+        let next = self.nodes.remove(index + 1).unwrap();
+        let node = &mut self.nodes[index];
+        node.bytes += next.bytes;
+        node.entries.extend(next.entries);
+    }
+
+    fn merge_all_nodes(&mut self) {
+        let mut index = 0;
+        while index + 1 < self.nodes.len() {
+            let before = self.nodes.len();
+            self.merge_nodes(index);
+            if self.nodes.len() == before {
+                index += 1;
+            }
+        }
+    }
+}
+
+impl PartialEq for QuickList {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl FromIterator<Bytes> for QuickList {
+    fn from_iter<I: IntoIterator<Item = Bytes>>(values: I) -> Self {
+        let mut list = QuickList::default();
+        for value in values {
+            list.push_back(value);
+        }
+        list
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn element(i: usize) -> Bytes {
+        Bytes::from(format!("{:0>100}", i))
+    }
+
+    fn assert_consistent(list: &QuickList) {
+        assert_eq!(list.len, list.nodes.iter().map(|node| node.entries.len()).sum::<usize>());
+        for node in &list.nodes {
+            assert!(!node.entries.is_empty());
+            assert_eq!(node.bytes, node.entries.iter().map(Bytes::len).sum::<usize>());
+            assert!(node.bytes <= NODE_MAX_BYTES || node.entries.len() == 1);
+        }
+    }
+
+    #[test]
+    fn should_push_and_pop_at_both_ends() {
+        let mut list = QuickList::default();
+        for i in 0..500 {
+            list.push_back(element(i));
+            list.push_front(element(1000 + i));
+        }
+
+        assert_eq!(1000, list.len());
+        assert!(list.nodes.len() > 1);
+        assert_eq!("quicklist", list.encoding());
+        assert_consistent(&list);
+
+        assert_eq!(Some(element(1499)), list.pop_front());
+        assert_eq!(Some(element(499)), list.pop_back());
+        assert_eq!(Some(&element(1000)), list.get(498));
+        assert_eq!(Some(&element(0)), list.get(499));
+
+        while list.len() > 2 {
+            list.pop_front();
+            assert_consistent(&list);
+        }
+        assert_eq!(1, list.nodes.len());
+        assert_eq!("listpack", list.encoding());
+
+        list.pop_back();
+        list.pop_back();
+        assert!(list.is_empty());
+        assert_eq!(None, list.pop_front());
+        assert_eq!(None, list.pop_back());
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(81)]
+    #[case(82)]
+    #[case(250)]
+    #[case(499)]
+    #[case(500)]
+    fn should_insert_and_index_across_nodes(#[case] index: usize) {
+        let mut list: QuickList = (0..500).map(element).collect();
+        let mut expected: Vec<Bytes> = (0..500).map(element).collect();
+
+        list.insert(index, Bytes::from("inserted"));
+        expected.insert(index, Bytes::from("inserted"));
+
+        assert_consistent(&list);
+        assert!(list.iter().eq(expected.iter()));
+        assert!(list.iter().rev().eq(expected.iter().rev()));
+        assert!(list.iter_from(index).eq(expected[index..].iter()));
+        assert_eq!(Some(&expected[index]), list.get(index));
+        assert_eq!(None, list.get(501));
+    }
+
+    #[test]
+    fn should_split_node_that_grows_too_large() {
+        let mut list: QuickList = (0..81).map(element).collect();
+        assert_eq!(1, list.nodes.len());
+
+        for _ in 0..10 {
+            list.insert(40, element(1000));
+        }
+
+        assert!(list.nodes.len() > 1);
+        assert_consistent(&list);
+    }
+
+    #[test]
+    fn should_set_element() {
+        let mut list: QuickList = (0..200).map(element).collect();
+
+        assert!(list.set(150, Bytes::from("replaced")));
+        assert!(!list.set(200, Bytes::from("missing")));
+
+        assert_eq!(Some(&Bytes::from("replaced")), list.get(150));
+        assert_consistent(&list);
+    }
+
+    #[rstest]
+    #[case(usize::MAX, false)]
+    #[case(3, false)]
+    #[case(3, true)]
+    #[case(0, true)]
+    fn should_remove_matching_elements(#[case] limit: usize, #[case] reverse: bool) {
+        // Every tenth element matches, across several nodes
+        let values: Vec<Bytes> = (0..200).map(|i| if i % 10 == 1 { Bytes::from("match") } else { element(i) }).collect();
+        let mut list: QuickList = values.iter().cloned().collect();
+
+        let mut matches: Vec<usize> = (0..200).filter(|i| i % 10 == 1).collect();
+        if reverse {
+            matches.reverse();
+        }
+        matches.truncate(limit);
+        let expected: Vec<&Bytes> = values.iter().enumerate()
+            .filter(|(i, _)| !matches.contains(i))
+            .map(|(_, value)| value)
+            .collect();
+
+        assert_eq!(matches.len(), list.remove_matching(b"match", limit, reverse));
+        assert!(list.iter().eq(expected));
+        assert_consistent(&list);
+    }
+
+    #[rstest]
+    #[case(Some((10, 19)), 10, Some(10))]
+    #[case(Some((0, 499)), 500, Some(0))]
+    #[case(Some((499, 499)), 1, Some(499))]
+    #[case(None, 0, None)]
+    fn should_trim_to_range(#[case] range: Option<(usize, usize)>, #[case] expected_len: usize, #[case] expected_first: Option<usize>) {
+        let mut list: QuickList = (0..500).map(element).collect();
+
+        list.trim(range);
+
+        assert_eq!(expected_len, list.len());
+        assert_eq!(expected_first.map(element).as_ref(), list.get(0));
+        assert_consistent(&list);
+    }
+
+    #[test]
+    fn should_compare_by_elements() {
+        let mut pushed_front = QuickList::default();
+        for i in (0..300).rev() {
+            pushed_front.push_front(element(i));
+        }
+
+        assert_eq!((0..300).map(element).collect::<QuickList>(), pushed_front);
+        assert_ne!((0..299).map(element).collect::<QuickList>(), pushed_front);
+    }
+}
@@ -0,0 +1,771 @@
+use std::{borrow::Cow, collections::{BTreeSet, HashMap}, sync::{Arc, OnceLock}, time::{Duration, Instant, UNIX_EPOCH}};
+
+use bytes::{Bytes, BytesMut};
+use indexmap::IndexMap;
+use rand::Rng;
+use tokio::sync::Mutex;
+
+use crate::{
+    resp::command::{canonical_float, canonical_integer, SetCommandFlags, SetCommandExistFlag, ExpireCommandFlags, ExpireCommandExistFlag, ExpireCommandCompareFlag},
+    clock::Clock,
+    error::RedisError
+};
+
+mod blocking;
+mod hash;
+mod list;
+mod value;
+
+pub use blocking::{BlockedAttempt, BlockedClient, Served};
+pub use hash::HashValue;
+pub use list::QuickList;
+pub use value::{RedisValue, StringValue};
+
+pub type EpochMillisecond = u64;
+
+/**
+ * Expiry state of a key
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyExpiry {
+    Missing,
+    Persistent,
+    At(EpochMillisecond),
+}
+
+const ACTIVE_EXPIRE_INTERVAL: Duration = Duration::from_millis(100);
+// Limits how long each cycle holds the store lock
+const ACTIVE_EXPIRE_TIME_BUDGET: Duration = Duration::from_millis(25);
+
+/**
+ * Storage implementation for Redis
+ * In-memory implementation
+ */
+pub struct RedisStore {
+    // Addressable by position too, so a random key is picked in constant time
+    store: IndexMap<Bytes, RedisValue>,
+    ttl_store: HashMap<Bytes, EpochMillisecond>,
+    // Keys with a TTL ordered by deadline, kept in sync with ttl_store
+    expiry_index: BTreeSet<(EpochMillisecond, Bytes)>,
+    blocked: blocking::BlockedClients,
+}
+
+type SharedRedisStore = Arc<Mutex<RedisStore>>;
+static SHARED_STORE: OnceLock<SharedRedisStore> = OnceLock::new();
+
+impl RedisStore {
+    pub fn init() {
+        // Static store can only initialise once, from this method only
+        SHARED_STORE.get_or_init(|| Arc::new(Mutex::new(RedisStore::default())));
+    }
+
+    fn default() -> Self {
+        Self {
+            store: IndexMap::new(),
+            ttl_store: HashMap::new(),
+            expiry_index: BTreeSet::new(),
+            blocked: blocking::BlockedClients::default(),
+        }
+    }
+    
+    pub fn get_shared_store() -> SharedRedisStore {
+        // Static store is protected behind a thread-safe reference
+        // It can not give any references to shared store until it is initialised
+        Arc::clone(SHARED_STORE.get_or_init(|| Arc::new(Mutex::new(RedisStore::default()))))
+    }
+    
+    /**
+     * Periodically removes expired keys that are never read
+     */
+    pub async fn active_expire_loop() {
+        let mut interval = tokio::time::interval(ACTIVE_EXPIRE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let expired = Self::get_shared_store().lock().await.active_expire_cycle();
+            if expired > 0 {
+                println!("Active expiry removed {} keys", expired);
+            }
+        }
+    }
+
+    /**
+     * Live value at key, of any type
+     */
+    pub fn value(&mut self, key: &Bytes) -> Option<&RedisValue> {
+        if self.try_expire(key) { return None }
+
+        self.store.get(key)
+    }
+
+    pub fn value_mut(&mut self, key: &Bytes) -> Option<&mut RedisValue> {
+        if self.try_expire(key) { return None }
+
+        self.store.get_mut(key)
+    }
+
+    /**
+     * Stores a value of any type at key, replacing any value and TTL
+     */
+    pub fn insert_value(&mut self, key: &Bytes, value: RedisValue) {
+        self.remove_ttl(key);
+        self.store.insert(key.clone(), value);
+        self.signal_ready(key);
+    }
+
+    /**
+     * String value at key, failing if it holds another type
+     */
+    pub fn get(&mut self, key: &Bytes) -> Result<Option<Bytes>, RedisError> {
+        println!("Get: {:?}", key);
+
+        Ok(self.get_string(key)?.map(StringValue::to_bytes))
+    }
+
+    /**
+     * Bytes of the string at key without copying a raw string, failing if it holds another type
+     */
+    pub fn get_raw(&mut self, key: &Bytes) -> Result<Option<Cow<'_, [u8]>>, RedisError> {
+        Ok(self.get_string(key)?.map(StringValue::as_bytes))
+    }
+
+    /**
+     * Buffer of the string at key to edit in place, creating an empty one if missing
+     * Keeps any TTL
+     */
+    pub fn get_string_buffer_mut(&mut self, key: &Bytes) -> Result<&mut BytesMut, RedisError> {
+        if !self.exists(key) {
+            self.store.insert(key.clone(), RedisValue::String(StringValue::Raw(BytesMut::new())));
+        }
+
+        Ok(self.store.get_mut(key).unwrap().as_string_mut()?.make_raw())
+    }
+
+    /**
+     * List at key, failing if it holds another type
+     */
+    pub fn get_list(&mut self, key: &Bytes) -> Result<Option<&QuickList>, RedisError> {
+        self.value(key).map(RedisValue::as_list).transpose()
+    }
+
+    pub fn get_list_mut(&mut self, key: &Bytes) -> Result<Option<&mut QuickList>, RedisError> {
+        self.value_mut(key).map(RedisValue::as_list_mut).transpose()
+    }
+
+    /**
+     * List at key, creating an empty one if missing
+     * Callers must not leave a created list empty, as clients blocked on key are served after the command
+     */
+    pub fn get_list_or_insert(&mut self, key: &Bytes) -> Result<&mut QuickList, RedisError> {
+        if !self.exists(key) {
+            self.insert_value(key, RedisValue::List(QuickList::default()));
+        }
+
+        self.store.get_mut(key).unwrap().as_list_mut()
+    }
+
+    /**
+     * Hash at key, failing if it holds another type
+     */
+    pub fn get_hash(&mut self, key: &Bytes) -> Result<Option<&HashValue>, RedisError> {
+        self.value(key).map(RedisValue::as_hash).transpose()
+    }
+
+    pub fn get_hash_mut(&mut self, key: &Bytes) -> Result<Option<&mut HashValue>, RedisError> {
+        self.value_mut(key).map(RedisValue::as_hash_mut).transpose()
+    }
+
+    /**
+     * Hash at key, creating an empty one if missing
+     * Callers must not leave a created hash empty
+     */
+    pub fn get_hash_or_insert(&mut self, key: &Bytes) -> Result<&mut HashValue, RedisError> {
+        if !self.exists(key) {
+            self.insert_value(key, RedisValue::Hash(HashValue::default()));
+        }
+
+        self.store.get_mut(key).unwrap().as_hash_mut()
+    }
+
+    /**
+     * Returns whether key was set successfully
+     */
+    pub fn set(&mut self, key: &Bytes, value: &Bytes, flags: &SetCommandFlags) -> bool {
+        println!("Set: {:?}, {:?}", key, value);
+
+        if let Some(exist_flag) = &flags.exist_flag {
+            if let (SetCommandExistFlag::NX, true)
+                | (SetCommandExistFlag::XX, false) = (exist_flag, self.exists(key)) {
+                return false
+            }
+        }
+        
+        if let Some(ttl_flag) = &flags.ttl_flag {
+            let maybe_ttl = ttl_flag.deadline(Self::get_unix_time());
+
+            if let Some(ttl) = maybe_ttl {
+                println!("Setting TTL for {:?}: {}", key, ttl);
+                self.set_ttl(key, ttl);
+            } else {
+                println!("Keeping existing TTL");
+            }
+        } else {
+            self.remove_ttl(key);
+        }
+
+        self.store.insert(key.clone(), RedisValue::String(StringValue::from_bytes(value)));
+        true
+    }
+
+    /**
+     * Adds to the integer value at key, treating a missing key as 0
+     * Keeps any TTL, and returns the new value
+     */
+    pub fn incr_by(&mut self, key: &Bytes, delta: i64) -> Result<i64, RedisError> {
+        let current = match self.get_string(key)? {
+            None => 0,
+            Some(StringValue::Int(n)) => *n,
+            Some(StringValue::Raw(bytes)) => canonical_integer(bytes).ok_or(RedisError::NotInteger)?,
+        };
+        let value = current.checked_add(delta).ok_or(RedisError::Overflow)?;
+
+        self.store.insert(key.clone(), RedisValue::String(StringValue::Int(value)));
+        Ok(value)
+    }
+
+    /**
+     * Adds to the float value at key, treating a missing key as 0
+     * Keeps any TTL, and returns the new value as stored
+     */
+    pub fn incr_by_float(&mut self, key: &Bytes, delta: f64) -> Result<Bytes, RedisError> {
+        let current = match self.get_string(key)? {
+            None => 0.0,
+            Some(StringValue::Int(n)) => *n as f64,
+            Some(StringValue::Raw(bytes)) => canonical_float(bytes).ok_or(RedisError::NotFloat)?,
+        };
+        let value = current + delta;
+        if !value.is_finite() {
+            return Err(RedisError::Custom("increment would produce NaN or Infinity".to_owned()))
+        }
+
+        let value = Bytes::from(value.to_string());
+        self.store.insert(key.clone(), RedisValue::String(StringValue::from_bytes(&value)));
+        Ok(value)
+    }
+
+    /**
+     * Appends to the value at key, creating it if missing
+     * Keeps any TTL, and returns the new length
+     */
+    pub fn append(&mut self, key: &Bytes, value: &Bytes) -> Result<usize, RedisError> {
+        if self.get_string(key)?.is_none() {
+            self.store.insert(key.clone(), RedisValue::String(StringValue::from_bytes(value)));
+            return Ok(value.len())
+        }
+
+        let buffer = self.get_string_buffer_mut(key)?;
+        buffer.extend_from_slice(value);
+        Ok(buffer.len())
+    }
+
+    pub fn strlen(&mut self, key: &Bytes) -> Result<usize, RedisError> {
+        Ok(match self.get_string(key)? {
+            None => 0,
+            Some(StringValue::Int(n)) => n.to_string().len(),
+            Some(StringValue::Raw(bytes)) => bytes.len(),
+        })
+    }
+
+    /**
+     * Overwrites part of the value at key from offset, zero padding any gap
+     * Keeps any TTL, and returns the new length
+     */
+    pub fn setrange(&mut self, key: &Bytes, offset: usize, value: &Bytes) -> Result<usize, RedisError> {
+        let buffer = self.get_string_buffer_mut(key)?;
+        if buffer.len() < offset + value.len() {
+            buffer.resize(offset + value.len(), 0);
+        }
+        buffer[offset..offset + value.len()].copy_from_slice(value);
+
+        Ok(buffer.len())
+    }
+
+    /**
+     * Returns whether key existed before it was deleted
+     */
+    pub fn del(&mut self, key: &Bytes) -> bool {
+        println!("Del: {:?}", key);
+        if self.try_expire(key) { return false }
+
+        self.remove_ttl(key);
+        self.store.swap_remove(key).is_some()
+    }
+
+    pub fn exists(&mut self, key: &Bytes) -> bool {
+        if self.try_expire(key) { return false }
+
+        self.store.contains_key(key)
+    }
+
+    /**
+     * Type name of the value held at key, or "none" if missing
+     */
+    pub fn type_name(&mut self, key: &Bytes) -> &'static str {
+        self.value(key).map_or("none", RedisValue::type_name)
+    }
+
+    /**
+     * Encoding of the value held at key, or None if missing
+     */
+    pub fn encoding(&mut self, key: &Bytes) -> Option<&'static str> {
+        self.value(key).map(RedisValue::encoding)
+    }
+
+    /**
+     * Moves value and TTL to new key, overwriting it
+     * Returns false if key doesn't exist
+     */
+    pub fn rename(&mut self, key: &Bytes, new_key: &Bytes) -> bool {
+        println!("Rename: {:?}, {:?}", key, new_key);
+        if self.try_expire(key) { return false }
+
+        let Some(value) = self.store.swap_remove(key) else { return false };
+        let ttl = self.remove_ttl(key);
+
+        self.remove_ttl(new_key);
+        if let Some(ttl) = ttl {
+            self.set_ttl(new_key, ttl);
+        }
+        self.store.insert(new_key.clone(), value);
+        self.signal_ready(new_key);
+        true
+    }
+
+    /**
+     * Copies value and TTL to destination
+     * Returns false if source doesn't exist, or destination exists without replace
+     */
+    pub fn copy(&mut self, source: &Bytes, destination: &Bytes, replace: bool) -> bool {
+        println!("Copy: {:?}, {:?}", source, destination);
+        if self.try_expire(source) { return false }
+        if !replace && self.exists(destination) { return false }
+
+        let Some(value) = self.store.get(source).cloned() else { return false };
+
+        match self.ttl_store.get(source).copied() {
+            Some(ttl) => self.set_ttl(destination, ttl),
+            None => { self.remove_ttl(destination); },
+        }
+        self.store.insert(destination.clone(), value);
+        self.signal_ready(destination);
+        true
+    }
+
+    /**
+     * Returns whether the deadline was applied, given the flag conditions
+     * A deadline already in the past deletes the key
+     */
+    pub fn expire(&mut self, key: &Bytes, deadline: EpochMillisecond, flags: &ExpireCommandFlags) -> bool {
+        println!("Expire: {:?}, {}", key, deadline);
+        let current = match self.expiry(key) {
+            KeyExpiry::Missing => return false,
+            KeyExpiry::Persistent => None,
+            KeyExpiry::At(current) => Some(current),
+        };
+
+        if let (Some(ExpireCommandExistFlag::NX), Some(_))
+            | (Some(ExpireCommandExistFlag::XX), None) = (&flags.exist_flag, current) {
+            return false
+        }
+
+        // Keys without a TTL never expire, so compare as an infinite TTL
+        let current = current.unwrap_or(EpochMillisecond::MAX);
+        match flags.compare_flag {
+            Some(ExpireCommandCompareFlag::GT) if deadline <= current => return false,
+            Some(ExpireCommandCompareFlag::LT) if deadline >= current => return false,
+            _ => {}
+        }
+
+        if deadline <= Self::get_unix_time() {
+            self.del(key);
+        } else {
+            self.set_ttl(key, deadline);
+        }
+        true
+    }
+
+    pub fn expiry(&mut self, key: &Bytes) -> KeyExpiry {
+        if !self.exists(key) {
+            return KeyExpiry::Missing
+        }
+
+        self.ttl_store.get(key).map_or(KeyExpiry::Persistent, |ttl| KeyExpiry::At(*ttl))
+    }
+
+    /**
+     * Returns whether a TTL was removed from an existing key
+     */
+    pub fn persist(&mut self, key: &Bytes) -> bool {
+        if self.try_expire(key) { return false }
+
+        self.remove_ttl(key).is_some()
+    }
+
+    /**
+     * Random live key, cleaning up any expired keys it samples
+     */
+    pub fn random_key(&mut self) -> Option<Bytes> {
+        while !self.store.is_empty() {
+            let index = rand::thread_rng().gen_range(0..self.store.len());
+            let (key, _) = self.store.get_index(index)?;
+            let key = key.clone();
+
+            if !self.try_expire(&key) {
+                return Some(key)
+            }
+        }
+        None
+    }
+
+    /**
+     * Number of keys, including expired keys not yet cleaned up
+     */
+    pub fn dbsize(&self) -> usize {
+        self.store.len()
+    }
+
+    /**
+     * Deletes due keys in deadline order, within the time budget
+     * Returns the number of keys deleted
+     */
+    pub fn active_expire_cycle(&mut self) -> usize {
+        // Budget is real time spent holding the lock, unlike the key deadlines
+        let started = Instant::now();
+        let now = Self::get_unix_time();
+        let mut expired = 0;
+
+        while started.elapsed() < ACTIVE_EXPIRE_TIME_BUDGET {
+            match self.expiry_index.first() {
+                Some((deadline, _)) if *deadline <= now => {
+                    let (_, key) = self.expiry_index.pop_first().unwrap();
+                    self.ttl_store.remove(&key);
+                    self.store.swap_remove(&key);
+                    expired += 1;
+                },
+                _ => break
+            }
+        }
+        expired
+    }
+
+    fn get_string(&mut self, key: &Bytes) -> Result<Option<&StringValue>, RedisError> {
+        self.value(key).map(RedisValue::as_string).transpose()
+    }
+
+    /**
+     * Returns true if key has expired.
+     * Cleans up store passively.
+     */
+    fn try_expire(&mut self, key: &Bytes) -> bool {
+        if let Some(ttl) = self.ttl_store.get(key).copied() {
+            if Self::get_unix_time() >= ttl {
+                // Clean up expired key
+                println!("Cleaning up for expired key {:?}: {}", key, ttl);
+                self.remove_ttl(key);
+                self.store.swap_remove(key);
+
+                return true
+            }
+        }
+        false
+    }
+
+    fn set_ttl(&mut self, key: &Bytes, ttl: EpochMillisecond) {
+        if let Some(previous) = self.ttl_store.insert(key.clone(), ttl) {
+            self.expiry_index.remove(&(previous, key.clone()));
+        }
+        self.expiry_index.insert((ttl, key.clone()));
+    }
+
+    fn remove_ttl(&mut self, key: &Bytes) -> Option<EpochMillisecond> {
+        let ttl = self.ttl_store.remove(key)?;
+        self.expiry_index.remove(&(ttl, key.clone()));
+        Some(ttl)
+    }
+
+    pub fn get_unix_time() -> EpochMillisecond {
+        Clock::now()
+            .duration_since(UNIX_EPOCH).unwrap()
+            .as_millis() as EpochMillisecond
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use crate::{clock::{Clock, MockClockSession}, resp::command::{SetCommandFlags, SetCommandTTLFlag}};
+    use super::*;
+
+    fn set_with_ttl(store: &mut RedisStore, key: &str, milliseconds: u64) {
+        let flags = SetCommandFlags { ttl_flag: Some(SetCommandTTLFlag::PX(milliseconds)), ..Default::default() };
+        store.set(&Bytes::from(key.to_owned()), &Bytes::from("value"), &flags);
+    }
+
+    fn assert_index_consistent(store: &RedisStore) {
+        let indexed: HashMap<Bytes, EpochMillisecond> = store.expiry_index.iter()
+            .map(|(deadline, key)| (key.clone(), *deadline))
+            .collect();
+
+        assert_eq!(store.ttl_store, indexed);
+        assert_eq!(store.ttl_store.len(), store.expiry_index.len());
+    }
+
+    #[test]
+    fn active_expire_cycle_should_remove_due_keys_in_deadline_order() {
+        let _session = MockClockSession::new();
+        Clock::mock_freeze();
+        let mut store = RedisStore::default();
+
+        for i in 0..1000 {
+            set_with_ttl(&mut store, &format!("expiring_{}", i), 100 + i);
+        }
+        store.set(&Bytes::from("persistent"), &Bytes::from("value"), &SetCommandFlags::default());
+
+        assert_eq!(0, store.active_expire_cycle());
+
+        Clock::mock_advance(Duration::from_millis(599));
+        assert_eq!(500, store.active_expire_cycle());
+        assert!(!store.exists(&Bytes::from("expiring_499")));
+        assert!(store.exists(&Bytes::from("expiring_500")));
+        assert_index_consistent(&store);
+
+        Clock::mock_advance(Duration::from_millis(500));
+        assert_eq!(500, store.active_expire_cycle());
+        assert_eq!(1, store.dbsize());
+        assert!(store.expiry_index.is_empty());
+    }
+
+    #[test]
+    fn should_encode_canonical_integers() {
+        let mut store = RedisStore::default();
+
+        for (value, expected) in [
+            ("123", StringValue::Int(123)),
+            ("-9223372036854775808", StringValue::Int(i64::MIN)),
+            ("0123", StringValue::Raw(BytesMut::from("0123"))),
+            ("+1", StringValue::Raw(BytesMut::from("+1"))),
+            ("-0", StringValue::Raw(BytesMut::from("-0"))),
+            ("9223372036854775808", StringValue::Raw(BytesMut::from("9223372036854775808"))),
+        ] {
+            let key = Bytes::from(value);
+            store.set(&key, &Bytes::from(value), &SetCommandFlags::default());
+
+            assert_eq!(Some(&RedisValue::String(expected)), store.store.get(&key));
+            assert_eq!(Some(Bytes::from(value)), store.get(&key).unwrap());
+        }
+    }
+
+    #[test]
+    fn should_edit_strings_in_place() {
+        let mut store = RedisStore::default();
+        let key = Bytes::from("in_place_key");
+        store.set(&key, &Bytes::from("12"), &SetCommandFlags::default());
+
+        assert_eq!(Ok(3), store.append(&key, &Bytes::from("3")));
+        assert_eq!(Some(&RedisValue::String(StringValue::Raw(BytesMut::from("123")))), store.store.get(&key));
+        assert_eq!(Ok(124), store.incr_by(&key, 1));
+
+        assert_eq!(Ok(5), store.setrange(&key, 3, &Bytes::from("xy")));
+        assert_eq!(Some(Bytes::from("124xy")), store.get(&key).unwrap());
+        assert_eq!(Err(RedisError::NotInteger), store.incr_by(&key, 1));
+    }
+
+    #[test]
+    fn string_accessors_should_reject_other_types() {
+        let mut store = RedisStore::default();
+        let key = Bytes::from("list_key");
+        store.insert_value(&key, RedisValue::List(vec![Bytes::from("a")].into_iter().collect()));
+
+        assert_eq!("list", store.type_name(&key));
+        assert_eq!(Some("listpack"), store.encoding(&key));
+        assert_eq!(Err(RedisError::WrongType), store.get(&key));
+        assert_eq!(Err(RedisError::WrongType), store.incr_by(&key, 1));
+        assert_eq!(Err(RedisError::WrongType), store.incr_by_float(&key, 1.0));
+        assert_eq!(Err(RedisError::WrongType), store.append(&key, &Bytes::from("b")));
+        assert_eq!(Err(RedisError::WrongType), store.strlen(&key));
+        assert_eq!(Err(RedisError::WrongType), store.setrange(&key, 0, &Bytes::from("b")));
+
+        // Moving keys keeps their type, and SET replaces any type
+        let other_key = Bytes::from("other_list_key");
+        store.rename(&key, &other_key);
+        assert_eq!("list", store.type_name(&other_key));
+        store.set(&other_key, &Bytes::from("1"), &SetCommandFlags::default());
+        assert_eq!(Some("int"), store.encoding(&other_key));
+    }
+
+    #[test]
+    fn active_expire_cycle_should_handle_empty_store() {
+        assert_eq!(0, RedisStore::default().active_expire_cycle());
+    }
+
+    #[test]
+    fn expiry_index_should_follow_ttl_changes() {
+        let _session = MockClockSession::new();
+        Clock::mock_freeze();
+        let mut store = RedisStore::default();
+        let key = Bytes::from("index_key");
+        let other_key = Bytes::from("index_other_key");
+
+        set_with_ttl(&mut store, "index_key", 100);
+        assert_index_consistent(&store);
+
+        // Overwriting with a new TTL replaces the old deadline
+        set_with_ttl(&mut store, "index_key", 200);
+        assert_index_consistent(&store);
+        assert_eq!(1, store.expiry_index.len());
+
+        // KEEPTTL leaves the deadline in place
+        let keepttl = SetCommandFlags { ttl_flag: Some(SetCommandTTLFlag::KEEPTTL), ..Default::default() };
+        store.set(&key, &Bytes::from("kept"), &keepttl);
+        assert_index_consistent(&store);
+        assert_eq!(1, store.expiry_index.len());
+
+        store.rename(&key, &other_key);
+        assert_index_consistent(&store);
+        store.copy(&other_key, &key, false);
+        assert_index_consistent(&store);
+        assert_eq!(2, store.expiry_index.len());
+
+        store.persist(&key);
+        assert_index_consistent(&store);
+        store.expire(&key, RedisStore::get_unix_time() + 50, &Default::default());
+        assert_index_consistent(&store);
+
+        // Overwriting without a TTL and deleting both drop the deadline
+        store.set(&key, &Bytes::from("plain"), &SetCommandFlags::default());
+        store.del(&other_key);
+        assert_index_consistent(&store);
+        assert!(store.expiry_index.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod concurrent_tests {
+    use loom::cell::UnsafeCell;
+    use loom::sync::{Arc, Mutex};
+    use loom::thread::{self, JoinHandle};
+    use bytes::Bytes;
+
+    use crate::resp::command::SetCommandFlags;
+    use crate::store::RedisStore;
+
+    #[test]
+    #[should_panic]
+    fn simulate_non_thread_safe_store_writers() {
+        // Two writers accessing store through pointers with no thread safety
+        loom::model(|| {
+            let shared_store = Arc::new(UnsafeCell::new(RedisStore::default()));
+
+            let threads: Vec<_> = (0..2)
+                .map(|_| {
+                    let store = shared_store.clone();
+
+                    thread::spawn(move || unsafe {
+                        store.get_mut().deref().set(&Bytes::from("buggy_concurrent_key"), &Bytes::from("assigned"), &SetCommandFlags::default());
+                    })
+                })
+                .collect();
+            
+            join_all(threads);
+
+            unsafe {
+                assert_eq!(Some(Bytes::from("assigned")), shared_store.get_mut().deref().get(&Bytes::from("buggy_concurrent_key")).unwrap());
+            }
+        });
+    }
+
+    #[test]
+    fn simulate_thread_safe_store_writers() {
+        // Two writers accessing store through pointers with thread safety
+        loom::model(|| {
+            let shared_store = Arc::new(Mutex::new(RedisStore::default()));
+
+            let threads: Vec<_> = (0..2)
+                .map(|_| {
+                    let store = shared_store.clone();
+
+                    thread::spawn(move || {
+                        store.lock().unwrap().set(&Bytes::from("concurrent_key"), &Bytes::from("assigned"), &SetCommandFlags::default());
+                    })
+                })
+                .collect();
+            
+            join_all(threads);
+
+            assert_eq!(Some(Bytes::from("assigned")), shared_store.lock().unwrap().get(&Bytes::from("concurrent_key")).unwrap());
+        });
+    }
+
+
+    #[test]
+    #[should_panic]
+    fn simulate_non_thread_safe_store_reader_writer() {
+        // A reader and writer accessing store through pointers with no thread safety
+        loom::model(|| {
+            let shared_store = Arc::new(UnsafeCell::new(RedisStore::default()));
+
+            let store_writer = shared_store.clone();
+            let store_reader = shared_store.clone();
+
+            let threads: Vec<_> = vec![
+                // Writer
+                thread::spawn(move || unsafe {
+                    store_writer.get_mut().deref().set(&Bytes::from("buggy_concurrent_key"), &Bytes::from("assigned"), &SetCommandFlags::default());
+                }),
+                // Reader
+                thread::spawn(move || unsafe {
+                    let _ = store_reader.get_mut().deref().get(&Bytes::from("buggy_concurrent_key"));
+                }),
+            ];
+            
+            join_all(threads);
+
+            unsafe {
+                assert_eq!(Some(Bytes::from("assigned")), shared_store.get_mut().deref().get(&Bytes::from("buggy_concurrent_key")).unwrap());
+            }
+        });
+    }
+
+    #[test]
+    fn simulate_thread_safe_store_reader_writer() {
+        // A reader and writer accessing store through pointers with thread safety
+        loom::model(|| {
+            let shared_store = Arc::new(Mutex::new(RedisStore::default()));
+
+            let store_reader = shared_store.clone();
+            let store_writer = shared_store.clone();
+
+            let threads: Vec<_> = vec![
+                // Writer
+                thread::spawn(move || {
+                    store_writer.lock().unwrap().set(&Bytes::from("concurrent_key"), &Bytes::from("assigned"), &SetCommandFlags::default());
+                }),
+                // Reader
+                thread::spawn(move || {
+                    let _ = store_reader.lock().unwrap().get(&Bytes::from("concurrent_key"));
+                }),
+            ];
+            
+            join_all(threads);
+
+            assert_eq!(Some(Bytes::from("assigned")), shared_store.lock().unwrap().get(&Bytes::from("concurrent_key")).unwrap());
+        });
+    }
+
+    fn join_all(threads: Vec<JoinHandle<()>>) {
+        threads.into_iter().for_each(|thread| thread.join().unwrap());
+    }
+}
@@ -0,0 +1,253 @@
+use std::{borrow::Cow, collections::{BTreeMap, HashMap, HashSet}};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{error::RedisError, resp::command::canonical_integer};
+
+use super::{hash::HashValue, list::QuickList};
+
+// Longest string stored alongside its header, as reported by OBJECT ENCODING
+const EMBSTR_MAX_LENGTH: usize = 44;
+// Limits for the compact encodings of small collections
+pub(super) const LISTPACK_MAX_ENTRIES: usize = 128;
+pub(super) const LISTPACK_MAX_VALUE_LENGTH: usize = 64;
+const INTSET_MAX_ENTRIES: usize = 512;
+
+/**
+ * String value, encoded as an integer when it is one in canonical form
+ * Raw strings are kept in a growable buffer so they can be edited in place
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StringValue {
+    Int(i64),
+    Raw(BytesMut),
+}
+
+impl StringValue {
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        canonical_integer(bytes).map_or_else(|| StringValue::Raw(BytesMut::from(bytes)), StringValue::Int)
+    }
+
+    pub fn to_bytes(&self) -> Bytes {
+        Bytes::copy_from_slice(&self.as_bytes())
+    }
+
+    /**
+     * Borrows a raw string, and only formats an integer
+     */
+    pub fn as_bytes(&self) -> Cow<'_, [u8]> {
+        match self {
+            StringValue::Int(n) => Cow::Owned(n.to_string().into_bytes()),
+            StringValue::Raw(bytes) => Cow::Borrowed(bytes),
+        }
+    }
+
+    /**
+     * Buffer to edit in place, converting an integer to its raw form first
+     */
+    pub fn make_raw(&mut self) -> &mut BytesMut {
+        if let StringValue::Int(n) = self {
+            *self = StringValue::Raw(BytesMut::from(n.to_string().as_str()));
+        }
+
+        match self {
+            StringValue::Raw(bytes) => bytes,
+            StringValue::Int(_) => unreachable!(),
+        }
+    }
+}
+
+pub type SetValue = HashSet<Bytes>;
+pub type SortedSetValue = HashMap<Bytes, f64>;
+
+/**
+ * Stream entry ID, as milliseconds and sequence number
+ */
+pub type StreamId = (u64, u64);
+pub type StreamValue = BTreeMap<StreamId, Vec<(Bytes, Bytes)>>;
+
+/**
+ * Value held at a key
+ */
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum RedisValue {
+    String(StringValue),
+    List(QuickList),
+    Hash(HashValue),
+    Set(SetValue),
+    SortedSet(SortedSetValue),
+    Stream(StreamValue),
+}
+
+impl RedisValue {
+    /**
+     * Name reported by TYPE
+     */
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            RedisValue::String(_) => "string",
+            RedisValue::List(_) => "list",
+            RedisValue::Hash(_) => "hash",
+            RedisValue::Set(_) => "set",
+            RedisValue::SortedSet(_) => "zset",
+            RedisValue::Stream(_) => "stream",
+        }
+    }
+
+    /**
+     * Internal representation reported by OBJECT ENCODING
+     */
+    pub fn encoding(&self) -> &'static str {
+        match self {
+            RedisValue::String(StringValue::Int(_)) => "int",
+            RedisValue::String(StringValue::Raw(bytes)) if bytes.len() <= EMBSTR_MAX_LENGTH => "embstr",
+            RedisValue::String(StringValue::Raw(_)) => "raw",
+            RedisValue::List(list) => list.encoding(),
+            RedisValue::Hash(hash) => hash.encoding(),
+            RedisValue::Set(set) if set.len() <= INTSET_MAX_ENTRIES && set.iter().all(|member| canonical_integer(member).is_some()) => "intset",
+            RedisValue::Set(set) if fits_listpack(set.len(), set.iter()) => "listpack",
+            RedisValue::Set(_) => "hashtable",
+            RedisValue::SortedSet(zset) if fits_listpack(zset.len(), zset.keys()) => "listpack",
+            RedisValue::SortedSet(_) => "skiplist",
+            RedisValue::Stream(_) => "stream",
+        }
+    }
+
+    pub fn as_string(&self) -> Result<&StringValue, RedisError> {
+        match self {
+            RedisValue::String(string) => Ok(string),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+
+    pub fn as_string_mut(&mut self) -> Result<&mut StringValue, RedisError> {
+        match self {
+            RedisValue::String(string) => Ok(string),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+
+    pub fn as_list(&self) -> Result<&QuickList, RedisError> {
+        match self {
+            RedisValue::List(list) => Ok(list),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+
+    pub fn as_list_mut(&mut self) -> Result<&mut QuickList, RedisError> {
+        match self {
+            RedisValue::List(list) => Ok(list),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+
+    pub fn as_hash(&self) -> Result<&HashValue, RedisError> {
+        match self {
+            RedisValue::Hash(hash) => Ok(hash),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+
+    pub fn as_hash_mut(&mut self) -> Result<&mut HashValue, RedisError> {
+        match self {
+            RedisValue::Hash(hash) => Ok(hash),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+}
+
+/**
+ * Accessors for the other collection types, each failing on a value of another type
+ */
+#[allow(dead_code)]
+impl RedisValue {
+    pub fn as_set(&self) -> Result<&SetValue, RedisError> {
+        match self {
+            RedisValue::Set(set) => Ok(set),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+
+    pub fn as_set_mut(&mut self) -> Result<&mut SetValue, RedisError> {
+        match self {
+            RedisValue::Set(set) => Ok(set),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+
+    pub fn as_sorted_set(&self) -> Result<&SortedSetValue, RedisError> {
+        match self {
+            RedisValue::SortedSet(zset) => Ok(zset),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+
+    pub fn as_sorted_set_mut(&mut self) -> Result<&mut SortedSetValue, RedisError> {
+        match self {
+            RedisValue::SortedSet(zset) => Ok(zset),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+
+    pub fn as_stream(&self) -> Result<&StreamValue, RedisError> {
+        match self {
+            RedisValue::Stream(stream) => Ok(stream),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+
+    pub fn as_stream_mut(&mut self) -> Result<&mut StreamValue, RedisError> {
+        match self {
+            RedisValue::Stream(stream) => Ok(stream),
+            _ => Err(RedisError::WrongType),
+        }
+    }
+}
+
+fn fits_listpack<'a>(entries: usize, mut values: impl Iterator<Item = &'a Bytes>) -> bool {
+    entries <= LISTPACK_MAX_ENTRIES && values.all(|value| value.len() <= LISTPACK_MAX_VALUE_LENGTH)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn bytes(values: &[&str]) -> Vec<Bytes> {
+        values.iter().map(|value| Bytes::from(value.to_string())).collect()
+    }
+
+    #[rstest]
+    #[case(RedisValue::String(StringValue::Int(1)), "string", "int")]
+    #[case(RedisValue::String(StringValue::Raw(BytesMut::from("a".repeat(44).as_str()))), "string", "embstr")]
+    #[case(RedisValue::String(StringValue::Raw(BytesMut::from("a".repeat(45).as_str()))), "string", "raw")]
+    #[case(RedisValue::List(bytes(&["a", "b"]).into_iter().collect()), "list", "listpack")]
+    #[case(RedisValue::List(vec![Bytes::from("a".repeat(8193))].into_iter().collect()), "list", "quicklist")]
+    #[case(RedisValue::Hash(vec![(Bytes::from("field"), Bytes::from("value"))].into_iter().collect()), "hash", "listpack")]
+    #[case(RedisValue::Hash(vec![(Bytes::from("field"), Bytes::from("a".repeat(65)))].into_iter().collect()), "hash", "hashtable")]
+    #[case(RedisValue::Set(bytes(&["1", "2"]).into_iter().collect()), "set", "intset")]
+    #[case(RedisValue::Set(bytes(&["1", "a"]).into_iter().collect()), "set", "listpack")]
+    #[case(RedisValue::Set((0..129).map(|i| Bytes::from(format!("m{}", i))).collect()), "set", "hashtable")]
+    #[case(RedisValue::SortedSet(HashMap::from([(Bytes::from("member"), 1.0)])), "zset", "listpack")]
+    #[case(RedisValue::SortedSet((0..129).map(|i| (Bytes::from(format!("m{}", i)), 1.0)).collect()), "zset", "skiplist")]
+    #[case(RedisValue::Stream(BTreeMap::new()), "stream", "stream")]
+    fn should_report_type_and_encoding(#[case] value: RedisValue, #[case] type_name: &str, #[case] encoding: &str) {
+        assert_eq!(type_name, value.type_name());
+        assert_eq!(encoding, value.encoding());
+    }
+
+    #[test]
+    fn accessors_should_reject_other_types() {
+        let mut list = RedisValue::List(bytes(&["a"]).into_iter().collect());
+
+        assert!(list.as_list().is_ok());
+        assert!(list.as_list_mut().is_ok());
+        assert!(matches!(list.as_string(), Err(RedisError::WrongType)));
+        assert!(matches!(list.as_hash_mut(), Err(RedisError::WrongType)));
+        assert!(matches!(list.as_set(), Err(RedisError::WrongType)));
+        assert!(matches!(list.as_sorted_set(), Err(RedisError::WrongType)));
+        assert!(matches!(list.as_stream(), Err(RedisError::WrongType)));
+    }
+}